@@ -8,6 +8,7 @@ perl -i -pe 's/\["cdylib", "rlib"\]/\["cdylib"\]/' contract/Cargo.toml
 
 RUSTFLAGS='-C link-arg=-s' cargo build --all --target wasm32-unknown-unknown --release
 cp $TARGET/wasm32-unknown-unknown/release/test_oracle.wasm ./res/
+cp $TARGET/wasm32-unknown-unknown/release/test_flash_loan_receiver.wasm ./res/
 cp $TARGET/wasm32-unknown-unknown/release/contract.wasm ./res/nearlend_protocol.wasm
 
 perl -i -pe 's/\["cdylib"\]/\["cdylib", "rlib"\]/' contract/Cargo.toml
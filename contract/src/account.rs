@@ -7,13 +7,19 @@ use crate::*;
 pub struct Account {
     /// A copy of an account ID. Saves one storage_read when iterating on accounts.
     pub account_id: AccountId,
-    /// A list of assets that are supplied by the account used as collateral.
+    /// A list of assets that are supplied by the account (but not used a collateral).
     /// It's not returned for account pagination.
     pub supplied: HashMap<TokenId, Shares>,
 
+    /// A list of assets that are supplied by the account used as collateral.
+    pub collateral: HashMap<TokenId, Shares>,
+
     /// A list of borrowed assets.
     pub borrowed: HashMap<TokenId, Shares>,
 
+    /// A list of assets that are borrowed at a stable rate.
+    pub stable_borrowed: HashMap<TokenId, StableDebt>,
+
     // A list of NFT assets that are supplied by the account used as a collateral.
     pub nft_supplied: HashMap<NFTContractTokenId, AccountNFTAsset>,
 
@@ -32,26 +38,46 @@ pub struct Account {
 
     /// Staking of booster token.
     pub booster_staking: Option<BoosterStaking>,
+
+    /// The e-mode category the account opted into.
+    pub e_mode_category_id: Option<EModeCategoryId>,
+
+    /// The amounts of the assets other accounts can borrow against the collateral of this account,
+    /// by the delegatee account ID.
+    pub borrow_allowances: HashMap<AccountId, HashMap<TokenId, U128>>,
+
+    /// The ID of this position, or `None` for the main account.
+    pub position_id: Option<PositionId>,
+
+    /// The IDs of the open positions of the main account.
+    pub position_ids: Vec<PositionId>,
+
+    /// The ID of the last position opened by the main account. The IDs of the closed positions
+    /// are not reused.
+    pub last_position_id: PositionId,
 }
 
 #[derive(BorshSerialize, BorshDeserialize)]
 pub enum VAccount {
     V0(AccountV0),
-    Current(Account),
+    V1(AccountV1),
+    /// Boxed, since it's much larger than the legacy versions. Borsh serializes `Box<T>` as `T`.
+    Current(Box<Account>),
 }
 
 impl From<VAccount> for Account {
     fn from(v: VAccount) -> Self {
         match v {
             VAccount::V0(c) => c.into(),
-            VAccount::Current(c) => c,
+            VAccount::V1(c) => c.into(),
+            VAccount::Current(c) => *c,
         }
     }
 }
 
 impl From<Account> for VAccount {
     fn from(c: Account) -> Self {
-        VAccount::Current(c)
+        VAccount::Current(Box::new(c))
     }
 }
 
@@ -60,15 +86,50 @@ impl Account {
         Self {
             account_id: account_id.clone(),
             supplied: HashMap::new(),
+            collateral: HashMap::new(),
             nft_supplied: HashMap::new(),
             borrowed: HashMap::new(),
+            stable_borrowed: HashMap::new(),
             farms: HashMap::new(),
             affected_farms: HashSet::new(),
             storage_tracker: Default::default(),
             booster_staking: None,
+            e_mode_category_id: None,
+            borrow_allowances: HashMap::new(),
+            position_id: None,
+            position_ids: vec![],
+            last_position_id: 0,
         }
     }
 
+    pub fn increase_collateral(&mut self, token_id: &TokenId, shares: Shares) {
+        self.collateral
+            .entry(token_id.clone())
+            .or_insert_with(|| 0.into())
+            .0 += shares.0;
+    }
+
+    pub fn decrease_collateral(&mut self, token_id: &TokenId, shares: Shares) {
+        let current_collateral = self.internal_unwrap_collateral(token_id);
+        if let Some(new_balance) = current_collateral.0.checked_sub(shares.0) {
+            if new_balance > 0 {
+                self.collateral
+                    .insert(token_id.clone(), Shares::from(new_balance));
+            } else {
+                self.collateral.remove(token_id);
+            }
+        } else {
+            env::panic_str("Not enough collateral balance");
+        }
+    }
+
+    pub fn internal_unwrap_collateral(&mut self, token_id: &TokenId) -> Shares {
+        *self
+            .collateral
+            .get(token_id)
+            .expect("Collateral asset not found")
+    }
+
     pub fn increase_borrowed(&mut self, token_id: &TokenId, shares: Shares) {
         self.borrowed
             .entry(token_id.clone())
@@ -105,6 +166,8 @@ impl Account {
     pub fn get_all_potential_farms(&self) -> HashSet<FarmId> {
         let mut potential_farms = HashSet::new();
         potential_farms.extend(self.supplied.keys().cloned().map(FarmId::Supplied));
+        potential_farms.extend(self.collateral.keys().cloned().map(FarmId::Supplied));
+        potential_farms.extend(self.collateral.keys().cloned().map(FarmId::Collateral));
         potential_farms.extend(self.borrowed.keys().cloned().map(FarmId::Borrowed));
         let nft_farmids = self.nft_supplied.keys().cloned().map(|s| {
             FarmId::SuppliedNFT(AccountId::new_unchecked(
@@ -116,12 +179,20 @@ impl Account {
         potential_farms
     }
 
+    /// Returns the total number of supplied shares including shares used as collateral.
     pub fn get_supplied_shares(&self, token_id: &TokenId) -> Shares {
         let supplied_shares = self
             .internal_get_asset(token_id)
             .map(|asset| asset.shares.0)
             .unwrap_or(0);
-        supplied_shares.into()
+        (supplied_shares + self.get_collateral_shares(token_id).0).into()
+    }
+
+    pub fn get_collateral_shares(&self, token_id: &TokenId) -> Shares {
+        self.collateral
+            .get(token_id)
+            .cloned()
+            .unwrap_or_else(|| 0.into())
     }
 
     pub fn get_borrowed_shares(&self, token_id: &TokenId) -> Shares {
@@ -160,6 +231,7 @@ impl Contract {
             .storage_tracker
             .consume(&mut account.storage_tracker);
         storage.storage_tracker.start();
+        self.internal_update_borrowers(account_id, &account);
         self.accounts.insert(account_id, &account.into());
         storage.storage_tracker.stop();
         self.internal_set_storage(account_id, storage);
@@ -208,12 +280,19 @@ impl Contract {
                     Account {
                         account_id,
                         supplied: _supplied,
+                        collateral: _collateral,
                         borrowed: _borrowed,
+                        stable_borrowed: _stable_borrowed,
                         nft_supplied: _nft_supplied,
                         farms: _farms,
                         affected_farms: _affected_farms,
                         storage_tracker: _storage_tracker,
                         booster_staking: _booster_staking,
+                        e_mode_category_id: _e_mode_category_id,
+                        borrow_allowances: _borrow_allowances,
+                        position_id: _position_id,
+                        position_ids: _position_ids,
+                        last_position_id: _last_position_id,
                     },
                 )| {
                     let account: Account = self.accounts.get(&account_id).unwrap().into();
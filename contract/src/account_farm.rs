@@ -18,6 +18,7 @@ pub enum FarmId {
     Supplied(TokenId),
     Borrowed(TokenId),
     SuppliedNFT(TokenId),
+    Collateral(TokenId),
 }
 
 impl FarmId {
@@ -26,6 +27,7 @@ impl FarmId {
             FarmId::Supplied(token_id) => token_id,
             FarmId::Borrowed(token_id) => token_id,
             FarmId::SuppliedNFT(token_id) => token_id,
+            FarmId::Collateral(token_id) => token_id,
         }
     }
 }
@@ -190,6 +192,7 @@ impl Contract {
             let shares = match &farm_id {
                 FarmId::Supplied(token_id) => account.get_supplied_shares(token_id).0,
                 FarmId::Borrowed(token_id) => account.get_borrowed_shares(token_id).0,
+                FarmId::Collateral(token_id) => account.get_collateral_shares(token_id).0,
                 FarmId::SuppliedNFT(nft_contract_id) => {
                     let nft_shares = account.get_nft_supplied_shares(nft_contract_id);
                     nft_shares.0
@@ -237,6 +240,8 @@ impl Contract {
     /// If the account_id is given, then it claims farms for the given account_id or uses
     /// predecessor_account_id otherwise.
     pub fn account_farm_claim_all(&mut self, account_id: Option<AccountId>) {
+        self.assert_not_paused();
+        self.assert_operation_not_paused(PausableOperation::FarmClaim);
         let account_id = account_id.unwrap_or_else(env::predecessor_account_id);
         let mut account = self.internal_unwrap_account(&account_id);
         account
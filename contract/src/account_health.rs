@@ -0,0 +1,230 @@
+use crate::*;
+
+/// The values of the positions of an account in one asset, in USD.
+#[derive(Serialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug, Deserialize))]
+#[serde(crate = "near_sdk::serde")]
+pub struct AssetHealthView {
+    pub token_id: TokenId,
+    /// The value of the collateral, including the supplied NFTs of this NFT contract.
+    pub collateral_value: BigDecimal,
+    /// The collateral value multiplied by the collateral ratio. It's 0, if the asset can't be
+    /// used as a collateral.
+    pub adjusted_collateral_value: BigDecimal,
+    /// The value of the borrowed assets, including the stable debt.
+    pub borrowed_value: BigDecimal,
+    /// The borrowed value divided by the borrow ratio.
+    pub adjusted_borrowed_value: BigDecimal,
+    /// The amount of the asset the account can borrow in addition, without failing the risk
+    /// check. It's limited by the available liquidity and the borrow cap of the asset.
+    #[serde(with = "u128_dec_format")]
+    pub max_borrow_amount: Balance,
+}
+
+#[derive(Serialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug, Deserialize))]
+#[serde(crate = "near_sdk::serde")]
+pub struct AccountHealthView {
+    pub account_id: AccountId,
+    /// The assets the account has positions in, followed by the other assets with the price, that
+    /// can be borrowed.
+    pub assets: Vec<AssetHealthView>,
+    pub collateral_sum: BigDecimal,
+    pub adjusted_collateral_sum: BigDecimal,
+    pub borrowed_sum: BigDecimal,
+    pub adjusted_borrowed_sum: BigDecimal,
+    /// The adjusted collateral sum divided by the adjusted borrowed sum. The account can be
+    /// liquidated, if it's less than 1. It's `None`, if the account has no debt.
+    pub health_factor: Option<BigDecimal>,
+    /// The share of the adjusted borrowed sum that is not covered by the adjusted collateral sum,
+    /// halved. It's 0, if the account can't be liquidated. It's not the discount the liquidators
+    /// get: the liquidation uses the `liquidation_bonus` of every seized asset.
+    pub health_deficit: BigDecimal,
+}
+
+fn asset_health_view<'a>(
+    assets: &'a mut Vec<AssetHealthView>,
+    token_id: &TokenId,
+) -> &'a mut AssetHealthView {
+    let index = match assets.iter().position(|view| &view.token_id == token_id) {
+        Some(index) => index,
+        None => {
+            assets.push(AssetHealthView {
+                token_id: token_id.clone(),
+                collateral_value: BigDecimal::zero(),
+                adjusted_collateral_value: BigDecimal::zero(),
+                borrowed_value: BigDecimal::zero(),
+                adjusted_borrowed_value: BigDecimal::zero(),
+                max_borrow_amount: 0,
+            });
+            assets.len() - 1
+        }
+    };
+    &mut assets[index]
+}
+
+impl Contract {
+    pub fn compute_account_health(
+        &self,
+        account_id: AccountId,
+        account: &Account,
+        prices: &Prices,
+    ) -> AccountHealthView {
+        let e_mode_category = self.internal_get_e_mode_category(account);
+        let mut assets = vec![];
+
+        for (token_id, shares) in account.collateral.iter() {
+            let asset = self.internal_unwrap_asset(token_id);
+            let value = BigDecimal::from_balance_price(
+                asset.supplied.shares_to_amount(*shares, false),
+                prices.get_unwrap(token_id),
+                asset.config.extra_decimals,
+            );
+            let view = asset_health_view(&mut assets, token_id);
+            if asset.config.can_use_as_collateral {
+                view.adjusted_collateral_value = view.adjusted_collateral_value
+                    + value.mul_ratio(get_collateral_ratio(
+                        e_mode_category.as_ref(),
+                        token_id,
+                        &asset.config,
+                    ));
+            }
+            view.collateral_value = view.collateral_value + value;
+        }
+
+        for account_nft_asset in account.nft_supplied.values() {
+            let token_id = &account_nft_asset.nft_contract_id;
+            let asset = self.internal_unwrap_asset(token_id);
+            let nft_valuation = self.internal_get_nft_valuation(token_id);
+            let value = nft_valuation.get_value(
+                &account_nft_asset.nft_token_id,
+                prices.get_unwrap(token_id),
+                asset.config.extra_decimals,
+            );
+            let view = asset_health_view(&mut assets, token_id);
+            if asset.config.can_use_as_collateral {
+                view.adjusted_collateral_value =
+                    view.adjusted_collateral_value
+                        + value.mul_ratio(nft_valuation.get_collateral_ratio(
+                            get_collateral_ratio(e_mode_category.as_ref(), token_id, &asset.config),
+                        ));
+            }
+            view.collateral_value = view.collateral_value + value;
+        }
+
+        let borrowed_amounts = account
+            .borrowed
+            .iter()
+            .map(|(token_id, shares)| {
+                let asset = self.internal_unwrap_asset(token_id);
+                (token_id, asset.borrowed.shares_to_amount(*shares, true))
+            })
+            .chain(
+                account
+                    .stable_borrowed
+                    .iter()
+                    .map(|(token_id, stable_debt)| (token_id, stable_debt.get_amount())),
+            );
+        for (token_id, amount) in borrowed_amounts {
+            let asset = self.internal_unwrap_asset(token_id);
+            let value = BigDecimal::from_balance_price(
+                amount,
+                prices.get_unwrap(token_id),
+                asset.config.extra_decimals,
+            );
+            let view = asset_health_view(&mut assets, token_id);
+            view.adjusted_borrowed_value = view.adjusted_borrowed_value
+                + value.div_ratio(get_borrow_ratio(
+                    e_mode_category.as_ref(),
+                    token_id,
+                    &asset.config,
+                ));
+            view.borrowed_value = view.borrowed_value + value;
+        }
+
+        let sum = |f: fn(&AssetHealthView) -> &BigDecimal| {
+            assets
+                .iter()
+                .fold(BigDecimal::zero(), |sum, view| sum + *f(view))
+        };
+        let collateral_sum = sum(|view| &view.collateral_value);
+        let adjusted_collateral_sum = sum(|view| &view.adjusted_collateral_value);
+        let borrowed_sum = sum(|view| &view.borrowed_value);
+        let adjusted_borrowed_sum = sum(|view| &view.adjusted_borrowed_value);
+
+        let health_factor = if adjusted_borrowed_sum > BigDecimal::zero() {
+            Some(adjusted_collateral_sum / adjusted_borrowed_sum)
+        } else {
+            None
+        };
+        let borrowing_power = if adjusted_collateral_sum > adjusted_borrowed_sum {
+            adjusted_collateral_sum - adjusted_borrowed_sum
+        } else {
+            BigDecimal::zero()
+        };
+        let is_isolated = self.internal_get_isolated_collateral(account).is_some();
+
+        for token_id in self.asset_ids.iter() {
+            let price = match prices.get(&token_id) {
+                Some(price) => price,
+                None => continue,
+            };
+            let asset = self.internal_unwrap_asset(&token_id);
+            let can_borrow = asset.config.can_borrow
+                && (!is_isolated || asset.config.can_borrow_in_isolation)
+                && e_mode_category
+                    .as_ref()
+                    .map(|category| category.contains(&token_id))
+                    .unwrap_or(true);
+            if !can_borrow {
+                continue;
+            }
+            let mut max_borrow_amount = std::cmp::min(
+                borrowing_power
+                    .mul_ratio(get_borrow_ratio(
+                        e_mode_category.as_ref(),
+                        &token_id,
+                        &asset.config,
+                    ))
+                    .to_balance_price(price, asset.config.extra_decimals),
+                asset.available_amount(),
+            );
+            if let Some(borrow_cap) = asset.config.borrow_cap {
+                max_borrow_amount = std::cmp::min(
+                    max_borrow_amount,
+                    borrow_cap.0.saturating_sub(asset.total_borrowed()),
+                );
+            }
+            asset_health_view(&mut assets, &token_id).max_borrow_amount = max_borrow_amount;
+        }
+
+        AccountHealthView {
+            account_id,
+            assets,
+            collateral_sum,
+            adjusted_collateral_sum,
+            borrowed_sum,
+            adjusted_borrowed_sum,
+            health_factor,
+            health_deficit: self.compute_max_discount(account, account, prices),
+        }
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Returns the health of the given account computed with the prices from the given price
+    /// data, including the values of its positions, the health factor, the amounts it can borrow
+    /// in addition and the health deficit.
+    /// The price data is not validated, so it's possible to estimate the health with any prices.
+    /// - Panics if the price of an asset the account has a position in is missing.
+    pub fn get_account_health(
+        &self,
+        account_id: AccountId,
+        price_data: PriceData,
+    ) -> Option<AccountHealthView> {
+        let prices: Prices = price_data.into();
+        self.internal_get_account(&account_id)
+            .map(|account| self.compute_account_health(account_id, &account, &prices))
+    }
+}
@@ -13,6 +13,18 @@ pub struct AssetView {
     pub apr: BigDecimal,
 }
 
+#[derive(Serialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug, Deserialize))]
+#[serde(crate = "near_sdk::serde")]
+pub struct StableDebtView {
+    pub token_id: TokenId,
+    /// The current debt including the accrued interest.
+    #[serde(with = "u128_dec_format")]
+    pub balance: Balance,
+    /// The stable APR fixed for this debt.
+    pub apr: BigDecimal,
+}
+
 #[derive(Serialize)]
 #[cfg_attr(not(target_arch = "wasm32"), derive(Debug, Deserialize))]
 #[serde(crate = "near_sdk::serde")]
@@ -27,18 +39,33 @@ pub struct AssetNFTView {
 #[serde(crate = "near_sdk::serde")]
 pub struct AccountDetailedView {
     pub account_id: AccountId,
-    /// A list of assets that are supplied by the account used as collateral.
+    /// A list of assets that are supplied by the account (but not used a collateral).
     pub supplied: Vec<AssetView>,
+    /// A list of assets that are used as a collateral.
+    pub collateral: Vec<AssetView>,
     /// A list of nft assets that are supplied by the account used a collateral.
     pub nft_supplied: Vec<AssetNFTView>,
     /// A list of assets that are borrowed.
     pub borrowed: Vec<AssetView>,
+    /// A list of assets that are borrowed at a stable rate.
+    pub stable_borrowed: Vec<StableDebtView>,
+    /// A list of collateral and NFT assets that are currently disabled to be used as a collateral.
+    /// They are not counted towards the health factor and can't be liquidated.
+    pub disabled_collateral: Vec<TokenId>,
     /// Account farms
     pub farms: Vec<AccountFarmView>,
     /// Whether the account has assets, that can be farmed.
     pub has_non_farmed_assets: bool,
     /// Staking of booster token.
     pub booster_staking: Option<BoosterStaking>,
+    /// The e-mode category the account opted into.
+    pub e_mode_category_id: Option<EModeCategoryId>,
+    /// The remaining amounts of the assets other accounts can borrow against the collateral.
+    pub borrow_allowances: Vec<BorrowAllowanceView>,
+    /// The ID of this position, or `None` for the main account.
+    pub position_id: Option<PositionId>,
+    /// The IDs of the open positions of the main account.
+    pub position_ids: Vec<PositionId>,
 }
 
 #[derive(Serialize)]
@@ -73,6 +100,12 @@ pub struct AccountSimpleView {
     pub nft: Vec<AssetNFTView>,
     /// A list of borrowed assets.
     pub borrowed: Vec<AssetView>,
+    /// A list of assets borrowed at a stable rate.
+    pub stable_borrowed: Vec<StableDebtView>,
+    /// A list of collateral and NFT assets that are currently disabled to be used as a collateral.
+    pub disabled_collateral: Vec<TokenId>,
+    /// The e-mode category the account opted into.
+    pub e_mode_category_id: Option<EModeCategoryId>,
 }
 
 impl Contract {
@@ -126,6 +159,8 @@ impl Contract {
         let has_non_farmed_assets = potential_farms
             .into_iter()
             .any(|farm_id| self.asset_farms.contains_key(&farm_id));
+        let disabled_collateral = self.get_disabled_collateral(&account);
+        let borrow_allowances = account.get_borrow_allowance_views();
         AccountDetailedView {
             account_id: account.account_id,
             supplied: account
@@ -133,6 +168,11 @@ impl Contract {
                 .into_iter()
                 .map(|(token_id, shares)| self.get_asset_view(token_id, shares, false))
                 .collect(),
+            collateral: account
+                .collateral
+                .into_iter()
+                .map(|(token_id, shares)| self.get_asset_view(token_id, shares, false))
+                .collect(),
             nft_supplied: account
                 .nft_supplied
                 .into_iter()
@@ -147,17 +187,32 @@ impl Contract {
                 .into_iter()
                 .map(|(token_id, shares)| self.get_asset_view(token_id, shares, true))
                 .collect(),
+            stable_borrowed: account
+                .stable_borrowed
+                .into_iter()
+                .map(|(token_id, stable_debt)| StableDebtView {
+                    token_id,
+                    balance: stable_debt.get_amount(),
+                    apr: stable_debt.get_apr(),
+                })
+                .collect(),
+            disabled_collateral,
             farms,
             has_non_farmed_assets,
             booster_staking: account.booster_staking,
+            e_mode_category_id: account.e_mode_category_id,
+            borrow_allowances,
+            position_id: account.position_id,
+            position_ids: account.position_ids,
         }
     }
 
     pub fn account_into_simple_view(&self, account: Account) -> AccountSimpleView {
+        let disabled_collateral = self.get_disabled_collateral(&account);
         AccountSimpleView {
             account_id: account.account_id,
             collateral: account
-                .supplied
+                .collateral
                 .into_iter()
                 .map(|(token_id, shares)| self.get_asset_view(token_id, shares, false))
                 .collect(),
@@ -175,10 +230,53 @@ impl Contract {
                 .into_iter()
                 .map(|(token_id, shares)| self.get_asset_view(token_id, shares, true))
                 .collect(),
+            stable_borrowed: account
+                .stable_borrowed
+                .into_iter()
+                .map(|(token_id, stable_debt)| StableDebtView {
+                    token_id,
+                    balance: stable_debt.get_amount(),
+                    apr: stable_debt.get_apr(),
+                })
+                .collect(),
+            disabled_collateral,
+            e_mode_category_id: account.e_mode_category_id,
         }
     }
 
-    fn get_asset_view(&self, token_id: TokenId, shares: Shares, is_borrowing: bool) -> AssetView {
+    /// Returns the collateral and NFT assets of the account that can't be used as a collateral
+    /// anymore, e.g. because the owner disabled it in the asset config.
+    fn get_disabled_collateral(&self, account: &Account) -> Vec<TokenId> {
+        let mut token_ids: Vec<TokenId> = account
+            .collateral
+            .keys()
+            .cloned()
+            .chain(
+                account
+                    .nft_supplied
+                    .values()
+                    .map(|account_nft_asset| account_nft_asset.nft_contract_id.clone()),
+            )
+            .collect();
+        token_ids.sort();
+        token_ids.dedup();
+        token_ids
+            .into_iter()
+            .filter(|token_id| {
+                !self
+                    .internal_unwrap_asset(token_id)
+                    .config
+                    .can_use_as_collateral
+            })
+            .collect()
+    }
+
+    pub fn get_asset_view(
+        &self,
+        token_id: TokenId,
+        shares: Shares,
+        is_borrowing: bool,
+    ) -> AssetView {
         let asset = self.internal_unwrap_asset(&token_id);
         let apr = if is_borrowing {
             asset.get_borrow_apr()
@@ -23,28 +23,122 @@ pub struct NFTAsset {
     pub token_id: NFTTokenId,
 }
 
+/// The liquidated account or its position, and the assets the liquidator repays and takes.
+pub struct Liquidation {
+    pub account_id: AccountId,
+    pub position_id: Option<PositionId>,
+    pub in_assets: Vec<AssetAmount>,
+    pub out_assets: Vec<AssetAmount>,
+}
+
+/// The liquidated account or its position, and the assets the liquidator repays and the NFTs the
+/// liquidator takes.
+pub struct NftLiquidation {
+    pub account_id: AccountId,
+    pub position_id: Option<PositionId>,
+    pub in_assets: Vec<AssetAmount>,
+    pub out_nft_assets: Vec<NFTAsset>,
+}
+
 #[derive(Deserialize)]
 #[cfg_attr(not(target_arch = "wasm32"), derive(Debug, Serialize))]
 #[serde(crate = "near_sdk::serde")]
 pub enum Action {
     Withdraw(AssetAmount),
     WithdrawNFT(NFTAsset),
+    IncreaseCollateral(AssetAmount),
+    DecreaseCollateral(AssetAmount),
     Borrow(AssetAmount),
     Repay(AssetAmount),
+    /// Repays the debt of the given account, or its position if the position ID is given, from
+    /// the supplied balance of the caller. The variable debt is repaid first, the stable debt is
+    /// repaid once there is no variable debt in the asset. The collateral of the given account is
+    /// not affected.
+    RepayFor {
+        account_id: AccountId,
+        position_id: Option<PositionId>,
+        asset_amount: AssetAmount,
+    },
+    /// Borrows the asset at the stable rate fixed at the current borrowing rate.
+    BorrowStable(AssetAmount),
+    /// Sets the amount of the asset the delegatee can borrow against the collateral of the
+    /// account. The debt is recorded on the account. The zero allowance removes the approval.
+    ApproveBorrowAllowance {
+        delegatee_id: AccountId,
+        token_id: TokenId,
+        allowance: U128,
+    },
+    /// Borrows the asset against the collateral of the delegator within the allowance approved by
+    /// the delegator. The borrowed amount is deposited to the supplied balance of the account,
+    /// while the debt is recorded on the delegator.
+    BorrowDelegated {
+        delegator_id: AccountId,
+        asset_amount: AssetAmount,
+    },
+    /// Repays the stable debt from the supplied balance.
+    RepayStable(AssetAmount),
+    /// Liquidates the account, or its position if the position ID is given.
     Liquidate {
         account_id: AccountId,
+        position_id: Option<PositionId>,
         in_assets: Vec<AssetAmount>,
         out_assets: Vec<AssetAmount>,
     },
+    /// Liquidates the NFT collateral of the account, or its position if the position ID is given.
     LiquidateNFT {
         account_id: AccountId,
+        position_id: Option<PositionId>,
         in_assets: Vec<AssetAmount>,
         out_nft_assets: Vec<NFTAsset>,
     },
     /// If the sum of borrowed assets exceeds the collateral, the account will be liquidated
-    /// using reserves.
+    /// using reserves. The position of the account is liquidated if the position ID is given.
     ForceClose {
         account_id: AccountId,
+        position_id: Option<PositionId>,
+    },
+    /// Starts a descending-price auction of the NFT collateral of the account at risk, or its
+    /// position if the position ID is given. Requires the NFT auctions to be enabled.
+    StartNftAuction {
+        account_id: AccountId,
+        position_id: Option<PositionId>,
+        nft_asset: NFTAsset,
+    },
+    /// Buys the auctioned NFT at the current auction price with the supplied balance of the given
+    /// asset. The asset has to be borrowed by the NFT owner.
+    /// If `max_amount` is given, the auction price can't be greater than this amount.
+    BuyNftAuction {
+        nft_asset: NFTAsset,
+        token_id: TokenId,
+        max_amount: Option<U128>,
+    },
+    /// Cancels the auction of the NFT, if its owner is no longer at risk.
+    CancelNftAuction(NFTAsset),
+    /// Opts the account into the e-mode category with the given id, or out of the e-mode, if the
+    /// category id is not given. All collateral and borrowed assets of the account have to be in
+    /// the category.
+    SetEMode {
+        category_id: Option<EModeCategoryId>,
+    },
+    /// Borrows the asset and increases the collateral of the asset by the borrowed amount, until
+    /// the collateral is the `target_leverage` multiple of the equity, i.e. the collateral minus
+    /// the variable debt of the asset. The leverage is multiplied by 10000, e.g. `30000` is 3x.
+    Leverage {
+        token_id: TokenId,
+        target_leverage: u32,
+    },
+    /// Decreases the collateral of the asset and repays the variable debt of the asset with it,
+    /// until the collateral is the `target_leverage` multiple of the equity. The leverage of
+    /// `10000` repays the whole debt.
+    Deleverage {
+        token_id: TokenId,
+        target_leverage: u32,
+    },
+    /// Moves the supplied balance of the asset to the given position of the account, or to the
+    /// main account if the position ID is not given.
+    TransferToPosition {
+        position_id: Option<PositionId>,
+        asset_amount: AssetAmount,
     },
 }
 
@@ -64,7 +158,6 @@ impl Contract {
         for action in actions {
             match action {
                 Action::Withdraw(asset_amount) => {
-                    need_risk_check = true;
                     account.add_affected_farm(FarmId::Supplied(asset_amount.token_id.clone()));
                     let amount = self.internal_withdraw(account, &asset_amount);
                     self.internal_ft_transfer(account_id, &asset_amount.token_id, amount);
@@ -85,7 +178,27 @@ impl Contract {
                         &nft_asset.token_id,
                     );
                 }
+                Action::IncreaseCollateral(asset_amount) => {
+                    need_number_check = true;
+                    account.add_affected_farm(FarmId::Collateral(asset_amount.token_id.clone()));
+                    let amount = self.internal_increase_collateral(account, &asset_amount);
+                    events::emit::increase_collateral(account_id, amount, &asset_amount.token_id);
+                }
+                Action::DecreaseCollateral(asset_amount) => {
+                    need_risk_check = true;
+                    account.add_affected_farm(FarmId::Collateral(asset_amount.token_id.clone()));
+                    let mut account_asset =
+                        account.internal_get_asset_or_default(&asset_amount.token_id);
+                    let amount = self.internal_decrease_collateral(
+                        &mut account_asset,
+                        account,
+                        &asset_amount,
+                    );
+                    account.internal_set_asset(&asset_amount.token_id, account_asset);
+                    events::emit::decrease_collateral(account_id, amount, &asset_amount.token_id);
+                }
                 Action::Borrow(asset_amount) => {
+                    self.assert_operation_not_paused(PausableOperation::Borrow);
                     need_number_check = true;
                     need_risk_check_borrow = true;
                     account.add_affected_farm(FarmId::Supplied(asset_amount.token_id.clone()));
@@ -101,11 +214,89 @@ impl Contract {
                     events::emit::repay(account_id, amount, &asset_amount.token_id);
                     account.internal_set_asset(&asset_amount.token_id, account_asset);
                 }
+                Action::RepayFor {
+                    account_id: repay_account_id,
+                    position_id,
+                    asset_amount,
+                } => {
+                    account.add_affected_farm(FarmId::Supplied(asset_amount.token_id.clone()));
+                    let amount = self.internal_repay_for(
+                        account_id,
+                        account,
+                        &repay_account_id,
+                        position_id,
+                        &asset_amount,
+                    );
+                    events::emit::repay_for(
+                        account_id,
+                        &repay_account_id,
+                        position_id,
+                        amount,
+                        &asset_amount.token_id,
+                    );
+                }
+                Action::BorrowStable(asset_amount) => {
+                    self.assert_operation_not_paused(PausableOperation::Borrow);
+                    need_number_check = true;
+                    need_risk_check_borrow = true;
+                    account.add_affected_farm(FarmId::Supplied(asset_amount.token_id.clone()));
+                    let amount = self.internal_borrow_stable(account, &asset_amount);
+                    events::emit::borrow_stable(account_id, amount, &asset_amount.token_id);
+                }
+                Action::ApproveBorrowAllowance {
+                    delegatee_id,
+                    token_id,
+                    allowance,
+                } => {
+                    assert_ne!(account_id, &delegatee_id, "Can't delegate to yourself");
+                    assert!(
+                        account.position_id.is_none(),
+                        "The allowance can only be approved by the main account"
+                    );
+                    self.internal_unwrap_asset(&token_id);
+                    account.set_borrow_allowance(&delegatee_id, &token_id, allowance.0);
+                    events::emit::approve_borrow_allowance(
+                        account_id,
+                        &delegatee_id,
+                        allowance.0,
+                        &token_id,
+                    );
+                }
+                Action::BorrowDelegated {
+                    delegator_id,
+                    asset_amount,
+                } => {
+                    self.assert_operation_not_paused(PausableOperation::Borrow);
+                    account.add_affected_farm(FarmId::Supplied(asset_amount.token_id.clone()));
+                    let amount = self.internal_borrow_delegated(
+                        account_id,
+                        account,
+                        &delegator_id,
+                        &asset_amount,
+                        &prices,
+                    );
+                    events::emit::borrow_delegated(
+                        account_id,
+                        &delegator_id,
+                        amount,
+                        &asset_amount.token_id,
+                    );
+                }
+                Action::RepayStable(asset_amount) => {
+                    let mut account_asset = account.internal_unwrap_asset(&asset_amount.token_id);
+                    account.add_affected_farm(FarmId::Supplied(asset_amount.token_id.clone()));
+                    let amount =
+                        self.internal_repay_stable(&mut account_asset, account, &asset_amount);
+                    events::emit::repay_stable(account_id, amount, &asset_amount.token_id);
+                    account.internal_set_asset(&asset_amount.token_id, account_asset);
+                }
                 Action::Liquidate {
                     account_id: liquidation_account_id,
+                    position_id,
                     in_assets,
                     out_assets,
                 } => {
+                    self.assert_operation_not_paused(PausableOperation::Liquidate);
                     assert_ne!(
                         account_id, &liquidation_account_id,
                         "Can't liquidate yourself"
@@ -116,17 +307,22 @@ impl Contract {
                         account_id,
                         account,
                         &prices,
-                        &liquidation_account_id,
-                        in_assets,
-                        out_assets,
+                        Liquidation {
+                            account_id: liquidation_account_id,
+                            position_id,
+                            in_assets,
+                            out_assets,
+                        },
                     );
                 }
 
                 Action::LiquidateNFT {
                     account_id: liquidation_account_id,
+                    position_id,
                     in_assets,
                     out_nft_assets,
                 } => {
+                    self.assert_operation_not_paused(PausableOperation::Liquidate);
                     assert_ne!(
                         account_id, &liquidation_account_id,
                         "Can't liquidate yourself"
@@ -137,32 +333,123 @@ impl Contract {
                         account_id,
                         account,
                         &prices,
-                        &liquidation_account_id,
-                        in_assets,
-                        out_nft_assets,
+                        NftLiquidation {
+                            account_id: liquidation_account_id,
+                            position_id,
+                            in_assets,
+                            out_nft_assets,
+                        },
                     );
                 }
                 Action::ForceClose {
                     account_id: liquidation_account_id,
+                    position_id,
+                } => {
+                    self.assert_operation_not_paused(PausableOperation::Liquidate);
+                    assert_ne!(
+                        account_id, &liquidation_account_id,
+                        "Can't liquidate yourself"
+                    );
+                    self.internal_force_close(&prices, &liquidation_account_id, position_id);
+                }
+                Action::StartNftAuction {
+                    account_id: liquidation_account_id,
+                    position_id,
+                    nft_asset,
                 } => {
+                    self.assert_operation_not_paused(PausableOperation::Liquidate);
                     assert_ne!(
                         account_id, &liquidation_account_id,
                         "Can't liquidate yourself"
                     );
-                    self.internal_force_close(&prices, &liquidation_account_id);
+                    self.internal_start_nft_auction(
+                        account_id,
+                        &prices,
+                        &liquidation_account_id,
+                        position_id,
+                        &nft_asset,
+                    );
+                }
+                Action::BuyNftAuction {
+                    nft_asset,
+                    token_id,
+                    max_amount,
+                } => {
+                    self.assert_operation_not_paused(PausableOperation::Liquidate);
+                    self.internal_buy_nft_auction(
+                        account_id, account, &prices, &nft_asset, &token_id, max_amount,
+                    );
+                }
+                Action::CancelNftAuction(nft_asset) => {
+                    self.internal_cancel_nft_auction(account_id, &prices, &nft_asset);
+                }
+                Action::SetEMode { category_id } => {
+                    need_risk_check = true;
+                    self.internal_set_e_mode(account, category_id);
+                    events::emit::set_e_mode(account_id, category_id);
+                }
+                Action::Leverage {
+                    token_id,
+                    target_leverage,
+                } => {
+                    self.assert_operation_not_paused(PausableOperation::Borrow);
+                    need_number_check = true;
+                    // The borrowed amount is used as a collateral, so the collateral of the
+                    // resulting account has to cover the debt.
+                    need_risk_check = true;
+                    account.add_affected_farm(FarmId::Supplied(token_id.clone()));
+                    account.add_affected_farm(FarmId::Collateral(token_id.clone()));
+                    account.add_affected_farm(FarmId::Borrowed(token_id.clone()));
+                    let amount = self.internal_leverage(account, &token_id, target_leverage);
+                    events::emit::leverage(account_id, amount, &token_id);
+                }
+                Action::Deleverage {
+                    token_id,
+                    target_leverage,
+                } => {
+                    need_risk_check = true;
+                    account.add_affected_farm(FarmId::Supplied(token_id.clone()));
+                    account.add_affected_farm(FarmId::Collateral(token_id.clone()));
+                    account.add_affected_farm(FarmId::Borrowed(token_id.clone()));
+                    let amount = self.internal_deleverage(account, &token_id, target_leverage);
+                    events::emit::deleverage(account_id, amount, &token_id);
+                }
+                Action::TransferToPosition {
+                    position_id,
+                    asset_amount,
+                } => {
+                    account.add_affected_farm(FarmId::Supplied(asset_amount.token_id.clone()));
+                    let amount = self.internal_transfer_to_position(
+                        account_id,
+                        account,
+                        position_id,
+                        &asset_amount,
+                    );
+                    events::emit::transfer_to_position(
+                        account_id,
+                        account.position_id,
+                        position_id,
+                        amount,
+                        &asset_amount.token_id,
+                    );
                 }
             }
         }
         if need_number_check {
             assert!(
-                account.supplied.len() as usize + account.borrowed.len()
+                account.collateral.len() + account.borrowed.len() + account.stable_borrowed.len()
                     <= self.internal_config().max_num_assets as _
             );
         }
+        self.assert_isolated_collateral_valid(account);
+        self.assert_e_mode_valid(&pre_account, account);
+        // Any action that increases the debt against the isolated collateral, e.g. `Leverage`, is
+        // checked against the ceiling.
+        if let Some(token_id) = self.internal_update_isolated_debt(&pre_account, account) {
+            self.assert_isolated_debt_ceiling(&token_id, &prices);
+        }
         if need_risk_check_borrow {
-            assert!(
-                self.compute_max_discount(&pre_account, account, &prices) == BigDecimal::zero()
-            );
+            assert!(self.compute_max_discount(account, account, &prices) == BigDecimal::zero());
         }
 
         if need_risk_check {
@@ -267,16 +554,26 @@ impl Contract {
         );
 
         // Check accoount is NFT owner before withdraw
-        if let Some(owner_id) = asset.get_owner_nft(&nft_asset.token_id, &asset) {
+        if let Some((owner_id, position_id)) =
+            self.internal_get_nft_owner(&nft_asset.nft_contract_id, &nft_asset.token_id)
+        {
             assert_eq!(
                 account_id.clone(),
                 owner_id,
                 "You are not authorized. You must be using the owner account {} to withdraw this NFT",
                 owner_id
             );
+            assert_eq!(
+                account.position_id, position_id,
+                "The NFT is kept by another position of the account"
+            );
         } else {
             env::panic_str("NFT not found in the NFT pool");
         }
+        assert!(
+            !self.internal_is_nft_auctioned(&nft_asset.nft_contract_id, &nft_asset.token_id),
+            "The NFT is being auctioned"
+        );
 
         // Remove NFT from account asset
         let contract_nft_token_id: NFTContractTokenId = format!(
@@ -286,29 +583,47 @@ impl Contract {
         account.nft_supplied.remove(&contract_nft_token_id);
 
         // Remove NFT from asset
-        self.internal_remove_nft_asset(
-            &nft_asset.nft_contract_id,
-            nft_asset.token_id.clone(),
-            asset,
+        self.internal_remove_nft_asset(&nft_asset.nft_contract_id, &nft_asset.token_id);
+    }
+
+    pub fn internal_increase_collateral(
+        &mut self,
+        account: &mut Account,
+        asset_amount: &AssetAmount,
+    ) -> Balance {
+        let asset = self.internal_unwrap_asset(&asset_amount.token_id);
+        assert!(
+            asset.config.can_use_as_collateral,
+            "This asset can't be used as a collateral"
         );
+        let mut account_asset = account.internal_unwrap_asset(&asset_amount.token_id);
+
+        let (shares, amount) =
+            asset_amount_to_shares(&asset.supplied, account_asset.shares, asset_amount, false);
+
+        account_asset.withdraw_shares(shares);
+        account.internal_set_asset(&asset_amount.token_id, account_asset);
+
+        account.increase_collateral(&asset_amount.token_id, shares);
+
+        amount
     }
 
-    pub fn internal_decrease_supplied(
+    /// Moves the collateral shares of the given account into the given account asset.
+    /// The account asset may belong to another account, e.g. to a liquidator.
+    pub fn internal_decrease_collateral(
         &mut self,
         account_asset: &mut AccountAsset,
         account: &mut Account,
         asset_amount: &AssetAmount,
     ) -> Balance {
         let asset = self.internal_unwrap_asset(&asset_amount.token_id);
-
-        let mut account_asset_liquidation = account.internal_unwrap_asset(&asset_amount.token_id);
-        let supplied_shares = account_asset_liquidation.shares;
+        let collateral_shares = account.internal_unwrap_collateral(&asset_amount.token_id);
 
         let (shares, amount) =
-            asset_amount_to_shares(&asset.supplied, supplied_shares, asset_amount, false);
+            asset_amount_to_shares(&asset.supplied, collateral_shares, asset_amount, false);
 
-        account_asset_liquidation.withdraw_shares(shares);
-        account.internal_set_asset(&asset_amount.token_id, account_asset_liquidation);
+        account.decrease_collateral(&asset_amount.token_id, shares);
 
         account_asset.deposit_shares(shares);
 
@@ -342,6 +657,7 @@ impl Contract {
 
         asset.borrowed.deposit(borrowed_shares, amount);
         asset.supplied.deposit(supplied_shares, amount);
+        asset.assert_borrow_cap(&asset_amount.token_id);
         self.internal_set_asset(&asset_amount.token_id, asset);
 
         account.increase_borrowed(&asset_amount.token_id, borrowed_shares);
@@ -393,16 +709,199 @@ impl Contract {
         amount
     }
 
+    /// Repays the variable debt of the account with the given amount of tokens, that are not in
+    /// the supplied balance. The whole debt is repaid, if the amount covers it.
+    /// Returns the repaid amount, that is not greater than the given amount.
+    pub fn internal_repay_amount(
+        &mut self,
+        account: &mut Account,
+        token_id: &TokenId,
+        amount: Balance,
+    ) -> Balance {
+        let mut asset = self.internal_unwrap_asset(token_id);
+        let available_borrowed_shares = account.internal_unwrap_borrowed(token_id);
+        let debt_amount = asset
+            .borrowed
+            .shares_to_amount(available_borrowed_shares, true);
+
+        let (borrowed_shares, amount) = if amount >= debt_amount {
+            (available_borrowed_shares, debt_amount)
+        } else {
+            (asset.borrowed.amount_to_shares(amount, false), amount)
+        };
+        assert!(borrowed_shares.0 > 0, "Shares can't be 0");
+
+        asset.borrowed.withdraw(borrowed_shares, amount);
+        self.internal_set_asset(token_id, asset);
+
+        account.decrease_borrowed(token_id, borrowed_shares);
+
+        amount
+    }
+
+    /// Repays the debt of the given repay account, or its position, from the supplied balance of
+    /// the caller's account, and updates the borrowed farms of the repay account.
+    pub fn internal_repay_for(
+        &mut self,
+        account_id: &AccountId,
+        account: &mut Account,
+        repay_account_id: &AccountId,
+        position_id: Option<PositionId>,
+        asset_amount: &AssetAmount,
+    ) -> Balance {
+        assert!(
+            account_id != repay_account_id || account.position_id != position_id,
+            "Use the Repay action to repay your own debt"
+        );
+        let mut repay_account =
+            self.internal_unwrap_account_or_position(repay_account_id, position_id);
+        let pre_repay_account = repay_account.clone();
+        repay_account.add_affected_farm(FarmId::Borrowed(asset_amount.token_id.clone()));
+
+        let mut account_asset = account.internal_unwrap_asset(&asset_amount.token_id);
+        let amount =
+            self.internal_liquidation_repay(&mut account_asset, &mut repay_account, asset_amount);
+        account.internal_set_asset(&asset_amount.token_id, account_asset);
+
+        self.internal_update_isolated_debt(&pre_repay_account, &repay_account);
+        self.internal_account_apply_affected_farms(&mut repay_account);
+        self.internal_set_account_or_position(repay_account_id, position_id, repay_account);
+
+        amount
+    }
+
+    /// Repays the debt of the liquidation account. The variable debt is repaid first, and the rest
+    /// of the amount repays the stable debt in the asset.
+    /// Returns the repaid amount.
+    pub fn internal_liquidation_repay(
+        &mut self,
+        account_asset: &mut AccountAsset,
+        liquidation_account: &mut Account,
+        asset_amount: &AssetAmount,
+    ) -> Balance {
+        let token_id = &asset_amount.token_id;
+        let requested_amount = asset_amount
+            .amount
+            .or(asset_amount.max_amount)
+            .map(|amount| amount.0);
+        let mut repaid_amount = 0;
+        if let Some(shares) = liquidation_account.borrowed.get(token_id).cloned() {
+            let variable_debt = self
+                .internal_unwrap_asset(token_id)
+                .borrowed
+                .shares_to_amount(shares, true);
+            // The whole variable debt is repaid, if the amount covers it.
+            let covers_variable_debt =
+                requested_amount.map_or(true, |amount| amount >= variable_debt);
+            let variable_asset_amount = AssetAmount {
+                token_id: token_id.clone(),
+                amount: asset_amount.amount.filter(|_| !covers_variable_debt),
+                max_amount: asset_amount.max_amount.filter(|_| !covers_variable_debt),
+            };
+            repaid_amount =
+                self.internal_repay(account_asset, liquidation_account, &variable_asset_amount);
+        }
+        if liquidation_account.stable_borrowed.contains_key(token_id) {
+            let remaining_amount =
+                requested_amount.map(|amount| amount.saturating_sub(repaid_amount));
+            let has_remaining_amount = match remaining_amount {
+                Some(amount) => amount > 0,
+                None => account_asset.shares.0 > 0,
+            };
+            if has_remaining_amount {
+                repaid_amount += self.internal_repay_stable(
+                    account_asset,
+                    liquidation_account,
+                    &AssetAmount {
+                        token_id: token_id.clone(),
+                        amount: asset_amount.amount.and(remaining_amount.map(U128)),
+                        max_amount: asset_amount.max_amount.and(remaining_amount.map(U128)),
+                    },
+                );
+            }
+        }
+        repaid_amount
+    }
+
+    /// Repays the debt of the liquidation account, same as `internal_liquidation_repay`. If the
+    /// amount exceeds both the variable and the stable debt, the debt is repaid in full and the
+    /// rest of the amount is credited to the liquidation account as a supplied asset.
+    /// Returns the repaid amount and the credited amount.
+    pub fn internal_liquidation_repay_and_credit(
+        &mut self,
+        account_asset: &mut AccountAsset,
+        liquidation_account: &mut Account,
+        asset_amount: &AssetAmount,
+    ) -> (Balance, Balance) {
+        let asset = self.internal_unwrap_asset(&asset_amount.token_id);
+        let variable_debt = liquidation_account
+            .borrowed
+            .get(&asset_amount.token_id)
+            .map(|shares| asset.borrowed.shares_to_amount(*shares, true))
+            .unwrap_or(0);
+        let stable_debt = liquidation_account
+            .internal_get_stable_debt(&asset_amount.token_id)
+            .map(|stable_debt| stable_debt.get_amount())
+            .unwrap_or(0);
+        let debt_amount = variable_debt + stable_debt;
+        let amount = match &asset_amount.amount {
+            Some(amount) if amount.0 > debt_amount => amount.0,
+            _ => {
+                let repaid_amount = self.internal_liquidation_repay(
+                    account_asset,
+                    liquidation_account,
+                    asset_amount,
+                );
+                return (repaid_amount, 0);
+            }
+        };
+        let repaid_amount = if debt_amount > 0 {
+            self.internal_liquidation_repay(
+                account_asset,
+                liquidation_account,
+                &AssetAmount {
+                    token_id: asset_amount.token_id.clone(),
+                    amount: None,
+                    max_amount: None,
+                },
+            )
+        } else {
+            0
+        };
+        let credited_amount = amount - std::cmp::min(repaid_amount, amount);
+        if credited_amount > 0 {
+            let asset = self.internal_unwrap_asset(&asset_amount.token_id);
+            let shares = asset.supplied.amount_to_shares(credited_amount, true);
+            account_asset.withdraw_shares(shares);
+            let mut liquidation_account_asset =
+                liquidation_account.internal_get_asset_or_default(&asset_amount.token_id);
+            liquidation_account_asset.deposit_shares(shares);
+            liquidation_account
+                .internal_set_asset(&asset_amount.token_id, liquidation_account_asset);
+        }
+        (repaid_amount, credited_amount)
+    }
+
     pub fn internal_liquidate_nft(
         &mut self,
         account_id: &AccountId,
         account: &mut Account,
         prices: &Prices,
-        liquidation_account_id: &AccountId,
-        in_assets: Vec<AssetAmount>,
-        out_nft_assets: Vec<NFTAsset>,
+        liquidation: NftLiquidation,
     ) {
-        let mut liquidation_account = self.internal_unwrap_account(liquidation_account_id);
+        assert_eq!(
+            self.internal_config().nft_auction_duration_sec,
+            0,
+            "The NFT collateral is liquidated through auctions"
+        );
+        let NftLiquidation {
+            account_id: liquidation_account_id,
+            position_id,
+            in_assets,
+            out_nft_assets,
+        } = liquidation;
+        let mut liquidation_account =
+            self.internal_unwrap_account_or_position(&liquidation_account_id, position_id);
         let max_discount =
             self.compute_max_discount(&liquidation_account, &liquidation_account, prices);
 
@@ -411,40 +910,77 @@ impl Contract {
             "The liquidation account is not at risk"
         );
 
+        let max_repaid_sum = self
+            .compute_borrowed_sum(&liquidation_account, prices)
+            .mul_ratio(self.internal_config().close_factor);
+
         let mut borrowed_repaid_sum = BigDecimal::zero();
+        let mut credited_sum = BigDecimal::zero();
         let mut collateral_taken_sum = BigDecimal::zero();
+        let mut discounted_collateral_taken = BigDecimal::zero();
 
         for asset_amount in in_assets {
             liquidation_account.add_affected_farm(FarmId::Borrowed(asset_amount.token_id.clone()));
             let mut account_asset = account.internal_unwrap_asset(&asset_amount.token_id);
-            let amount =
-                self.internal_repay(&mut account_asset, &mut liquidation_account, &asset_amount);
+            let (amount, credited_amount) = self.internal_liquidation_repay_and_credit(
+                &mut account_asset,
+                &mut liquidation_account,
+                &asset_amount,
+            );
             account.internal_set_asset(&asset_amount.token_id, account_asset);
             let asset = self.internal_unwrap_asset(&asset_amount.token_id);
+            let price = prices.get_unwrap(&asset_amount.token_id);
 
             borrowed_repaid_sum = borrowed_repaid_sum
-                + BigDecimal::from_balance_price(
-                    amount,
-                    prices.get_unwrap(&asset_amount.token_id),
-                    asset.config.extra_decimals,
+                + BigDecimal::from_balance_price(amount, price, asset.config.extra_decimals);
+            if credited_amount > 0 {
+                account.add_affected_farm(FarmId::Supplied(asset_amount.token_id.clone()));
+                liquidation_account
+                    .add_affected_farm(FarmId::Supplied(asset_amount.token_id.clone()));
+                credited_sum = credited_sum
+                    + BigDecimal::from_balance_price(
+                        credited_amount,
+                        price,
+                        asset.config.extra_decimals,
+                    );
+                events::emit::liquidation_credit(
+                    &liquidation_account_id,
+                    credited_amount,
+                    &asset_amount.token_id,
                 );
+            }
         }
 
         for nft_asset in out_nft_assets {
             let asset = self.internal_unwrap_asset(&nft_asset.nft_contract_id);
+            assert!(
+                asset.config.can_use_as_collateral,
+                "This NFT can't be liquidated, because it's not used as a collateral"
+            );
             let config_extra_decimals = asset.config.extra_decimals;
+            let config_liquidation_bonus = asset.config.liquidation_bonus;
 
             // Check NFT owner from liquidation account
-            if let Some(owner_id) = asset.get_owner_nft(&nft_asset.token_id, &asset) {
+            if let Some((owner_id, owner_position_id)) =
+                self.internal_get_nft_owner(&nft_asset.nft_contract_id, &nft_asset.token_id)
+            {
                 assert_eq!(
                         liquidation_account_id.clone(),
                         owner_id,
                         "You are not authorized. You can only liquidate nft from the liquidation account {}",
                         owner_id
                     );
+                assert_eq!(
+                    position_id, owner_position_id,
+                    "The NFT is kept by another position of the liquidation account"
+                );
             } else {
                 env::panic_str("NFT notfound");
             }
+            assert!(
+                !self.internal_is_nft_auctioned(&nft_asset.nft_contract_id, &nft_asset.token_id),
+                "The NFT is being auctioned"
+            );
 
             // Get NFT from liquidation account
             let contract_nft_token_id: NFTContractTokenId = format!(
@@ -467,34 +1003,41 @@ impl Contract {
             self.internal_set_nft_asset(
                 &nft_asset.nft_contract_id,
                 account_id.clone(),
+                account.position_id,
                 nft_asset.token_id.clone(),
-                asset,
             );
 
-            // Fix NFT balance is 1 (decimals 24)
-            let balance = 10u128.pow(24);
-            collateral_taken_sum = collateral_taken_sum
-                + BigDecimal::from_balance_price(
-                    balance,
+            let collateral_taken = self
+                .internal_get_nft_valuation(&nft_asset.nft_contract_id)
+                .get_value(
+                    &nft_asset.token_id,
                     prices.get_unwrap(&nft_asset.nft_contract_id),
                     config_extra_decimals,
                 );
+            collateral_taken_sum = collateral_taken_sum + collateral_taken;
+            discounted_collateral_taken = discounted_collateral_taken
+                + collateral_taken.mul_ratio(MAX_RATIO - config_liquidation_bonus);
         }
 
-        let discounted_collateral_taken = collateral_taken_sum * (BigDecimal::one() - max_discount);
+        // The NFT is indivisible, so the debt can be repaid over the close factor limit up to the
+        // discounted value of the taken NFTs.
         assert!(
-            discounted_collateral_taken <= borrowed_repaid_sum,
-            "Not enough balances repaid: discounted collateral {} > borrowed repaid sum {}",
+            borrowed_repaid_sum <= max_repaid_sum
+                || borrowed_repaid_sum <= discounted_collateral_taken,
+            "The liquidation amount is too large: borrowed repaid sum {} > close factor limit {}",
+            borrowed_repaid_sum,
+            max_repaid_sum
+        );
+        assert!(
+            discounted_collateral_taken <= borrowed_repaid_sum + credited_sum,
+            "Not enough balances repaid: discounted collateral {} > borrowed repaid sum {} + credited sum {}",
             discounted_collateral_taken,
-            borrowed_repaid_sum
+            borrowed_repaid_sum,
+            credited_sum
         );
 
         let new_max_discount =
             self.compute_max_discount(&liquidation_account, &liquidation_account, prices);
-        assert!(
-            new_max_discount > BigDecimal::zero(),
-            "The liquidation amount is too large. The liquidation account should stay in risk"
-        );
         assert!(
             new_max_discount < max_discount,
             "The health factor of liquidation account can't decrease. New discount {} < old discount {}",
@@ -502,11 +1045,15 @@ impl Contract {
         );
 
         self.internal_account_apply_affected_farms(&mut liquidation_account);
-        self.internal_set_account(liquidation_account_id, liquidation_account);
+        self.internal_set_account_or_position(
+            &liquidation_account_id,
+            position_id,
+            liquidation_account,
+        );
 
         events::emit::liquidate(
             account_id,
-            liquidation_account_id,
+            &liquidation_account_id,
             &collateral_taken_sum,
             &borrowed_repaid_sum,
         );
@@ -517,11 +1064,17 @@ impl Contract {
         account_id: &AccountId,
         account: &mut Account,
         prices: &Prices,
-        liquidation_account_id: &AccountId,
-        in_assets: Vec<AssetAmount>,
-        out_assets: Vec<AssetAmount>,
+        liquidation: Liquidation,
     ) {
-        let mut liquidation_account = self.internal_unwrap_account(liquidation_account_id);
+        let Liquidation {
+            account_id: liquidation_account_id,
+            position_id,
+            in_assets,
+            out_assets,
+        } = liquidation;
+        let mut liquidation_account =
+            self.internal_unwrap_account_or_position(&liquidation_account_id, position_id);
+        let pre_liquidation_account = liquidation_account.clone();
         let max_discount =
             self.compute_max_discount(&liquidation_account, &liquidation_account, prices);
 
@@ -530,14 +1083,22 @@ impl Contract {
             "The liquidation account is not at risk"
         );
 
+        let max_repaid_sum = self
+            .compute_borrowed_sum(&liquidation_account, prices)
+            .mul_ratio(self.internal_config().close_factor);
+
         let mut borrowed_repaid_sum = BigDecimal::zero();
         let mut collateral_taken_sum = BigDecimal::zero();
+        let mut discounted_collateral_taken = BigDecimal::zero();
 
         for asset_amount in in_assets {
             liquidation_account.add_affected_farm(FarmId::Borrowed(asset_amount.token_id.clone()));
             let mut account_asset = account.internal_unwrap_asset(&asset_amount.token_id);
-            let amount =
-                self.internal_repay(&mut account_asset, &mut liquidation_account, &asset_amount);
+            let amount = self.internal_liquidation_repay(
+                &mut account_asset,
+                &mut liquidation_account,
+                &asset_amount,
+            );
             account.internal_set_asset(&asset_amount.token_id, account_asset);
             let asset = self.internal_unwrap_asset(&asset_amount.token_id);
 
@@ -551,25 +1112,38 @@ impl Contract {
 
         for asset_amount in out_assets {
             let asset = self.internal_unwrap_asset(&asset_amount.token_id);
+            assert!(
+                asset.config.can_use_as_collateral,
+                "This asset can't be liquidated, because it's not used as a collateral"
+            );
             liquidation_account.add_affected_farm(FarmId::Supplied(asset_amount.token_id.clone()));
+            liquidation_account
+                .add_affected_farm(FarmId::Collateral(asset_amount.token_id.clone()));
             let mut account_asset = account.internal_get_asset_or_default(&asset_amount.token_id);
 
-            let amount = self.internal_decrease_supplied(
+            let amount = self.internal_decrease_collateral(
                 &mut account_asset,
                 &mut liquidation_account,
                 &asset_amount,
             );
             account.internal_set_asset(&asset_amount.token_id, account_asset);
 
-            collateral_taken_sum = collateral_taken_sum
-                + BigDecimal::from_balance_price(
-                    amount,
-                    prices.get_unwrap(&asset_amount.token_id),
-                    asset.config.extra_decimals,
-                );
+            let collateral_taken = BigDecimal::from_balance_price(
+                amount,
+                prices.get_unwrap(&asset_amount.token_id),
+                asset.config.extra_decimals,
+            );
+            collateral_taken_sum = collateral_taken_sum + collateral_taken;
+            discounted_collateral_taken = discounted_collateral_taken
+                + collateral_taken.mul_ratio(MAX_RATIO - asset.config.liquidation_bonus);
         }
 
-        let discounted_collateral_taken = collateral_taken_sum * (BigDecimal::one() - max_discount);
+        assert!(
+            borrowed_repaid_sum <= max_repaid_sum,
+            "The liquidation amount is too large: borrowed repaid sum {} > close factor limit {}",
+            borrowed_repaid_sum,
+            max_repaid_sum
+        );
         assert!(
             discounted_collateral_taken <= borrowed_repaid_sum,
             "Not enough balances repaid: discounted collateral {} > borrowed repaid sum {}",
@@ -579,42 +1153,63 @@ impl Contract {
 
         let new_max_discount =
             self.compute_max_discount(&liquidation_account, &liquidation_account, prices);
-        assert!(
-            new_max_discount > BigDecimal::zero(),
-            "The liquidation amount is too large. The liquidation account should stay in risk"
-        );
         assert!(
             new_max_discount < max_discount,
             "The health factor of liquidation account can't decrease. New discount {} < old discount {}",
             new_max_discount, max_discount
         );
 
+        self.internal_update_isolated_debt(&pre_liquidation_account, &liquidation_account);
         self.internal_account_apply_affected_farms(&mut liquidation_account);
-        self.internal_set_account(liquidation_account_id, liquidation_account);
+        self.internal_set_account_or_position(
+            &liquidation_account_id,
+            position_id,
+            liquidation_account,
+        );
 
         events::emit::liquidate(
             account_id,
-            liquidation_account_id,
+            &liquidation_account_id,
             &collateral_taken_sum,
             &borrowed_repaid_sum,
         );
     }
 
-    pub fn internal_force_close(&mut self, prices: &Prices, liquidation_account_id: &AccountId) {
+    pub fn internal_force_close(
+        &mut self,
+        prices: &Prices,
+        liquidation_account_id: &AccountId,
+        position_id: Option<PositionId>,
+    ) {
         let config = self.internal_config();
         assert!(
             config.force_closing_enabled,
             "The force closing is not enabled"
         );
 
-        let mut liquidation_account = self.internal_unwrap_account(liquidation_account_id);
+        let mut liquidation_account =
+            self.internal_unwrap_account_or_position(liquidation_account_id, position_id);
+        let pre_liquidation_account = liquidation_account.clone();
 
         let mut borrowed_sum = BigDecimal::zero();
         let mut collateral_sum = BigDecimal::zero();
 
         let mut affected_farms = vec![];
 
-        for (token_id, shares) in liquidation_account.supplied.drain() {
+        // Only the assets that can be used as a collateral are taken. The rest stays on the account.
+        let collateral: Vec<(TokenId, Shares)> = liquidation_account
+            .collateral
+            .iter()
+            .filter(|(token_id, _)| {
+                self.internal_unwrap_asset(token_id)
+                    .config
+                    .can_use_as_collateral
+            })
+            .map(|(token_id, shares)| (token_id.clone(), *shares))
+            .collect();
+
+        for (token_id, shares) in collateral {
+            liquidation_account.collateral.remove(&token_id);
             let mut asset = self.internal_unwrap_asset(&token_id);
             let amount = asset.supplied.shares_to_amount(shares, false);
             asset.reserved += amount;
@@ -627,19 +1222,22 @@ impl Contract {
                     asset.config.extra_decimals,
                 );
             self.internal_set_asset(&token_id, asset);
-            affected_farms.push(FarmId::Supplied(token_id));
+            affected_farms.push(FarmId::Supplied(token_id.clone()));
+            affected_farms.push(FarmId::Collateral(token_id));
         }
 
         for (token_id, shares) in liquidation_account.borrowed.drain() {
             let mut asset = self.internal_unwrap_asset(&token_id);
             let amount = asset.borrowed.shares_to_amount(shares, true);
-            assert!(
-                asset.reserved >= amount,
-                "Not enough {} in reserve",
-                token_id
-            );
-            asset.reserved -= amount;
+            let socialized_amount = asset.cover_bad_debt(&token_id, amount);
             asset.borrowed.withdraw(shares, amount);
+            if socialized_amount > 0 {
+                events::emit::bad_debt_socialized(
+                    liquidation_account_id,
+                    socialized_amount,
+                    &token_id,
+                );
+            }
 
             borrowed_sum = borrowed_sum
                 + BigDecimal::from_balance_price(
@@ -651,6 +1249,28 @@ impl Contract {
             affected_farms.push(FarmId::Borrowed(token_id));
         }
 
+        for (token_id, stable_debt) in liquidation_account.stable_borrowed.drain() {
+            let mut asset = self.internal_unwrap_asset(&token_id);
+            let amount = stable_debt.get_amount();
+            let socialized_amount = asset.cover_bad_debt(&token_id, amount);
+            asset.decrease_stable_borrowed(amount, stable_debt.rate.into());
+            if socialized_amount > 0 {
+                events::emit::bad_debt_socialized(
+                    liquidation_account_id,
+                    socialized_amount,
+                    &token_id,
+                );
+            }
+
+            borrowed_sum = borrowed_sum
+                + BigDecimal::from_balance_price(
+                    amount,
+                    prices.get_unwrap(&token_id),
+                    asset.config.extra_decimals,
+                );
+            self.internal_set_asset(&token_id, asset);
+        }
+
         assert!(
             borrowed_sum > collateral_sum,
             "Total borrowed sum {} is not greater than total collateral sum {}",
@@ -659,49 +1279,88 @@ impl Contract {
         );
         liquidation_account.affected_farms.extend(affected_farms);
 
+        self.internal_update_isolated_debt(&pre_liquidation_account, &liquidation_account);
         self.internal_account_apply_affected_farms(&mut liquidation_account);
-        self.internal_set_account(liquidation_account_id, liquidation_account);
+        self.internal_set_account_or_position(
+            liquidation_account_id,
+            position_id,
+            liquidation_account,
+        );
 
         events::emit::force_close(liquidation_account_id, &collateral_sum, &borrowed_sum);
     }
 
+    /// Returns the total value of the borrowed assets of the given account without adjusting it
+    /// by the volatility ratios.
+    pub fn compute_borrowed_sum(&self, account: &Account, prices: &Prices) -> BigDecimal {
+        account
+            .borrowed
+            .iter()
+            .fold(BigDecimal::zero(), |sum, (token_id, shares)| {
+                let asset = self.internal_unwrap_asset(token_id);
+                let balance = asset.borrowed.shares_to_amount(*shares, true);
+                sum + BigDecimal::from_balance_price(
+                    balance,
+                    prices.get_unwrap(token_id),
+                    asset.config.extra_decimals,
+                )
+            })
+            + self.compute_stable_borrowed_sum(account, prices, false)
+    }
+
     pub fn compute_max_discount(
         &self,
         pre_account: &Account,
         account: &Account,
         prices: &Prices,
     ) -> BigDecimal {
-        if account.borrowed.is_empty() {
+        if account.borrowed.is_empty() && account.stable_borrowed.is_empty() {
             return BigDecimal::zero();
         }
+        let e_mode_category = self.internal_get_e_mode_category(account);
 
         let collateral_sum =
             pre_account
-                .supplied
+                .collateral
                 .iter()
                 .fold(BigDecimal::zero(), |sum, (token_id, shares)| {
                     let asset = self.internal_unwrap_asset(token_id);
+                    if !asset.config.can_use_as_collateral {
+                        return sum;
+                    }
                     let balance = asset.supplied.shares_to_amount(*shares, false);
                     sum + BigDecimal::from_balance_price(
                         balance,
                         prices.get_unwrap(token_id),
                         asset.config.extra_decimals,
                     )
-                    .mul_ratio(asset.config.volatility_ratio)
+                    .mul_ratio(get_collateral_ratio(
+                        e_mode_category.as_ref(),
+                        token_id,
+                        &asset.config,
+                    ))
                 });
 
         let nft_collateral_sum = pre_account.nft_supplied.iter().fold(
             BigDecimal::zero(),
             |sum, (_, account_nft_asset)| {
                 let asset = self.internal_unwrap_asset(&account_nft_asset.nft_contract_id);
-                // Fix NFT balance is 1 (decimals 24)
-                let balance = 10u128.pow(24);
-                sum + BigDecimal::from_balance_price(
-                    balance,
-                    prices.get_unwrap(&account_nft_asset.nft_contract_id),
-                    asset.config.extra_decimals,
-                )
-                .mul_ratio(asset.config.volatility_ratio)
+                if !asset.config.can_use_as_collateral {
+                    return sum;
+                }
+                let nft_valuation =
+                    self.internal_get_nft_valuation(&account_nft_asset.nft_contract_id);
+                sum + nft_valuation
+                    .get_value(
+                        &account_nft_asset.nft_token_id,
+                        prices.get_unwrap(&account_nft_asset.nft_contract_id),
+                        asset.config.extra_decimals,
+                    )
+                    .mul_ratio(nft_valuation.get_collateral_ratio(get_collateral_ratio(
+                        e_mode_category.as_ref(),
+                        &account_nft_asset.nft_contract_id,
+                        &asset.config,
+                    )))
             },
         );
 
@@ -717,8 +1376,13 @@ impl Contract {
                         prices.get_unwrap(token_id),
                         asset.config.extra_decimals,
                     )
-                    .div_ratio(asset.config.volatility_ratio)
-                });
+                    .div_ratio(get_borrow_ratio(
+                        e_mode_category.as_ref(),
+                        token_id,
+                        &asset.config,
+                    ))
+                })
+                + self.compute_stable_borrowed_sum(account, prices, true);
 
         let total_collateral_sum = collateral_sum.add(nft_collateral_sum);
 
@@ -736,7 +1400,7 @@ impl Contract {
     }
 }
 
-fn asset_amount_to_shares(
+pub(crate) fn asset_amount_to_shares(
     pool: &Pool,
     available_shares: Shares,
     asset_amount: &AssetAmount,
@@ -779,6 +1443,7 @@ impl Contract {
     #[payable]
     pub fn execute(&mut self, actions: Vec<Action>) {
         assert_one_yocto();
+        self.assert_not_paused();
         let account_id = env::predecessor_account_id();
         let mut account = self.internal_unwrap_account(&account_id);
         self.internal_execute(&account_id, &mut account, actions, Prices::new());
@@ -1,7 +1,6 @@
 use crate::*;
 
 pub const MS_PER_YEAR: u64 = 31536000000;
-pub const MAX_ITEMS: usize = 99999;
 
 static ASSETS: Lazy<Mutex<HashMap<TokenId, Option<Asset>>>> =
     Lazy::new(|| Mutex::new(HashMap::new()));
@@ -12,13 +11,27 @@ static ASSETS: Lazy<Mutex<HashMap<TokenId, Option<Asset>>>> =
 pub struct Asset {
     /// Total supplied including collateral, but excluding reserved.
     pub supplied: Pool,
-    /// Total borrowed.
+    /// Total borrowed at the variable rate.
     pub borrowed: Pool,
-    pub nft_supplied: Vec<NftPool>,
+    /// Total borrowed at the stable rates, including the accrued interest.
+    #[serde(with = "u128_dec_format")]
+    pub stable_borrowed: Balance,
+    /// The average compounding rate of the stable borrowed, weighted by the debt.
+    pub average_stable_rate: LowU128,
+    /// The amount currently lent out by the flash loans. It's returned within the same
+    /// transaction, so it doesn't affect the utilization.
+    #[serde(with = "u128_dec_format")]
+    pub flash_loaned: Balance,
+    /// The debt amounts borrowed against this asset, if it's an isolated collateral.
+    pub isolated_debt: HashMap<TokenId, U128>,
     /// The amount reserved for the stability. This amount can also be borrowed and affects
     /// borrowing rate.
     #[serde(with = "u128_dec_format")]
     pub reserved: Balance,
+    /// The total bad debt that wasn't covered by the reserve and was written off against the
+    /// supplied balance.
+    #[serde(with = "u128_dec_format")]
+    pub socialized_bad_debt: Balance,
     /// When the asset was last updated. It's always going to be the current block timestamp.
     #[serde(with = "u64_dec_format")]
     pub last_update_timestamp: Timestamp,
@@ -28,20 +41,23 @@ pub struct Asset {
 
 #[derive(BorshSerialize, BorshDeserialize)]
 pub enum VAsset {
-    Current(Asset),
+    V0(AssetV0),
+    /// Boxed the same way as `VAccount::Current`, without changing the stored layout.
+    Current(Box<Asset>),
 }
 
 impl From<VAsset> for Asset {
     fn from(v: VAsset) -> Self {
         match v {
-            VAsset::Current(c) => c,
+            VAsset::V0(c) => c.into(),
+            VAsset::Current(c) => *c,
         }
     }
 }
 
 impl From<Asset> for VAsset {
     fn from(c: Asset) -> Self {
-        VAsset::Current(c)
+        VAsset::Current(Box::new(c))
     }
 }
 
@@ -50,16 +66,24 @@ impl Asset {
         Self {
             supplied: Pool::new(),
             borrowed: Pool::new(),
-            nft_supplied: vec![],
+            stable_borrowed: 0,
+            average_stable_rate: BigDecimal::one().into(),
+            flash_loaned: 0,
+            isolated_debt: HashMap::new(),
             reserved: 0,
+            socialized_bad_debt: 0,
             last_update_timestamp: timestamp,
             config,
         }
     }
 
+    /// Returns the variable borrowing rate. Both variable and stable debt count towards the
+    /// utilization.
     pub fn get_rate(&self) -> BigDecimal {
-        self.config
-            .get_rate(self.borrowed.balance, self.supplied.balance + self.reserved)
+        self.config.get_rate(
+            self.borrowed.balance + self.stable_borrowed,
+            self.supplied.balance + self.reserved,
+        )
     }
 
     pub fn get_borrow_apr(&self) -> BigDecimal {
@@ -68,16 +92,18 @@ impl Asset {
     }
 
     pub fn get_supply_apr(&self) -> BigDecimal {
-        if self.supplied.balance == 0 || self.borrowed.balance == 0 {
+        if self.supplied.balance == 0 || self.borrowed.balance + self.stable_borrowed == 0 {
             return BigDecimal::zero();
         }
 
-        let borrow_apr = self.get_borrow_apr();
-        if borrow_apr == BigDecimal::zero() {
-            return borrow_apr;
+        let interest = self.get_borrow_apr().round_mul_u128(self.borrowed.balance)
+            + self
+                .get_stable_borrow_apr()
+                .round_mul_u128(self.stable_borrowed);
+        if interest == 0 {
+            return BigDecimal::zero();
         }
 
-        let interest = borrow_apr.round_mul_u128(self.borrowed.balance);
         let supply_interest = ratio(interest, MAX_RATIO - self.config.reserve_ratio);
         BigDecimal::from(supply_interest).div_u128(self.supplied.balance)
     }
@@ -95,8 +121,15 @@ impl Asset {
     // n = in millis
     fn compound(&mut self, time_diff_ms: Duration) {
         let rate = self.get_rate();
-        let interest =
+        let variable_interest =
             rate.pow(time_diff_ms).round_mul_u128(self.borrowed.balance) - self.borrowed.balance;
+        let stable_interest = BigDecimal::from(self.average_stable_rate)
+            .pow(time_diff_ms)
+            .round_mul_u128(self.stable_borrowed)
+            - self.stable_borrowed;
+        self.borrowed.balance += variable_interest;
+        self.stable_borrowed += stable_interest;
+        let interest = variable_interest + stable_interest;
         // TODO: Split interest based on ratio between reserved and supplied?
         let reserved = ratio(interest, self.config.reserve_ratio);
         if self.supplied.shares.0 > 0 {
@@ -105,7 +138,6 @@ impl Asset {
         } else {
             self.reserved += interest;
         }
-        self.borrowed.balance += interest;
     }
 
     pub fn update(&mut self) {
@@ -119,23 +151,70 @@ impl Asset {
     }
 
     pub fn available_amount(&self) -> Balance {
-        self.supplied.balance + self.reserved - self.borrowed.balance
+        self.supplied.balance + self.reserved
+            - self.borrowed.balance
+            - self.stable_borrowed
+            - self.flash_loaned
+    }
+
+    /// Covers the written off debt from the reserve. The shortfall is written off against the
+    /// supplied balance, so the suppliers share the loss pro rata.
+    /// Returns the socialized amount.
+    pub fn cover_bad_debt(&mut self, token_id: &TokenId, amount: Balance) -> Balance {
+        let reserved_amount = std::cmp::min(self.reserved, amount);
+        self.reserved -= reserved_amount;
+        let socialized_amount = amount - reserved_amount;
+        assert!(
+            socialized_amount < self.supplied.balance || socialized_amount == 0,
+            "Not enough {} in reserve and supplied",
+            token_id
+        );
+        self.supplied.balance -= socialized_amount;
+        self.socialized_bad_debt += socialized_amount;
+        socialized_amount
     }
 
-    /// Get the NFT owner from asset NFT pool
-    pub fn get_owner_nft(&self, token_id: &NFTTokenId, asset: &Asset) -> Option<AccountId> {
-        let index = asset
-            .nft_supplied
-            .iter()
-            .position(|x| *x.token_id == token_id.clone())
-            .unwrap_or(MAX_ITEMS);
+    /// Returns the total borrowed amount including the stable debt.
+    pub fn total_borrowed(&self) -> Balance {
+        self.borrowed.balance + self.stable_borrowed
+    }
 
-        if index != MAX_ITEMS {
-            let nft_pool = asset.nft_supplied.get(index).unwrap();
-            return Some(nft_pool.owner_id.clone());
+    /// Returns the supplied amount relative to the supply cap, if the asset has one.
+    pub fn get_supply_cap_usage(&self) -> Option<BigDecimal> {
+        self.config
+            .supply_cap
+            .map(|cap| BigDecimal::from(self.supplied.balance).div_u128(cap.0))
+    }
+
+    /// Returns the borrowed amount relative to the borrow cap, if the asset has one.
+    pub fn get_borrow_cap_usage(&self) -> Option<BigDecimal> {
+        self.config
+            .borrow_cap
+            .map(|cap| BigDecimal::from(self.total_borrowed()).div_u128(cap.0))
+    }
+
+    pub fn assert_supply_cap(&self, token_id: &TokenId) {
+        if let Some(cap) = self.config.supply_cap {
+            assert!(
+                self.supplied.balance <= cap.0,
+                "Supply cap exceeded: total supplied {} of {} exceeds the cap {}",
+                self.supplied.balance,
+                token_id,
+                cap.0
+            );
         }
+    }
 
-        None
+    pub fn assert_borrow_cap(&self, token_id: &TokenId) {
+        if let Some(cap) = self.config.borrow_cap {
+            assert!(
+                self.total_borrowed() <= cap.0,
+                "Borrow cap exceeded: total borrowed {} of {} exceeds the cap {}",
+                self.total_borrowed(),
+                token_id,
+                cap.0
+            );
+        }
     }
 }
 
@@ -157,61 +236,6 @@ impl Contract {
         })
     }
 
-    pub fn internal_set_nft_asset(
-        &mut self,
-        nft_contract_id: &NFTContractId,
-        owner_id: AccountId,
-        token_id: NFTTokenId,
-        mut asset: Asset,
-    ) {
-        let index = asset
-            .nft_supplied
-            .iter()
-            .position(|x| *x.token_id == token_id)
-            .unwrap_or(MAX_ITEMS);
-
-        let mut deposit_timestamp = env::block_timestamp();
-        if index != MAX_ITEMS {
-            let current_nft = asset.nft_supplied.get(index).expect("Can't find the nft");
-            deposit_timestamp = current_nft.deposit_timestamp;
-            asset.nft_supplied.remove(index);
-        }
-
-        asset.nft_supplied.push(NftPool {
-            owner_id,
-            token_id,
-            deposit_timestamp,
-        });
-
-        ASSETS
-            .lock()
-            .unwrap()
-            .insert(nft_contract_id.clone(), Some(asset.clone()));
-        self.assets.insert(nft_contract_id, &asset.into());
-    }
-
-    pub fn internal_remove_nft_asset(
-        &mut self,
-        nft_contract_id: &NFTContractId,
-        token_id: NFTTokenId,
-        mut asset: Asset,
-    ) {
-        let index = asset
-            .nft_supplied
-            .iter()
-            .position(|x| *x.token_id == token_id)
-            .unwrap_or(MAX_ITEMS);
-        if index != MAX_ITEMS {
-            asset.nft_supplied.remove(index);
-        }
-
-        ASSETS
-            .lock()
-            .unwrap()
-            .insert(nft_contract_id.clone(), Some(asset.clone()));
-        self.assets.insert(nft_contract_id, &asset.into());
-    }
-
     pub fn internal_set_asset(&mut self, token_id: &TokenId, mut asset: Asset) {
         if asset.supplied.shares.0 == 0 && asset.supplied.balance > 0 {
             asset.reserved += asset.supplied.balance;
@@ -221,7 +245,11 @@ impl Contract {
             asset.borrowed.shares.0 > 0 || asset.borrowed.balance == 0,
             "Borrowed invariant broken"
         );
-        asset.supplied.assert_invariant();
+        // The supplied balance can only be less than the number of shares after the bad debt is
+        // socialized.
+        if asset.socialized_bad_debt == 0 {
+            asset.supplied.assert_invariant();
+        }
         asset.borrowed.assert_invariant();
         ASSETS
             .lock()
@@ -305,37 +333,4 @@ impl Contract {
             })
             .collect()
     }
-
-    /// Returns the NFTs from the asset
-    pub fn get_nft_assets_paged(
-        &self,
-        nft_contract_id: NFTContractId,
-        from_index: Option<u64>,
-        limit: Option<u64>,
-    ) -> Vec<NftPool> {
-        let mut nft_asset: Asset = self
-            .assets
-            .get(&nft_contract_id)
-            .expect("Can't get the NFT asset")
-            .into();
-
-        nft_asset.nft_supplied.reverse();
-        let values = nft_asset.nft_supplied;
-        let from_index = from_index.unwrap_or(0);
-        let limit = limit.unwrap_or(values.len() as u64);
-        (from_index..std::cmp::min(values.len() as u64, from_index + limit))
-            .map(|index| values.get(index as usize).unwrap().clone())
-            .collect()
-    }
-
-    /// Returns the number of NFTs
-    pub fn get_num_nfts(&self, nft_contract_id: NFTContractId) -> u32 {
-        let nft_asset: Asset = self
-            .assets
-            .get(&nft_contract_id)
-            .expect("Can't get the NFT asset")
-            .into();
-
-        nft_asset.nft_supplied.len() as _
-    }
 }
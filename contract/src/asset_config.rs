@@ -5,8 +5,8 @@ const MAX_RATIO: u32 = 10000;
 
 /// Represents an asset config.
 /// Example:
-/// 25% reserve, 80% target utilization, 12% target APR, 250% max APR, 60% vol
-/// no extra decimals, can be deposited, withdrawn, used as a collateral, borrowed
+/// 25% reserve, 80% target utilization, 12% target APR, 250% max APR, 60% vol, 5% liquidation
+/// bonus, no extra decimals, can be deposited, withdrawn, used as a collateral, borrowed
 /// JSON:
 /// ```json
 /// {
@@ -15,6 +15,7 @@ const MAX_RATIO: u32 = 10000;
 ///   "target_utilization_rate": "1000000000003593629036885046",
 ///   "max_utilization_rate": "1000000000039724853136740579",
 ///   "volatility_ratio": 6000,
+///   "liquidation_bonus": 500,
 ///   "extra_decimals": 0,
 ///   "can_deposit": true,
 ///   "can_withdraw": true,
@@ -40,6 +41,13 @@ pub struct AssetConfig {
     /// Use `apr_to_rate.py` script to compute the value for a given APR.
     /// Given as a decimal string. E.g. "1000000000039724853136740579" for 250% APR.
     pub max_utilization_rate: LowU128,
+    /// The optional list of utilization breakpoints of the interest rate curve, sorted by the
+    /// utilization. The rate is linearly interpolated between 0% utilization, the breakpoints and
+    /// 100% utilization at `max_utilization_rate`.
+    /// If given, the breakpoints replace the `target_utilization` point of the curve, so jump-rate
+    /// curves can be modelled. If empty, the curve goes through the `target_utilization` point.
+    #[serde(default)]
+    pub rate_breakpoints: Vec<RateBreakpoint>,
     /// Volatility ratio (multiplied by 10000).
     /// It defines which percentage collateral that covers borrowing as well as which percentage of
     /// borrowed asset can be taken.
@@ -48,6 +56,10 @@ pub struct AssetConfig {
     /// Now if you're trying to borrow $XYZ and it's volatility ratio is 80%, then you can only
     /// borrow less than 80% of $600 = $480 of XYZ before liquidation can begin.
     pub volatility_ratio: u32,
+    /// The liquidation bonus of this asset when it's taken as a collateral (multiplied by 10000).
+    /// The taken collateral value is discounted by this ratio, so the liquidator needs to repay less.
+    /// E.g. 500 means 5%. A liquidator can take 100$ of this asset by repaying 95$.
+    pub liquidation_bonus: u32,
     /// The amount of extra decimals to use for the fungible token. For example, if the asset like
     /// USDT has `6` decimals in the metadata, the `extra_decimals` can be set to `12`, to make the
     /// inner balance of USDT at `18` decimals.
@@ -60,6 +72,63 @@ pub struct AssetConfig {
     pub can_use_as_collateral: bool,
     /// Whether this assets can be borrowed.
     pub can_borrow: bool,
+    /// Whether this assets can be borrowed at a stable rate. The stable rate is fixed at the
+    /// borrowing rate at the borrow time.
+    #[serde(default)]
+    pub can_borrow_stable: bool,
+    /// The spread between the borrowing APR and the stable APR of an account debt (multiplied by
+    /// 10000) that allows the owner to rebalance the stable rate of this debt.
+    /// E.g. 500 means the stable rate can be rebalanced once it's 5% APR off the borrowing APR.
+    #[serde(default)]
+    pub stable_rate_rebalance_threshold: u32,
+    /// Whether this assets can be flash loaned.
+    #[serde(default)]
+    pub can_flash_loan: bool,
+    /// The fee of a flash loan relative to the loaned amount (multiplied by 10000).
+    /// E.g. 9 means 0.09%. The fee is split between the reserve and the suppliers based on the
+    /// `reserve_ratio`.
+    #[serde(default)]
+    pub flash_loan_fee: u32,
+    /// Whether this asset is an isolated collateral. The isolated collateral can't be combined
+    /// with other collateral and only the assets with `can_borrow_in_isolation` can be borrowed
+    /// against it.
+    #[serde(default)]
+    pub isolated: bool,
+    /// The maximum total value in USD of the debt borrowed against this isolated collateral.
+    #[serde(default)]
+    pub isolated_debt_ceiling: u64,
+    /// Whether this asset can be borrowed against an isolated collateral.
+    #[serde(default)]
+    pub can_borrow_in_isolation: bool,
+    /// The maximum total supplied amount of this asset (with the `extra_decimals`). New deposits
+    /// that exceed it are rejected. No cap if it's not set.
+    #[serde(default)]
+    pub supply_cap: Option<U128>,
+    /// The maximum total borrowed amount of this asset (with the `extra_decimals`), including the
+    /// stable debt. New borrows that exceed it are rejected. No cap if it's not set.
+    #[serde(default)]
+    pub borrow_cap: Option<U128>,
+}
+
+/// Represents a breakpoint of the interest rate curve.
+/// Example:
+/// 90% utilization, 20% APR
+/// JSON:
+/// ```json
+/// {
+///   "utilization": 9000,
+///   "rate": "1000000000005781378640109134"
+/// }
+/// ```
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug))]
+#[serde(crate = "near_sdk::serde")]
+pub struct RateBreakpoint {
+    /// Utilization ratio of the breakpoint (multiplied by 10000).
+    pub utilization: u32,
+    /// The compounding rate at the utilization ratio of the breakpoint.
+    /// Use `apr_to_rate.py` script to compute the value for a given APR.
+    pub rate: LowU128,
 }
 
 impl AssetConfig {
@@ -67,8 +136,35 @@ impl AssetConfig {
         assert!(self.reserve_ratio <= MAX_RATIO);
         assert!(self.target_utilization < MAX_POS);
         assert!(self.target_utilization_rate.0 <= self.max_utilization_rate.0);
+        let mut prev_utilization = 0;
+        let mut prev_rate = LowU128::from(BigDecimal::one()).0;
+        for breakpoint in &self.rate_breakpoints {
+            assert!(
+                breakpoint.utilization > prev_utilization && breakpoint.utilization < MAX_POS,
+                "The rate breakpoints should be sorted by the utilization between 0% and 100%"
+            );
+            assert!(
+                breakpoint.rate.0 >= prev_rate,
+                "The rate breakpoints can't decrease the rate"
+            );
+            prev_utilization = breakpoint.utilization;
+            prev_rate = breakpoint.rate.0;
+        }
+        if let Some(breakpoint) = self.rate_breakpoints.last() {
+            assert!(
+                breakpoint.rate.0 <= self.max_utilization_rate.0,
+                "The rate breakpoints can't exceed the max utilization rate"
+            );
+        }
         // The volatility ratio can't be 100% to avoid free liquidations of such assets.
         assert!(self.volatility_ratio < MAX_RATIO);
+        assert!(self.liquidation_bonus < MAX_RATIO);
+        assert!(self.flash_loan_fee <= MAX_RATIO);
+        assert!(
+            self.supply_cap.map(|cap| cap.0 > 0).unwrap_or(true)
+                && self.borrow_cap.map(|cap| cap.0 > 0).unwrap_or(true),
+            "The caps can't be 0"
+        );
     }
 
     pub fn get_rate(
@@ -80,6 +176,9 @@ impl AssetConfig {
             BigDecimal::one()
         } else {
             let pos = BigDecimal::from(borrowed_balance).div_u128(total_supplied_balance);
+            if !self.rate_breakpoints.is_empty() {
+                return self.get_breakpoints_rate(pos);
+            }
             let target_utilization = BigDecimal::from_ratio(self.target_utilization);
             if pos < target_utilization {
                 BigDecimal::one()
@@ -94,6 +193,30 @@ impl AssetConfig {
             }
         }
     }
+
+    /// Returns the rate at the given utilization on the curve going through the rate breakpoints.
+    /// The rate is capped by `max_utilization_rate` above 100% utilization.
+    fn get_breakpoints_rate(&self, pos: BigDecimal) -> BigDecimal {
+        let mut prev_utilization = BigDecimal::zero();
+        let mut prev_rate = BigDecimal::one();
+        let points = self
+            .rate_breakpoints
+            .iter()
+            .map(|breakpoint| (breakpoint.utilization, breakpoint.rate))
+            .chain(std::iter::once((MAX_POS, self.max_utilization_rate)));
+        for (utilization, rate) in points {
+            let utilization = BigDecimal::from_ratio(utilization);
+            let rate = BigDecimal::from(rate);
+            if pos < utilization {
+                return prev_rate
+                    + (pos - prev_utilization) * (rate - prev_rate)
+                        / (utilization - prev_utilization);
+            }
+            prev_utilization = utilization;
+            prev_rate = rate;
+        }
+        prev_rate
+    }
 }
 
 #[cfg(test)]
@@ -102,18 +225,35 @@ mod tests {
 
     const ONE_NEAR: u128 = 10u128.pow(24);
 
+    fn assert_rate_eq(a: BigDecimal, b: BigDecimal) {
+        let (a, b) = (LowU128::from(a).0, LowU128::from(b).0);
+        // The rates may differ in the last digits due to the rounding.
+        assert!(a.max(b) - a.min(b) < 100, "Expected {} to eq {}", a, b);
+    }
+
     fn test_config() -> AssetConfig {
         AssetConfig {
             reserve_ratio: 2500,
             target_utilization: 8000,
             target_utilization_rate: 1000000000003593629036885046u128.into(),
             max_utilization_rate: 1000000000039724853136740579u128.into(),
+            rate_breakpoints: vec![],
             volatility_ratio: 6000,
+            liquidation_bonus: 500,
             extra_decimals: 0,
             can_deposit: true,
             can_withdraw: true,
             can_use_as_collateral: true,
             can_borrow: true,
+            can_borrow_stable: false,
+            stable_rate_rebalance_threshold: 0,
+            can_flash_loan: false,
+            flash_loan_fee: 0,
+            isolated: false,
+            isolated_debt_ceiling: 0,
+            can_borrow_in_isolation: false,
+            supply_cap: None,
+            borrow_cap: None,
         }
     }
 
@@ -126,4 +266,66 @@ mod tests {
         let apr = rate.pow(MS_PER_YEAR) - BigDecimal::one();
         println!("APR: {}", apr)
     }
+
+    #[test]
+    fn test_get_rate_target_breakpoint() {
+        let config = test_config();
+        let mut breakpoints_config = test_config();
+        breakpoints_config.rate_breakpoints = vec![RateBreakpoint {
+            utilization: config.target_utilization,
+            rate: config.target_utilization_rate,
+        }];
+        breakpoints_config.assert_valid();
+        for borrowed in [0, 40, 80, 81, 95, 100] {
+            assert_rate_eq(
+                config.get_rate(borrowed * ONE_NEAR, 100 * ONE_NEAR),
+                breakpoints_config.get_rate(borrowed * ONE_NEAR, 100 * ONE_NEAR),
+            );
+        }
+    }
+
+    #[test]
+    fn test_get_rate_jump_curve() {
+        let mut config = test_config();
+        // 12% APR at 80% utilization, 20% APR at 90% utilization.
+        config.rate_breakpoints = vec![
+            RateBreakpoint {
+                utilization: 8000,
+                rate: 1000000000003593629036885046u128.into(),
+            },
+            RateBreakpoint {
+                utilization: 9000,
+                rate: 1000000000005781378640109134u128.into(),
+            },
+        ];
+        config.assert_valid();
+
+        let rate_80 = config.get_rate(80 * ONE_NEAR, 100 * ONE_NEAR);
+        let rate_85 = config.get_rate(85 * ONE_NEAR, 100 * ONE_NEAR);
+        let rate_90 = config.get_rate(90 * ONE_NEAR, 100 * ONE_NEAR);
+        let rate_100 = config.get_rate(100 * ONE_NEAR, 100 * ONE_NEAR);
+        assert_rate_eq(rate_80, config.rate_breakpoints[0].rate.into());
+        assert_rate_eq(rate_90, config.rate_breakpoints[1].rate.into());
+        assert_rate_eq(rate_100, config.max_utilization_rate.into());
+        assert!(rate_80 < rate_85 && rate_85 < rate_90);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "The rate breakpoints should be sorted by the utilization between 0% and 100%"
+    )]
+    fn test_unsorted_rate_breakpoints() {
+        let mut config = test_config();
+        config.rate_breakpoints = vec![
+            RateBreakpoint {
+                utilization: 9000,
+                rate: 1000000000003593629036885046u128.into(),
+            },
+            RateBreakpoint {
+                utilization: 8000,
+                rate: 1000000000005781378640109134u128.into(),
+            },
+        ];
+        config.assert_valid();
+    }
 }
@@ -218,7 +218,8 @@ impl Contract {
         for token_id in self.asset_ids.iter() {
             farm_ids.push(FarmId::Supplied(token_id.clone()));
             farm_ids.push(FarmId::Borrowed(token_id.clone()));
-            farm_ids.push(FarmId::SuppliedNFT(token_id));
+            farm_ids.push(FarmId::SuppliedNFT(token_id.clone()));
+            farm_ids.push(FarmId::Collateral(token_id));
         }
         self.get_asset_farms(farm_ids)
     }
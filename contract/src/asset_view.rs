@@ -9,11 +9,19 @@ pub struct AssetDetailedView {
     pub supplied: Pool,
     /// Total borrowed.
     pub borrowed: Pool,
-    pub nft_supplied: Vec<NftPool>,
+    /// Total borrowed at a stable rate including the accrued interest.
+    #[serde(with = "u128_dec_format")]
+    pub stable_borrowed: Balance,
+    /// The debt amounts borrowed against this asset, if it's an isolated collateral.
+    pub isolated_debt: HashMap<TokenId, U128>,
     /// The amount reserved for the stability. This amount can also be borrowed and affects
     /// borrowing rate.
     #[serde(with = "u128_dec_format")]
     pub reserved: Balance,
+    /// The total bad debt that wasn't covered by the reserve and was written off against the
+    /// supplied balance.
+    #[serde(with = "u128_dec_format")]
+    pub socialized_bad_debt: Balance,
     /// When the asset was last updated. It's always going to be the current block timestamp.
     #[serde(with = "u64_dec_format")]
     pub last_update_timestamp: Timestamp,
@@ -23,6 +31,13 @@ pub struct AssetDetailedView {
     pub supply_apr: BigDecimal,
     /// Current APR excluding farms for borrowing the asset.
     pub borrow_apr: BigDecimal,
+    /// The average APR of the stable debt.
+    pub stable_borrow_apr: BigDecimal,
+    /// The supplied amount relative to the supply cap, if the asset has one.
+    pub supply_cap_usage: Option<BigDecimal>,
+    /// The borrowed amount including the stable debt relative to the borrow cap, if the asset
+    /// has one.
+    pub borrow_cap_usage: Option<BigDecimal>,
     /// Asset farms
     pub farms: Vec<AssetFarmView>,
 }
@@ -43,6 +58,7 @@ impl Contract {
                 FarmId::Supplied(token_id.clone()),
                 FarmId::Borrowed(token_id.clone()),
                 FarmId::SuppliedNFT(token_id.clone()),
+                FarmId::Collateral(token_id.clone()),
             ])
             .into_iter()
             .map(|(farm_id, asset_farm)| AssetFarmView {
@@ -52,11 +68,18 @@ impl Contract {
             .collect();
         let supply_apr = asset.get_supply_apr();
         let borrow_apr = asset.get_borrow_apr();
+        let stable_borrow_apr = asset.get_stable_borrow_apr();
+        let supply_cap_usage = asset.get_supply_cap_usage();
+        let borrow_cap_usage = asset.get_borrow_cap_usage();
         let Asset {
             supplied,
             borrowed,
-            nft_supplied,
+            stable_borrowed,
+            average_stable_rate: _,
+            flash_loaned: _,
+            isolated_debt,
             reserved,
+            socialized_bad_debt,
             last_update_timestamp,
             config,
         } = asset;
@@ -64,12 +87,17 @@ impl Contract {
             token_id,
             supplied,
             borrowed,
-            nft_supplied,
+            stable_borrowed,
+            isolated_debt,
             reserved,
+            socialized_bad_debt,
             last_update_timestamp,
             config,
             supply_apr,
             borrow_apr,
+            stable_borrow_apr,
+            supply_cap_usage,
+            borrow_cap_usage,
             farms,
         }
     }
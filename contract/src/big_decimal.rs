@@ -196,6 +196,36 @@ impl BigDecimal {
         }
     }
 
+    fn to_balance_price_fraction(self, price: &Price, extra_decimals: u8) -> (U384, U384) {
+        let denominator_decimals = price.decimals + extra_decimals;
+        if denominator_decimals > NUM_DECIMALS {
+            (
+                self.0 * U384::exp10((denominator_decimals - NUM_DECIMALS) as usize),
+                U384::from(price.multiplier),
+            )
+        } else {
+            (
+                self.0,
+                U384::from(price.multiplier)
+                    * U384::exp10((NUM_DECIMALS - denominator_decimals) as usize),
+            )
+        }
+    }
+
+    /// Returns the balance of the token with the given price that has this value.
+    /// The balance is rounded down.
+    pub fn to_balance_price(&self, price: &Price, extra_decimals: u8) -> Balance {
+        let (num, denom) = self.to_balance_price_fraction(price, extra_decimals);
+        (num / denom).as_u128()
+    }
+
+    /// Returns the balance of the token with the given price that has this value.
+    /// The balance is rounded up.
+    pub fn to_balance_price_round_up(&self, price: &Price, extra_decimals: u8) -> Balance {
+        let (num, denom) = self.to_balance_price_fraction(price, extra_decimals);
+        ((num + denom - U384::one()) / denom).as_u128()
+    }
+
     pub fn round_u128(&self) -> u128 {
         ((self.0 + U384::from(HALF_DIVISOR)) / U384::from(BIG_DIVISOR)).as_u128()
     }
@@ -369,6 +399,27 @@ mod tests {
         almost_eq(val, initial_val * 2, 15);
     }
 
+    #[test]
+    fn test_to_balance_price_round_up() {
+        let price = Price {
+            multiplier: 1234,
+            decimals: 28,
+        };
+        let balance = 12345 * 10u128.pow(24);
+        let value = BigDecimal::from_balance_price(balance, &price, 0);
+        assert_eq!(value.to_balance_price_round_up(&price, 0), balance);
+
+        let price = Price {
+            multiplier: 3,
+            decimals: 4,
+        };
+        let value = BigDecimal::from(1u32);
+        assert_eq!(value.to_balance_price_round_up(&price, 0), 3334);
+        assert_eq!(value.to_balance_price_round_up(&price, 2), 333334);
+        assert_eq!(value.to_balance_price(&price, 0), 3333);
+        assert_eq!(value.to_balance_price(&price, 2), 333333);
+    }
+
     #[test]
     fn test_display() {
         assert_eq!("1.0", BigDecimal::one().to_string());
@@ -11,9 +11,31 @@ pub struct Config {
     /// The account ID of the oracle contract
     pub oracle_account_id: AccountId,
 
+    /// The account IDs of the additional oracle contracts. The latest prices of all oracles that
+    /// are not stale are aggregated into the median price.
+    pub extra_oracle_account_ids: Vec<AccountId>,
+
+    /// The maximum deviation of an oracle price from the median price (multiplied by 10000).
+    /// The majority of the oracle prices has to be within this deviation.
+    /// E.g. 200 means the prices can deviate up to 2% from the median price.
+    pub max_price_deviation: u32,
+
+    /// The number of the latest aggregated prices kept per asset to compute the TWAP.
+    /// 0 disables the price history.
+    pub price_history_size: u32,
+
+    /// The duration of the time-weighted average price window in seconds. If greater than 0, the
+    /// actions executed through the oracle, e.g. borrowing and liquidations, use the TWAP instead
+    /// of the latest prices, so a single-block price spike doesn't affect them.
+    pub twap_duration_sec: DurationSec,
+
     /// The account ID of the contract owner that allows to modify config, assets and use reserves.
     pub owner_id: AccountId,
 
+    /// The account ID of the guardian that allows to pause the contract or individual operations.
+    /// Only the owner can unpause them.
+    pub guardian_id: Option<AccountId>,
+
     /// The account ID of the booster token contract.
     pub booster_token_id: TokenId,
 
@@ -46,6 +68,24 @@ pub struct Config {
     /// Whether an account with bad debt can be liquidated using reserves.
     /// The account should have borrowed sum larger than the collateral sum.
     pub force_closing_enabled: bool,
+
+    /// The maximum ratio of the borrowed sum of an account that can be repaid in a single
+    /// liquidation (multiplied by 10000).
+    /// E.g. 5000 means a liquidator can repay up to 50% of the total borrowed value at once.
+    pub close_factor: u32,
+
+    /// The duration of an NFT auction in seconds. The price of an auctioned NFT declines linearly
+    /// from the start price to the end price during this duration and then stays at the end price.
+    /// If 0, the NFT collateral is liquidated with `LiquidateNFT` instead of auctions.
+    pub nft_auction_duration_sec: DurationSec,
+
+    /// The start price of an NFT auction relative to the NFT value (multiplied by 10000).
+    /// E.g. 12000 means the auction starts at 120% of the NFT value.
+    pub nft_auction_start_price_ratio: u32,
+
+    /// The end price of an NFT auction relative to the NFT value (multiplied by 10000).
+    /// E.g. 5000 means the auction ends at 50% of the NFT value.
+    pub nft_auction_end_price_ratio: u32,
 }
 
 impl Config {
@@ -58,6 +98,23 @@ impl Config {
             self.x_booster_multiplier_at_maximum_staking_duration >= MIN_BOOSTER_MULTIPLIER,
             "xBooster multiplier should be no less than 100%"
         );
+        assert!(
+            self.max_price_deviation <= MAX_RATIO,
+            "The max price deviation should be no more than 100%"
+        );
+        assert!(
+            self.twap_duration_sec == 0 || self.price_history_size > 0,
+            "The TWAP requires the price history"
+        );
+        assert!(
+            self.close_factor > 0 && self.close_factor <= MAX_RATIO,
+            "The close factor should be greater than 0% and no more than 100%"
+        );
+        assert!(
+            self.nft_auction_end_price_ratio > 0
+                && self.nft_auction_end_price_ratio <= self.nft_auction_start_price_ratio,
+            "The NFT auction end price ratio should be greater than 0% and no more than the start price ratio"
+        );
     }
 }
 
@@ -132,6 +189,36 @@ impl Contract {
         self.internal_set_asset(&token_id, asset);
     }
 
+    /// Withdraws a given amount (or the maximum possible amount) from the reserve of the asset
+    /// with a given token_id and transfers it to a given receiver_id, e.g. the treasury account.
+    /// The withdrawal is limited by the available amount of the asset, so the borrowed part of the
+    /// reserve can't be withdrawn. If the transfer fails, the amount is returned to the reserve.
+    /// - Panics if an asset with the given token_id doesn't exist.
+    /// - Panics if the asset doesn't have enough reserved or available balance.
+    /// - Requires one yoctoNEAR.
+    /// - Requires to be called by the contract owner.
+    #[payable]
+    pub fn withdraw_reserve(
+        &mut self,
+        token_id: AccountId,
+        receiver_id: AccountId,
+        amount: Option<U128>,
+    ) -> Promise {
+        assert_one_yocto();
+        self.assert_owner();
+        let mut asset = self.internal_unwrap_asset(&token_id);
+        let max_amount = std::cmp::min(asset.reserved, asset.available_amount());
+        let amount = amount.map(|a| a.0).unwrap_or(max_amount);
+        assert!(amount > 0, "Amount can't be 0");
+        assert!(
+            amount <= max_amount,
+            "Not enough available reserved balance"
+        );
+        asset.reserved -= amount;
+        self.internal_set_asset(&token_id, asset);
+        self.internal_ft_transfer_reserve(&receiver_id, &token_id, amount)
+    }
+
     /// Adds an asset farm reward for the farm with a given farm_id. The reward is of token_id with
     /// the new reward per day amount and a new booster log base. The extra amount of reward is
     /// taken from the asset reserved balance.
@@ -154,7 +241,10 @@ impl Contract {
         assert_one_yocto();
         self.assert_owner();
         match &farm_id {
-            FarmId::Supplied(token_id) | FarmId::Borrowed(token_id) |  FarmId::SuppliedNFT(token_id) => {
+            FarmId::Supplied(token_id)
+            | FarmId::Borrowed(token_id)
+            | FarmId::SuppliedNFT(token_id)
+            | FarmId::Collateral(token_id) => {
                 assert!(self.assets.contains_key(token_id));
             }
         };
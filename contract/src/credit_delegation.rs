@@ -0,0 +1,151 @@
+use crate::*;
+
+#[derive(Serialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug, Deserialize))]
+#[serde(crate = "near_sdk::serde")]
+pub struct BorrowAllowanceView {
+    pub delegatee_id: AccountId,
+    pub token_id: TokenId,
+    /// The remaining amount of the asset the delegatee can borrow.
+    #[serde(with = "u128_dec_format")]
+    pub allowance: Balance,
+}
+
+impl Account {
+    pub fn get_borrow_allowance(&self, delegatee_id: &AccountId, token_id: &TokenId) -> Balance {
+        self.borrow_allowances
+            .get(delegatee_id)
+            .and_then(|allowances| allowances.get(token_id))
+            .map(|allowance| allowance.0)
+            .unwrap_or(0)
+    }
+
+    /// Sets the amount of the asset the delegatee can borrow against the collateral of this
+    /// account. The zero allowance removes the approval.
+    pub fn set_borrow_allowance(
+        &mut self,
+        delegatee_id: &AccountId,
+        token_id: &TokenId,
+        allowance: Balance,
+    ) {
+        let mut allowances = self
+            .borrow_allowances
+            .remove(delegatee_id)
+            .unwrap_or_default();
+        if allowance > 0 {
+            allowances.insert(token_id.clone(), allowance.into());
+        } else {
+            allowances.remove(token_id);
+        }
+        if !allowances.is_empty() {
+            self.borrow_allowances
+                .insert(delegatee_id.clone(), allowances);
+        }
+    }
+
+    pub fn consume_borrow_allowance(
+        &mut self,
+        delegatee_id: &AccountId,
+        token_id: &TokenId,
+        amount: Balance,
+    ) {
+        let allowance = self.get_borrow_allowance(delegatee_id, token_id);
+        assert!(
+            amount <= allowance,
+            "The borrowed amount {} of {} exceeds the allowance {}",
+            amount,
+            token_id,
+            allowance
+        );
+        self.set_borrow_allowance(delegatee_id, token_id, allowance - amount);
+    }
+
+    pub fn get_borrow_allowance_views(&self) -> Vec<BorrowAllowanceView> {
+        self.borrow_allowances
+            .iter()
+            .flat_map(|(delegatee_id, allowances)| {
+                allowances
+                    .iter()
+                    .map(move |(token_id, allowance)| BorrowAllowanceView {
+                        delegatee_id: delegatee_id.clone(),
+                        token_id: token_id.clone(),
+                        allowance: allowance.0,
+                    })
+            })
+            .collect()
+    }
+}
+
+impl Contract {
+    /// Borrows the asset against the collateral of the delegator, and deposits the borrowed
+    /// amount to the supplied balance of the given account. The debt stays on the delegator, and
+    /// the borrowed amount is taken from the allowance the delegator approved for the account.
+    /// The delegator goes through the same checks as the borrowing account.
+    pub fn internal_borrow_delegated(
+        &mut self,
+        account_id: &AccountId,
+        account: &mut Account,
+        delegator_id: &AccountId,
+        asset_amount: &AssetAmount,
+        prices: &Prices,
+    ) -> Balance {
+        assert_ne!(
+            account_id, delegator_id,
+            "Use the Borrow action to borrow against your own collateral"
+        );
+        let token_id = &asset_amount.token_id;
+        let mut delegator = self.internal_unwrap_account(delegator_id);
+        let pre_delegator = delegator.clone();
+        delegator.add_affected_farm(FarmId::Borrowed(token_id.clone()));
+
+        let supplied_shares = delegator.internal_get_asset_or_default(token_id).shares;
+        let amount = self.internal_borrow(&mut delegator, asset_amount);
+        delegator.consume_borrow_allowance(account_id, token_id, amount);
+
+        // The borrowed amount is moved from the delegator to the account.
+        let mut delegator_asset = delegator.internal_unwrap_asset(token_id);
+        let shares = Shares::from(delegator_asset.shares.0 - supplied_shares.0);
+        delegator_asset.withdraw_shares(shares);
+        delegator.internal_set_asset(token_id, delegator_asset);
+        let mut account_asset = account.internal_get_asset_or_default(token_id);
+        account_asset.deposit_shares(shares);
+        account.internal_set_asset(token_id, account_asset);
+
+        assert!(
+            delegator.collateral.len() + delegator.borrowed.len() + delegator.stable_borrowed.len()
+                <= self.internal_config().max_num_assets as _
+        );
+        self.assert_e_mode_valid(&pre_delegator, &delegator);
+        if let Some(isolated_token_id) =
+            self.internal_update_isolated_debt(&pre_delegator, &delegator)
+        {
+            self.assert_isolated_debt_ceiling(&isolated_token_id, prices);
+        }
+        assert!(
+            self.compute_max_discount(&pre_delegator, &delegator, prices) == BigDecimal::zero(),
+            "The delegator doesn't have enough collateral"
+        );
+
+        self.internal_account_apply_affected_farms(&mut delegator);
+        self.internal_set_account(delegator_id, delegator);
+
+        amount
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Returns the remaining amount of the asset the delegatee can borrow against the collateral
+    /// of the given account.
+    pub fn get_borrow_allowance(
+        &self,
+        account_id: AccountId,
+        delegatee_id: AccountId,
+        token_id: TokenId,
+    ) -> U128 {
+        self.internal_get_account(&account_id)
+            .map(|account| account.get_borrow_allowance(&delegatee_id, &token_id))
+            .unwrap_or(0)
+            .into()
+    }
+}
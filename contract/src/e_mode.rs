@@ -0,0 +1,223 @@
+use crate::*;
+
+pub type EModeCategoryId = u32;
+
+/// Represents an efficiency mode category of correlated assets, e.g. stablecoins. An account that
+/// opted into the category can only use the assets of the category as a collateral and borrow
+/// them, and their ratios replace the volatility ratios of these assets.
+/// Example:
+/// Stablecoins, 97% collateral ratio, 97% borrow ratio
+/// JSON:
+/// ```json
+/// {
+///   "label": "Stablecoins",
+///   "token_ids": ["dai.fakes.testnet", "usdc.fakes.testnet", "usdt.fakes.testnet"],
+///   "collateral_ratio": 9700,
+///   "borrow_ratio": 9700
+/// }
+/// ```
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug))]
+#[serde(crate = "near_sdk::serde")]
+pub struct EModeCategory {
+    /// The name of the category.
+    pub label: String,
+    /// The assets of the category.
+    pub token_ids: Vec<TokenId>,
+    /// The ratio of the collateral value of the category assets that can back borrowing
+    /// (multiplied by 10000). Replaces the volatility ratio of the collateral.
+    pub collateral_ratio: u32,
+    /// The ratio the borrowed value of the category assets is divided by (multiplied by 10000).
+    /// Replaces the volatility ratio of the borrowed assets.
+    pub borrow_ratio: u32,
+}
+
+impl EModeCategory {
+    pub fn assert_valid(&self) {
+        assert!(
+            !self.token_ids.is_empty(),
+            "The e-mode category should have assets"
+        );
+        // The ratios can't be 100% to avoid free liquidations, same as the volatility ratio.
+        assert!(
+            self.collateral_ratio > 0 && self.collateral_ratio < MAX_RATIO,
+            "The collateral ratio should be greater than 0% and less than 100%"
+        );
+        assert!(
+            self.borrow_ratio > 0 && self.borrow_ratio < MAX_RATIO,
+            "The borrow ratio should be greater than 0% and less than 100%"
+        );
+    }
+
+    pub fn contains(&self, token_id: &TokenId) -> bool {
+        self.token_ids.contains(token_id)
+    }
+
+    pub fn assert_contains(&self, token_id: &TokenId) {
+        assert!(
+            self.contains(token_id),
+            "The asset {} is not in the e-mode category {}",
+            token_id,
+            self.label
+        );
+    }
+}
+
+/// Returns the ratio of the collateral value of the asset that can back borrowing. It's the
+/// category ratio, if the asset is in the e-mode category of the account.
+pub fn get_collateral_ratio(
+    e_mode_category: Option<&EModeCategory>,
+    token_id: &TokenId,
+    asset_config: &AssetConfig,
+) -> u32 {
+    match e_mode_category {
+        Some(category) if category.contains(token_id) => category.collateral_ratio,
+        _ => asset_config.volatility_ratio,
+    }
+}
+
+/// Returns the ratio the borrowed value of the asset is divided by. It's the category ratio, if
+/// the asset is in the e-mode category of the account.
+pub fn get_borrow_ratio(
+    e_mode_category: Option<&EModeCategory>,
+    token_id: &TokenId,
+    asset_config: &AssetConfig,
+) -> u32 {
+    match e_mode_category {
+        Some(category) if category.contains(token_id) => category.borrow_ratio,
+        _ => asset_config.volatility_ratio,
+    }
+}
+
+impl Contract {
+    /// Returns the e-mode category the account opted into, if it still exists.
+    pub fn internal_get_e_mode_category(&self, account: &Account) -> Option<EModeCategory> {
+        account
+            .e_mode_category_id
+            .and_then(|category_id| self.e_mode_categories.get(&category_id))
+    }
+
+    pub fn internal_set_e_mode(
+        &self,
+        account: &mut Account,
+        e_mode_category_id: Option<EModeCategoryId>,
+    ) {
+        if let Some(category_id) = e_mode_category_id {
+            assert!(
+                self.e_mode_categories.get(&category_id).is_some(),
+                "E-mode category {} not found",
+                category_id
+            );
+        }
+        account.e_mode_category_id = e_mode_category_id;
+    }
+
+    /// Asserts that all collateral and borrowed assets of the account are in its e-mode category.
+    /// Only the increased positions are checked, unless the account has just switched the
+    /// category, so the account can always reduce the positions in the assets that the owner
+    /// removed from the category.
+    pub fn assert_e_mode_valid(&self, pre_account: &Account, account: &Account) {
+        let e_mode_category = match self.internal_get_e_mode_category(account) {
+            Some(e_mode_category) => e_mode_category,
+            None => return,
+        };
+        let e_mode_changed = pre_account.e_mode_category_id != account.e_mode_category_id;
+        let token_ids = account
+            .collateral
+            .iter()
+            .filter(|(token_id, shares)| {
+                e_mode_changed
+                    || pre_account
+                        .collateral
+                        .get(*token_id)
+                        .map(|s| s.0)
+                        .unwrap_or(0)
+                        < shares.0
+            })
+            .map(|(token_id, _)| token_id)
+            .chain(
+                account
+                    .borrowed
+                    .iter()
+                    .filter(|(token_id, shares)| {
+                        e_mode_changed
+                            || pre_account
+                                .borrowed
+                                .get(*token_id)
+                                .map(|s| s.0)
+                                .unwrap_or(0)
+                                < shares.0
+                    })
+                    .map(|(token_id, _)| token_id),
+            )
+            .chain(
+                account
+                    .stable_borrowed
+                    .iter()
+                    .filter(|(token_id, stable_debt)| {
+                        e_mode_changed
+                            || pre_account
+                                .stable_borrowed
+                                .get(*token_id)
+                                .map(|pre_stable_debt| pre_stable_debt.get_amount())
+                                .unwrap_or(0)
+                                < stable_debt.get_amount()
+                    })
+                    .map(|(token_id, _)| token_id),
+            )
+            .chain(
+                account
+                    .nft_supplied
+                    .iter()
+                    .filter(|(nft_contract_token_id, _)| {
+                        e_mode_changed
+                            || !pre_account
+                                .nft_supplied
+                                .contains_key(*nft_contract_token_id)
+                    })
+                    .map(|(_, account_nft_asset)| &account_nft_asset.nft_contract_id),
+            );
+        for token_id in token_ids {
+            e_mode_category.assert_contains(token_id);
+        }
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Returns the e-mode category with the given id.
+    pub fn get_e_mode_category(&self, category_id: EModeCategoryId) -> Option<EModeCategory> {
+        self.e_mode_categories.get(&category_id)
+    }
+
+    /// Returns a list of pairs (category_id, category) for all e-mode categories.
+    pub fn get_e_mode_categories(&self) -> Vec<(EModeCategoryId, EModeCategory)> {
+        self.e_mode_categories.to_vec()
+    }
+
+    /// Adds or updates the e-mode category with the given id.
+    /// The accounts in this category keep their positions, even if an asset is removed from it.
+    /// - Panics if the category is invalid.
+    /// - Panics if an asset of the category doesn't exist.
+    /// - Requires one yoctoNEAR.
+    /// - Requires to be called by the contract owner.
+    #[payable]
+    pub fn update_e_mode_category(
+        &mut self,
+        category_id: EModeCategoryId,
+        e_mode_category: EModeCategory,
+    ) {
+        assert_one_yocto();
+        e_mode_category.assert_valid();
+        self.assert_owner();
+        for token_id in &e_mode_category.token_ids {
+            assert!(
+                self.assets.contains_key(token_id),
+                "Asset {} not found",
+                token_id
+            );
+        }
+        self.e_mode_categories
+            .insert(&category_id, &e_mode_category);
+    }
+}
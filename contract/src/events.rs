@@ -43,6 +43,28 @@ pub mod emit {
         );
     }
 
+    pub fn reserve_withdrawn(account_id: &AccountId, amount: Balance, token_id: &TokenId) {
+        log_event(
+            "reserve_withdrawn",
+            AccountAmountToken {
+                account_id,
+                amount,
+                token_id,
+            },
+        );
+    }
+
+    pub fn reserve_withdraw_failed(account_id: &AccountId, amount: Balance, token_id: &TokenId) {
+        log_event(
+            "reserve_withdraw_failed",
+            AccountAmountToken {
+                account_id,
+                amount,
+                token_id,
+            },
+        );
+    }
+
     pub fn deposit(account_id: &AccountId, amount: Balance, token_id: &TokenId) {
         log_event(
             "deposit",
@@ -143,6 +165,28 @@ pub mod emit {
         );
     }
 
+    pub fn increase_collateral(account_id: &AccountId, amount: Balance, token_id: &TokenId) {
+        log_event(
+            "increase_collateral",
+            AccountAmountToken {
+                account_id,
+                amount,
+                token_id,
+            },
+        );
+    }
+
+    pub fn decrease_collateral(account_id: &AccountId, amount: Balance, token_id: &TokenId) {
+        log_event(
+            "decrease_collateral",
+            AccountAmountToken {
+                account_id,
+                amount,
+                token_id,
+            },
+        );
+    }
+
     pub fn borrow(account_id: &AccountId, amount: Balance, token_id: &TokenId) {
         log_event(
             "borrow",
@@ -165,6 +209,171 @@ pub mod emit {
         );
     }
 
+    pub fn repay_for(
+        account_id: &AccountId,
+        repay_account_id: &AccountId,
+        position_id: Option<PositionId>,
+        amount: Balance,
+        token_id: &TokenId,
+    ) {
+        log_event(
+            "repay_for",
+            json!({
+                "account_id": account_id,
+                "repay_account_id": repay_account_id,
+                "position_id": position_id,
+                "amount": U128(amount),
+                "token_id": token_id,
+            }),
+        );
+    }
+
+    pub fn approve_borrow_allowance(
+        account_id: &AccountId,
+        delegatee_id: &AccountId,
+        allowance: Balance,
+        token_id: &TokenId,
+    ) {
+        log_event(
+            "approve_borrow_allowance",
+            json!({
+                "account_id": account_id,
+                "delegatee_id": delegatee_id,
+                "allowance": U128(allowance),
+                "token_id": token_id,
+            }),
+        );
+    }
+
+    pub fn borrow_delegated(
+        account_id: &AccountId,
+        delegator_id: &AccountId,
+        amount: Balance,
+        token_id: &TokenId,
+    ) {
+        log_event(
+            "borrow_delegated",
+            json!({
+                "account_id": account_id,
+                "delegator_id": delegator_id,
+                "amount": U128(amount),
+                "token_id": token_id,
+            }),
+        );
+    }
+
+    pub fn borrow_stable(account_id: &AccountId, amount: Balance, token_id: &TokenId) {
+        log_event(
+            "borrow_stable",
+            AccountAmountToken {
+                account_id,
+                amount,
+                token_id,
+            },
+        );
+    }
+
+    pub fn repay_stable(account_id: &AccountId, amount: Balance, token_id: &TokenId) {
+        log_event(
+            "repay_stable",
+            AccountAmountToken {
+                account_id,
+                amount,
+                token_id,
+            },
+        );
+    }
+
+    pub fn stable_rate_rebalanced(
+        account_id: &AccountId,
+        position_id: Option<PositionId>,
+        token_id: &TokenId,
+        old_rate: LowU128,
+        new_rate: LowU128,
+    ) {
+        log_event(
+            "stable_rate_rebalanced",
+            json!({
+                "account_id": account_id,
+                "position_id": position_id,
+                "token_id": token_id,
+                "old_rate": old_rate,
+                "new_rate": new_rate,
+            }),
+        );
+    }
+
+    pub fn set_e_mode(account_id: &AccountId, category_id: Option<EModeCategoryId>) {
+        log_event(
+            "set_e_mode",
+            json!({
+                "account_id": account_id,
+                "category_id": category_id,
+            }),
+        );
+    }
+
+    pub fn leverage(account_id: &AccountId, amount: Balance, token_id: &TokenId) {
+        log_event(
+            "leverage",
+            AccountAmountToken {
+                account_id,
+                amount,
+                token_id,
+            },
+        );
+    }
+
+    pub fn deleverage(account_id: &AccountId, amount: Balance, token_id: &TokenId) {
+        log_event(
+            "deleverage",
+            AccountAmountToken {
+                account_id,
+                amount,
+                token_id,
+            },
+        );
+    }
+
+    pub fn open_position(account_id: &AccountId, position_id: PositionId) {
+        log_event(
+            "open_position",
+            json!({
+                "account_id": account_id,
+                "position_id": position_id,
+            }),
+        );
+    }
+
+    pub fn close_position(account_id: &AccountId, position_id: PositionId) {
+        log_event(
+            "close_position",
+            json!({
+                "account_id": account_id,
+                "position_id": position_id,
+            }),
+        );
+    }
+
+    pub fn transfer_to_position(
+        account_id: &AccountId,
+        from_position_id: Option<PositionId>,
+        to_position_id: Option<PositionId>,
+        amount: Balance,
+        token_id: &TokenId,
+    ) {
+        log_event(
+            "transfer_to_position",
+            json!({
+                "account_id": account_id,
+                "from_position_id": from_position_id,
+                "to_position_id": to_position_id,
+                "amount": U128(amount),
+                "token_id": token_id,
+            }),
+        );
+    }
+
     pub fn liquidate(
         account_id: &AccountId,
         liquidation_account_id: &AccountId,
@@ -182,6 +391,36 @@ pub mod emit {
         );
     }
 
+    pub fn liquidation_credit(
+        liquidation_account_id: &AccountId,
+        amount: Balance,
+        token_id: &TokenId,
+    ) {
+        log_event(
+            "liquidation_credit",
+            AccountAmountToken {
+                account_id: liquidation_account_id,
+                amount,
+                token_id,
+            },
+        );
+    }
+
+    pub fn bad_debt_socialized(
+        liquidation_account_id: &AccountId,
+        amount: Balance,
+        token_id: &TokenId,
+    ) {
+        log_event(
+            "bad_debt_socialized",
+            AccountAmountToken {
+                account_id: liquidation_account_id,
+                amount,
+                token_id,
+            },
+        );
+    }
+
     pub fn force_close(
         liquidation_account_id: &AccountId,
         collateral_sum: &BigDecimal,
@@ -227,4 +466,167 @@ pub mod emit {
             }),
         );
     }
+
+    pub fn nft_auction_started(
+        account_id: &AccountId,
+        owner_id: &AccountId,
+        nft_contract_id: &NFTContractId,
+        token_id: &NFTTokenId,
+    ) {
+        log_event(
+            "nft_auction_started",
+            json!({
+                "account_id": account_id,
+                "owner_id": owner_id,
+                "nft_contract_id": nft_contract_id,
+                "token_id": token_id,
+            }),
+        );
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn nft_auction_bought(
+        account_id: &AccountId,
+        owner_id: &AccountId,
+        nft_contract_id: &NFTContractId,
+        token_id: &NFTTokenId,
+        amount: Balance,
+        repaid_amount: Balance,
+        surplus_amount: Balance,
+        payment_token_id: &TokenId,
+    ) {
+        log_event(
+            "nft_auction_bought",
+            json!({
+                "account_id": account_id,
+                "owner_id": owner_id,
+                "nft_contract_id": nft_contract_id,
+                "token_id": token_id,
+                "amount": U128(amount),
+                "repaid_amount": U128(repaid_amount),
+                "surplus_amount": U128(surplus_amount),
+                "payment_token_id": payment_token_id,
+            }),
+        );
+    }
+
+    pub fn nft_auction_canceled(
+        account_id: &AccountId,
+        owner_id: &AccountId,
+        nft_contract_id: &NFTContractId,
+        token_id: &NFTTokenId,
+    ) {
+        log_event(
+            "nft_auction_canceled",
+            json!({
+                "account_id": account_id,
+                "owner_id": owner_id,
+                "nft_contract_id": nft_contract_id,
+                "token_id": token_id,
+            }),
+        );
+    }
+
+    pub fn flash_loan(
+        account_id: &AccountId,
+        receiver_id: &AccountId,
+        amount: Balance,
+        fee: Balance,
+        token_id: &TokenId,
+    ) {
+        log_event(
+            "flash_loan",
+            json!({
+                "account_id": account_id,
+                "receiver_id": receiver_id,
+                "amount": U128(amount),
+                "fee": U128(fee),
+                "token_id": token_id,
+            }),
+        );
+    }
+
+    pub fn flash_loan_failed(
+        account_id: &AccountId,
+        receiver_id: &AccountId,
+        amount: Balance,
+        token_id: &TokenId,
+    ) {
+        log_event(
+            "flash_loan_failed",
+            json!({
+                "account_id": account_id,
+                "receiver_id": receiver_id,
+                "amount": U128(amount),
+                "token_id": token_id,
+            }),
+        );
+    }
+
+    pub fn flash_loan_repayment(receiver_id: &AccountId, amount: Balance, token_id: &TokenId) {
+        log_event(
+            "flash_loan_repayment",
+            json!({
+                "receiver_id": receiver_id,
+                "amount": U128(amount),
+                "token_id": token_id,
+            }),
+        );
+    }
+
+    pub fn flash_loan_repaid(
+        account_id: &AccountId,
+        receiver_id: &AccountId,
+        amount: Balance,
+        fee: Balance,
+        token_id: &TokenId,
+    ) {
+        log_event(
+            "flash_loan_repaid",
+            json!({
+                "account_id": account_id,
+                "receiver_id": receiver_id,
+                "amount": U128(amount),
+                "fee": U128(fee),
+                "token_id": token_id,
+            }),
+        );
+    }
+
+    pub fn flash_loan_defaulted(
+        account_id: &AccountId,
+        receiver_id: &AccountId,
+        unpaid_amount: Balance,
+        token_id: &TokenId,
+    ) {
+        log_event(
+            "flash_loan_defaulted",
+            json!({
+                "account_id": account_id,
+                "receiver_id": receiver_id,
+                "unpaid_amount": U128(unpaid_amount),
+                "token_id": token_id,
+            }),
+        );
+    }
+
+    pub fn pause(account_id: &AccountId, operation: Option<PausableOperation>) {
+        log_event(
+            "pause",
+            json!({
+                "account_id": account_id,
+                "operation": operation,
+            }),
+        );
+    }
+
+    pub fn unpause(account_id: &AccountId, operation: Option<PausableOperation>) {
+        log_event(
+            "unpause",
+            json!({
+                "account_id": account_id,
+                "operation": operation,
+            }),
+        );
+    }
 }
@@ -0,0 +1,313 @@
+use crate::*;
+use near_sdk::json_types::U128;
+use near_sdk::{is_promise_success, PromiseOrValue, StorageUsage};
+
+const GAS_FOR_AFTER_FLASH_LOAN: Gas = Gas(Gas::ONE_TERA.0 * 20);
+/// The gas required to schedule the receiver call and the final callback.
+const GAS_FOR_AFTER_FLASH_LOAN_TRANSFER: Gas = Gas(Gas::ONE_TERA.0 * 10);
+/// The storage reserved on the account for the borrowed entry of the unpaid loan.
+const FLASH_LOAN_STORAGE_RESERVE: StorageUsage = 1000;
+
+/// An active flash loan. It's keyed by the receiver, because the repayment comes from the receiver.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug))]
+#[serde(crate = "near_sdk::serde")]
+pub struct FlashLoan {
+    /// The account that took the flash loan. It's liable for the unpaid part of the loan.
+    pub account_id: AccountId,
+    pub token_id: TokenId,
+    #[serde(with = "u128_dec_format")]
+    pub amount: Balance,
+    #[serde(with = "u128_dec_format")]
+    pub fee: Balance,
+    /// The amount repaid so far.
+    #[serde(with = "u128_dec_format")]
+    pub repaid: Balance,
+}
+
+/// The arguments of the `on_flash_loan` call of the receiver.
+/// The amount and the fee are in the token decimals.
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug))]
+#[serde(crate = "near_sdk::serde")]
+pub struct OnFlashLoanArgs {
+    /// The account that took the flash loan.
+    pub sender_id: AccountId,
+    pub token_id: TokenId,
+    pub amount: U128,
+    pub fee: U128,
+    /// The message passed to `flash_loan`.
+    pub msg: String,
+}
+
+#[ext_contract(ext_flash_loan_receiver)]
+pub trait FlashLoanReceiver {
+    /// Called after the flash loan amount is transferred to the receiver. The receiver has to
+    /// transfer `amount + fee` back using `ft_transfer_call` with the `FlashLoanRepay` message and
+    /// return the promise of this transfer, so it's completed before the loan is checked.
+    fn on_flash_loan(&mut self, args: OnFlashLoanArgs) -> PromiseOrValue<U128>;
+}
+
+#[ext_contract(ext_flash_loan_self)]
+trait ExtSelf {
+    fn after_flash_loan_transfer(
+        &mut self,
+        receiver_id: AccountId,
+        msg: String,
+    ) -> PromiseOrValue<bool>;
+
+    fn after_flash_loan(&mut self, receiver_id: AccountId) -> bool;
+}
+
+trait ExtSelf {
+    fn after_flash_loan_transfer(
+        &mut self,
+        receiver_id: AccountId,
+        msg: String,
+    ) -> PromiseOrValue<bool>;
+
+    fn after_flash_loan(&mut self, receiver_id: AccountId) -> bool;
+}
+
+impl Contract {
+    /// Accepts the repayment of the flash loan of the given receiver.
+    /// Returns the amount that exceeds the rest of the loan and has to be refunded.
+    pub fn internal_repay_flash_loan(
+        &mut self,
+        receiver_id: &AccountId,
+        token_id: &TokenId,
+        amount: Balance,
+    ) -> Balance {
+        let mut flash_loan = self
+            .flash_loans
+            .get(receiver_id)
+            .expect("Flash loan not found");
+        assert_eq!(
+            &flash_loan.token_id, token_id,
+            "The flash loan is taken in another token"
+        );
+        let repaid_amount = std::cmp::min(
+            amount,
+            flash_loan.amount + flash_loan.fee - flash_loan.repaid,
+        );
+        flash_loan.repaid += repaid_amount;
+        self.flash_loans.insert(receiver_id, &flash_loan);
+        amount - repaid_amount
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Lends the given amount of the asset to the receiver and calls `on_flash_loan` on it. The
+    /// receiver has to transfer the amount and the fee back before its call completes.
+    /// The unpaid part of the loan and the fee becomes the borrowed balance of the predecessor
+    /// account, so the collateral of the account has to cover the whole loan with the fee at the
+    /// latest oracle prices.
+    /// - Panics if the asset can't be flash loaned.
+    /// - Panics if the amount exceeds the available amount of the asset.
+    /// - Panics if the account doesn't have enough collateral to cover the loan.
+    /// - Panics if the receiver already has an active flash loan.
+    /// - Panics if the storage balance of the account doesn't cover the storage reserved for the
+    ///   unpaid loan.
+    /// - Requires the predecessor to be registered.
+    /// - Requires one yoctoNEAR.
+    #[payable]
+    pub fn flash_loan(
+        &mut self,
+        token_id: TokenId,
+        amount: U128,
+        receiver_id: AccountId,
+        msg: String,
+    ) -> Promise {
+        assert_one_yocto();
+        self.assert_not_paused();
+        self.assert_operation_not_paused(PausableOperation::FlashLoan);
+        let account_id = env::predecessor_account_id();
+        let account = self.internal_unwrap_account(&account_id);
+        let mut asset = self.internal_unwrap_asset(&token_id);
+        assert!(
+            asset.config.can_flash_loan,
+            "This asset can't be flash loaned"
+        );
+
+        // The amounts are rounded to the token decimals, so they can be transferred exactly.
+        let multiplier = 10u128.pow(asset.config.extra_decimals as u32);
+        let ft_amount = amount.0 / multiplier;
+        let ft_fee = (U256::from(ft_amount) * U256::from(asset.config.flash_loan_fee)
+            + U256::from(MAX_RATIO - 1))
+            / U256::from(MAX_RATIO);
+        let amount = ft_amount * multiplier;
+        let fee = ft_fee.as_u128() * multiplier;
+        assert!(amount > 0, "Amount can't be 0");
+        let available_amount = asset.available_amount();
+        assert!(
+            amount <= available_amount,
+            "Flash loan error: Exceeded available amount {} of {}",
+            available_amount,
+            &token_id
+        );
+
+        // The account has to stay healthy, even if the receiver doesn't repay anything.
+        let mut defaulted_account = account.clone();
+        defaulted_account.increase_borrowed(
+            &token_id,
+            asset.borrowed.amount_to_shares(amount + fee, true),
+        );
+        let prices = self.internal_get_account_prices(&defaulted_account);
+        assert!(
+            self.compute_max_discount(&defaulted_account, &defaulted_account, &prices)
+                == BigDecimal::zero(),
+            "The account doesn't have enough collateral for the flash loan"
+        );
+
+        assert!(
+            self.flash_loans
+                .insert(
+                    &receiver_id,
+                    &FlashLoan {
+                        account_id: account_id.clone(),
+                        token_id: token_id.clone(),
+                        amount,
+                        fee,
+                        repaid: 0,
+                    },
+                )
+                .is_none(),
+            "The receiver already has an active flash loan"
+        );
+        asset.flash_loaned += amount;
+        self.internal_set_asset(&token_id, asset);
+
+        // The storage for the unpaid loan is reserved upfront, so it's covered before the tokens
+        // leave the contract.
+        let mut storage = self.internal_unwrap_storage(&account_id);
+        storage.storage_tracker.bytes_added += FLASH_LOAN_STORAGE_RESERVE;
+        self.internal_set_storage(&account_id, storage);
+
+        events::emit::flash_loan(&account_id, &receiver_id, amount, fee, &token_id);
+
+        let remaining_gas = env::prepaid_gas() - env::used_gas() - GAS_FOR_FT_TRANSFER;
+        self.internal_ft_transfer_without_callback(&receiver_id, &token_id, amount)
+            .then(ext_flash_loan_self::after_flash_loan_transfer(
+                receiver_id,
+                msg,
+                env::current_account_id(),
+                NO_DEPOSIT,
+                remaining_gas - GAS_FOR_AFTER_FLASH_LOAN_TRANSFER,
+            ))
+    }
+
+    /// Returns the active flash loan of the given receiver.
+    pub fn get_flash_loan(&self, receiver_id: AccountId) -> Option<FlashLoan> {
+        self.flash_loans.get(&receiver_id)
+    }
+}
+
+#[near_bindgen]
+impl ExtSelf for Contract {
+    #[private]
+    fn after_flash_loan_transfer(
+        &mut self,
+        receiver_id: AccountId,
+        msg: String,
+    ) -> PromiseOrValue<bool> {
+        let flash_loan = self.flash_loans.get(&receiver_id).unwrap();
+        if !is_promise_success() {
+            self.flash_loans.remove(&receiver_id);
+            let mut asset = self.internal_unwrap_asset(&flash_loan.token_id);
+            asset.flash_loaned -= flash_loan.amount;
+            self.internal_set_asset(&flash_loan.token_id, asset);
+            self.internal_release_flash_loan_storage(&flash_loan);
+            events::emit::flash_loan_failed(
+                &flash_loan.account_id,
+                &receiver_id,
+                flash_loan.amount,
+                &flash_loan.token_id,
+            );
+            return PromiseOrValue::Value(false);
+        }
+
+        let multiplier = 10u128.pow(
+            self.internal_unwrap_asset(&flash_loan.token_id)
+                .config
+                .extra_decimals as u32,
+        );
+        let remaining_gas = env::prepaid_gas()
+            - env::used_gas()
+            - GAS_FOR_AFTER_FLASH_LOAN
+            - GAS_FOR_AFTER_FLASH_LOAN_TRANSFER;
+        ext_flash_loan_receiver::on_flash_loan(
+            OnFlashLoanArgs {
+                sender_id: flash_loan.account_id,
+                token_id: flash_loan.token_id,
+                amount: (flash_loan.amount / multiplier).into(),
+                fee: (flash_loan.fee / multiplier).into(),
+                msg,
+            },
+            receiver_id.clone(),
+            NO_DEPOSIT,
+            remaining_gas,
+        )
+        .then(ext_flash_loan_self::after_flash_loan(
+            receiver_id,
+            env::current_account_id(),
+            NO_DEPOSIT,
+            GAS_FOR_AFTER_FLASH_LOAN,
+        ))
+        .into()
+    }
+
+    /// Checks that the flash loan is repaid. The fee is distributed to the reserve and the
+    /// suppliers. The unpaid part of the loan and the fee is borrowed by the account that took the
+    /// loan, so the unpaid part of the fee is owed to the reserve and the suppliers as well.
+    #[private]
+    fn after_flash_loan(&mut self, receiver_id: AccountId) -> bool {
+        let flash_loan = self.flash_loans.remove(&receiver_id).unwrap();
+        let mut asset = self.internal_unwrap_asset(&flash_loan.token_id);
+        asset.flash_loaned -= flash_loan.amount;
+        let reserved_fee = ratio(flash_loan.fee, asset.config.reserve_ratio);
+        asset.reserved += reserved_fee;
+        asset.supplied.balance += flash_loan.fee - reserved_fee;
+        self.internal_release_flash_loan_storage(&flash_loan);
+
+        let unpaid_amount = flash_loan.amount + flash_loan.fee - flash_loan.repaid;
+        if unpaid_amount == 0 {
+            self.internal_set_asset(&flash_loan.token_id, asset);
+            events::emit::flash_loan_repaid(
+                &flash_loan.account_id,
+                &receiver_id,
+                flash_loan.amount,
+                flash_loan.fee,
+                &flash_loan.token_id,
+            );
+            return true;
+        }
+
+        let mut account = self.internal_unwrap_account(&flash_loan.account_id);
+        let pre_account = account.clone();
+        let borrowed_shares = asset.borrowed.amount_to_shares(unpaid_amount, false);
+        asset.borrowed.deposit(borrowed_shares, unpaid_amount);
+        account.increase_borrowed(&flash_loan.token_id, borrowed_shares);
+        account.add_affected_farm(FarmId::Borrowed(flash_loan.token_id.clone()));
+        self.internal_set_asset(&flash_loan.token_id, asset);
+        self.internal_update_isolated_debt(&pre_account, &account);
+        self.internal_account_apply_affected_farms(&mut account);
+        self.internal_set_account(&flash_loan.account_id, account);
+        events::emit::flash_loan_defaulted(
+            &flash_loan.account_id,
+            &receiver_id,
+            unpaid_amount,
+            &flash_loan.token_id,
+        );
+        false
+    }
+}
+
+impl Contract {
+    /// Releases the storage reserved for the unpaid flash loan.
+    fn internal_release_flash_loan_storage(&mut self, flash_loan: &FlashLoan) {
+        let mut storage = self.internal_unwrap_storage(&flash_loan.account_id);
+        storage.storage_tracker.bytes_released += FLASH_LOAN_STORAGE_RESERVE;
+        self.internal_set_storage(&flash_loan.account_id, storage);
+    }
+}
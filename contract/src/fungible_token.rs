@@ -4,15 +4,29 @@ use near_contract_standards::fungible_token::receiver::FungibleTokenReceiver;
 use near_sdk::json_types::U128;
 use near_sdk::{is_promise_success, serde_json, PromiseOrValue};
 
-const GAS_FOR_FT_TRANSFER: Gas = Gas(Gas::ONE_TERA.0 * 10);
+pub(crate) const GAS_FOR_FT_TRANSFER: Gas = Gas(Gas::ONE_TERA.0 * 10);
 const GAS_FOR_AFTER_FT_TRANSFER: Gas = Gas(Gas::ONE_TERA.0 * 20);
 
 #[derive(Deserialize)]
 #[cfg_attr(not(target_arch = "wasm32"), derive(Debug, Serialize))]
 #[serde(crate = "near_sdk::serde")]
 pub enum TokenReceiverMsg {
-    Execute { actions: Vec<Action> },
+    Execute {
+        actions: Vec<Action>,
+    },
     DepositToReserve,
+    FlashLoanRepay,
+    /// Repays the debt of the given account, or its position if the position ID is given, with
+    /// the transferred amount. The amount exceeding the debt stays in the supplied balance of the
+    /// sender.
+    RepayFor {
+        account_id: AccountId,
+        position_id: Option<PositionId>,
+    },
+    /// Repays the variable and then the stable debt of the sender with the transferred amount
+    /// directly, without depositing it to the supplied balance first. The amount exceeding the
+    /// debt is deposited.
+    Repay,
 }
 
 #[near_bindgen]
@@ -27,6 +41,7 @@ impl FungibleTokenReceiver for Contract {
         amount: U128,
         msg: String,
     ) -> PromiseOrValue<U128> {
+        self.assert_not_paused();
         let token_id = env::predecessor_account_id();
         let mut asset = self.internal_unwrap_asset(&token_id);
         assert!(
@@ -45,20 +60,56 @@ impl FungibleTokenReceiver for Contract {
                 serde_json::from_str(&msg).expect("Can't parse TokenReceiverMsg");
             match token_receiver_msg {
                 TokenReceiverMsg::Execute { actions } => actions,
+                TokenReceiverMsg::RepayFor {
+                    account_id,
+                    position_id,
+                } => vec![Action::RepayFor {
+                    account_id,
+                    position_id,
+                    asset_amount: AssetAmount {
+                        token_id: token_id.clone(),
+                        amount: None,
+                        max_amount: Some(amount.into()),
+                    },
+                }],
                 TokenReceiverMsg::DepositToReserve => {
                     asset.reserved += amount;
                     self.internal_set_asset(&token_id, asset);
                     events::emit::deposit_to_reserve(&sender_id, amount, &token_id);
                     return PromiseOrValue::Value(U128(0));
                 }
+                TokenReceiverMsg::Repay => {
+                    self.internal_repay_transferred(&sender_id, &token_id, amount);
+                    return PromiseOrValue::Value(U128(0));
+                }
+                TokenReceiverMsg::FlashLoanRepay => {
+                    let unused_amount =
+                        self.internal_repay_flash_loan(&sender_id, &token_id, amount);
+                    events::emit::flash_loan_repayment(
+                        &sender_id,
+                        amount - unused_amount,
+                        &token_id,
+                    );
+                    return PromiseOrValue::Value(U128(
+                        unused_amount / 10u128.pow(asset.config.extra_decimals as u32),
+                    ));
+                }
             }
         };
 
+        self.assert_operation_not_paused(PausableOperation::Deposit);
+        let supplied_balance = asset.supplied.balance;
         let mut account = self.internal_unwrap_account(&sender_id);
         account.add_affected_farm(FarmId::Supplied(token_id.clone()));
         self.internal_deposit(&mut account, &token_id, amount);
         events::emit::deposit(&sender_id, amount, &token_id);
         self.internal_execute(&sender_id, &mut account, actions, Prices::new());
+        // The supply cap only limits the new supply, so repaying with the deposited tokens is
+        // allowed even if the asset is above the cap.
+        let asset = self.internal_unwrap_asset(&token_id);
+        if asset.supplied.balance > supplied_balance {
+            asset.assert_supply_cap(&token_id);
+        }
         self.internal_set_account(&sender_id, account);
 
         PromiseOrValue::Value(U128(0))
@@ -71,6 +122,82 @@ impl Contract {
         account_id: &AccountId,
         token_id: &TokenId,
         amount: Balance,
+    ) -> Promise {
+        self.internal_ft_transfer_without_callback(account_id, token_id, amount)
+            .then(ext_self::after_ft_transfer(
+                account_id.clone(),
+                token_id.clone(),
+                amount.into(),
+                env::current_account_id(),
+                NO_DEPOSIT,
+                GAS_FOR_AFTER_FT_TRANSFER,
+            ))
+    }
+
+    /// Transfers the given amount from the reserve of the asset to the given account.
+    /// The amount should already be taken from the reserve. It's returned back if the transfer fails.
+    pub fn internal_ft_transfer_reserve(
+        &mut self,
+        account_id: &AccountId,
+        token_id: &TokenId,
+        amount: Balance,
+    ) -> Promise {
+        self.internal_ft_transfer_without_callback(account_id, token_id, amount)
+            .then(ext_self::after_ft_transfer_reserve(
+                account_id.clone(),
+                token_id.clone(),
+                amount.into(),
+                env::current_account_id(),
+                NO_DEPOSIT,
+                GAS_FOR_AFTER_FT_TRANSFER,
+            ))
+    }
+
+    /// Repays the variable and then the stable debt of the account with the transferred amount.
+    /// The amount exceeding the debt is deposited to the supplied balance of the account.
+    fn internal_repay_transferred(
+        &mut self,
+        account_id: &AccountId,
+        token_id: &TokenId,
+        amount: Balance,
+    ) {
+        let mut account = self.internal_unwrap_account(account_id);
+        let pre_account = account.clone();
+        // The variable debt is repaid first, then the stable debt. Without the debt in this
+        // asset, the whole amount is deposited.
+        let mut repaid_amount = 0;
+        if account.borrowed.contains_key(token_id) {
+            account.add_affected_farm(FarmId::Borrowed(token_id.clone()));
+            repaid_amount = self.internal_repay_amount(&mut account, token_id, amount);
+            events::emit::repay(account_id, repaid_amount, token_id);
+        }
+        if repaid_amount < amount && account.stable_borrowed.contains_key(token_id) {
+            let stable_repaid_amount =
+                self.internal_repay_stable_amount(&mut account, token_id, amount - repaid_amount);
+            events::emit::repay_stable(account_id, stable_repaid_amount, token_id);
+            repaid_amount += stable_repaid_amount;
+        }
+
+        let deposit_amount = amount - repaid_amount;
+        if deposit_amount > 0 {
+            self.assert_operation_not_paused(PausableOperation::Deposit);
+            account.add_affected_farm(FarmId::Supplied(token_id.clone()));
+            self.internal_deposit(&mut account, token_id, deposit_amount);
+            events::emit::deposit(account_id, deposit_amount, token_id);
+            self.internal_unwrap_asset(token_id)
+                .assert_supply_cap(token_id);
+        }
+
+        self.internal_update_isolated_debt(&pre_account, &account);
+        self.internal_account_apply_affected_farms(&mut account);
+        self.internal_set_account(account_id, account);
+    }
+
+    pub fn internal_ft_transfer_without_callback(
+        &self,
+        account_id: &AccountId,
+        token_id: &TokenId,
+        amount: Balance,
     ) -> Promise {
         let asset = self.internal_unwrap_asset(token_id);
         let ft_amount = amount / 10u128.pow(asset.config.extra_decimals as u32);
@@ -82,14 +209,6 @@ impl Contract {
             ONE_YOCTO,
             GAS_FOR_FT_TRANSFER,
         )
-        .then(ext_self::after_ft_transfer(
-            account_id.clone(),
-            token_id.clone(),
-            amount.into(),
-            env::current_account_id(),
-            NO_DEPOSIT,
-            GAS_FOR_AFTER_FT_TRANSFER,
-        ))
     }
 }
 
@@ -97,11 +216,25 @@ impl Contract {
 trait ExtSelf {
     fn after_ft_transfer(&mut self, account_id: AccountId, token_id: TokenId, amount: U128)
         -> bool;
+
+    fn after_ft_transfer_reserve(
+        &mut self,
+        account_id: AccountId,
+        token_id: TokenId,
+        amount: U128,
+    ) -> bool;
 }
 
 trait ExtSelf {
     fn after_ft_transfer(&mut self, account_id: AccountId, token_id: TokenId, amount: U128)
         -> bool;
+
+    fn after_ft_transfer_reserve(
+        &mut self,
+        account_id: AccountId,
+        token_id: TokenId,
+        amount: U128,
+    ) -> bool;
 }
 
 #[near_bindgen]
@@ -125,4 +258,23 @@ impl ExtSelf for Contract {
         }
         promise_success
     }
+
+    #[private]
+    fn after_ft_transfer_reserve(
+        &mut self,
+        account_id: AccountId,
+        token_id: TokenId,
+        amount: U128,
+    ) -> bool {
+        let promise_success = is_promise_success();
+        if !promise_success {
+            let mut asset = self.internal_unwrap_asset(&token_id);
+            asset.reserved += amount.0;
+            self.internal_set_asset(&token_id, asset);
+            events::emit::reserve_withdraw_failed(&account_id, amount.0, &token_id);
+        } else {
+            events::emit::reserve_withdrawn(&account_id, amount.0, &token_id);
+        }
+        promise_success
+    }
 }
@@ -0,0 +1,147 @@
+use crate::*;
+
+impl Asset {
+    /// Returns the total value of the debt borrowed against this isolated collateral.
+    pub fn get_isolated_debt_value(&self, contract: &Contract, prices: &Prices) -> BigDecimal {
+        self.isolated_debt
+            .iter()
+            .fold(BigDecimal::zero(), |sum, (token_id, amount)| {
+                let asset = contract.internal_unwrap_asset(token_id);
+                sum + BigDecimal::from_balance_price(
+                    amount.0,
+                    prices.get_unwrap(token_id),
+                    asset.config.extra_decimals,
+                )
+            })
+    }
+
+    fn increase_isolated_debt(&mut self, token_id: &TokenId, amount: Balance) {
+        self.isolated_debt
+            .entry(token_id.clone())
+            .or_insert_with(|| 0.into())
+            .0 += amount;
+    }
+
+    /// The debt is tracked by the amounts at the time of the update, so the repaid amount
+    /// including the interest may exceed it.
+    fn decrease_isolated_debt(&mut self, token_id: &TokenId, amount: Balance) {
+        if let Some(debt) = self.isolated_debt.get_mut(token_id) {
+            debt.0 = debt.0.saturating_sub(amount);
+            if debt.0 == 0 {
+                self.isolated_debt.remove(token_id);
+            }
+        }
+    }
+}
+
+impl Contract {
+    /// Returns the isolated collateral asset of the account, if the account uses one.
+    pub fn internal_get_isolated_collateral(&self, account: &Account) -> Option<TokenId> {
+        account
+            .collateral
+            .keys()
+            .find(|token_id| self.internal_unwrap_asset(token_id).config.isolated)
+            .cloned()
+    }
+
+    /// The isolated collateral has to be the only collateral of the account, so the whole debt of
+    /// the account is borrowed against it.
+    pub fn assert_isolated_collateral_valid(&self, account: &Account) {
+        if self.internal_get_isolated_collateral(account).is_some() {
+            assert!(
+                account.collateral.len() == 1 && account.nft_supplied.is_empty(),
+                "The isolated collateral can't be combined with other collateral"
+            );
+        }
+    }
+
+    fn get_debt_amounts(&self, account: &Account) -> HashMap<TokenId, Balance> {
+        let mut debt_amounts: HashMap<TokenId, Balance> = account
+            .borrowed
+            .iter()
+            .map(|(token_id, shares)| {
+                let asset = self.internal_unwrap_asset(token_id);
+                (
+                    token_id.clone(),
+                    asset.borrowed.shares_to_amount(*shares, true),
+                )
+            })
+            .collect();
+        for (token_id, stable_debt) in account.stable_borrowed.iter() {
+            *debt_amounts.entry(token_id.clone()).or_insert(0) += stable_debt.get_amount();
+        }
+        debt_amounts
+    }
+
+    /// Moves the debt of the account between the isolated collateral assets based on the account
+    /// state before and after the update.
+    /// Returns the isolated collateral asset, if the debt against it has increased.
+    pub fn internal_update_isolated_debt(
+        &mut self,
+        pre_account: &Account,
+        account: &Account,
+    ) -> Option<TokenId> {
+        let pre_token_id = self.internal_get_isolated_collateral(pre_account);
+        let token_id = self.internal_get_isolated_collateral(account);
+        if pre_token_id.is_none() && token_id.is_none() {
+            return None;
+        }
+
+        let pre_debt_amounts = pre_token_id
+            .as_ref()
+            .map(|_| self.get_debt_amounts(pre_account))
+            .unwrap_or_default();
+        if let Some(pre_token_id) = &pre_token_id {
+            let mut asset = self.internal_unwrap_asset(pre_token_id);
+            for (debt_token_id, amount) in pre_debt_amounts.iter() {
+                asset.decrease_isolated_debt(debt_token_id, *amount);
+            }
+            self.internal_set_asset(pre_token_id, asset);
+        }
+
+        let token_id = token_id?;
+        let debt_amounts = self.get_debt_amounts(account);
+        let mut asset = self.internal_unwrap_asset(&token_id);
+        let mut debt_increased = false;
+        for (debt_token_id, amount) in debt_amounts {
+            asset.increase_isolated_debt(&debt_token_id, amount);
+            let pre_amount = if pre_token_id.as_ref() == Some(&token_id) {
+                pre_debt_amounts.get(&debt_token_id).cloned().unwrap_or(0)
+            } else {
+                0
+            };
+            if amount > pre_amount {
+                debt_increased = true;
+                assert!(
+                    self.internal_unwrap_asset(&debt_token_id)
+                        .config
+                        .can_borrow_in_isolation,
+                    "The asset {} can't be borrowed against the isolated collateral",
+                    debt_token_id
+                );
+            }
+        }
+        self.internal_set_asset(&token_id, asset);
+
+        if debt_increased {
+            Some(token_id)
+        } else {
+            None
+        }
+    }
+
+    /// Asserts that the debt borrowed against the isolated collateral doesn't exceed its ceiling.
+    /// Requires the prices of all assets borrowed against this collateral.
+    pub fn assert_isolated_debt_ceiling(&self, token_id: &TokenId, prices: &Prices) {
+        let asset = self.internal_unwrap_asset(token_id);
+        let debt_value = asset.get_isolated_debt_value(self, prices);
+        let debt_ceiling = BigDecimal::from(u128::from(asset.config.isolated_debt_ceiling));
+        assert!(
+            debt_value <= debt_ceiling,
+            "The debt {} against the isolated collateral {} exceeds the ceiling {}",
+            debt_value,
+            token_id,
+            debt_ceiling
+        );
+    }
+}
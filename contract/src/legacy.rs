@@ -27,13 +27,305 @@ impl From<AccountV0> for Account {
         } = a;
         Self {
             account_id,
-            supplied,
+            supplied: HashMap::new(),
+            collateral: supplied,
             borrowed,
+            stable_borrowed: HashMap::new(),
             nft_supplied,
             farms,
             affected_farms: Default::default(),
             storage_tracker: Default::default(),
             booster_staking: None,
+            e_mode_category_id: None,
+            borrow_allowances: HashMap::new(),
+            position_id: None,
+            position_ids: vec![],
+            last_position_id: 0,
+        }
+    }
+}
+
+/// V1 legacy version of Account structure, before collateral was separated from supplied assets.
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct AccountV1 {
+    /// A copy of an account ID. Saves one storage_read when iterating on accounts.
+    pub account_id: AccountId,
+    /// A list of assets that are supplied by the account used as collateral.
+    pub supplied: HashMap<TokenId, Shares>,
+    /// A list of borrowed assets.
+    pub borrowed: HashMap<TokenId, Shares>,
+    /// A list of nft supplied assets.
+    pub nft_supplied: HashMap<NFTContractTokenId, AccountNFTAsset>,
+    /// Keeping track of data required for farms for this account.
+    pub farms: HashMap<FarmId, AccountFarm>,
+    /// Staking of booster token.
+    pub booster_staking: Option<BoosterStaking>,
+}
+
+impl From<AccountV1> for Account {
+    /// Every supplied asset used to back borrows, so it's moved to the collateral to keep the
+    /// health factor of the existing accounts unchanged.
+    fn from(a: AccountV1) -> Self {
+        let AccountV1 {
+            account_id,
+            supplied,
+            borrowed,
+            nft_supplied,
+            farms,
+            booster_staking,
+        } = a;
+        Self {
+            account_id,
+            supplied: HashMap::new(),
+            collateral: supplied,
+            borrowed,
+            stable_borrowed: HashMap::new(),
+            nft_supplied,
+            farms,
+            affected_farms: Default::default(),
+            storage_tracker: Default::default(),
+            booster_staking,
+            e_mode_category_id: None,
+            borrow_allowances: HashMap::new(),
+            position_id: None,
+            position_ids: vec![],
+            last_position_id: 0,
+        }
+    }
+}
+
+/// V0 legacy version of AssetConfig structure, before the liquidation bonus, the rate breakpoints
+/// and the stable borrowing were introduced.
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct AssetConfigV0 {
+    pub reserve_ratio: u32,
+    pub target_utilization: u32,
+    pub target_utilization_rate: LowU128,
+    pub max_utilization_rate: LowU128,
+    pub volatility_ratio: u32,
+    pub extra_decimals: u8,
+    pub can_deposit: bool,
+    pub can_withdraw: bool,
+    pub can_use_as_collateral: bool,
+    pub can_borrow: bool,
+}
+
+/// The liquidation bonus of the existing assets, 5%.
+const DEFAULT_LIQUIDATION_BONUS: u32 = 500;
+
+impl From<AssetConfigV0> for AssetConfig {
+    fn from(c: AssetConfigV0) -> Self {
+        let AssetConfigV0 {
+            reserve_ratio,
+            target_utilization,
+            target_utilization_rate,
+            max_utilization_rate,
+            volatility_ratio,
+            extra_decimals,
+            can_deposit,
+            can_withdraw,
+            can_use_as_collateral,
+            can_borrow,
+        } = c;
+        Self {
+            reserve_ratio,
+            target_utilization,
+            target_utilization_rate,
+            max_utilization_rate,
+            rate_breakpoints: vec![],
+            volatility_ratio,
+            liquidation_bonus: DEFAULT_LIQUIDATION_BONUS,
+            extra_decimals,
+            can_deposit,
+            can_withdraw,
+            can_use_as_collateral,
+            can_borrow,
+            can_borrow_stable: false,
+            stable_rate_rebalance_threshold: 0,
+            can_flash_loan: false,
+            flash_loan_fee: 0,
+            isolated: false,
+            isolated_debt_ceiling: 0,
+            can_borrow_in_isolation: false,
+            supply_cap: None,
+            borrow_cap: None,
+        }
+    }
+}
+
+/// V0 legacy version of NftPool structure, before the NFTs could be kept by the positions.
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct NftPoolV0 {
+    pub owner_id: AccountId,
+    pub token_id: NFTTokenId,
+    pub deposit_timestamp: Timestamp,
+}
+
+impl From<NftPoolV0> for NftPool {
+    fn from(p: NftPoolV0) -> Self {
+        let NftPoolV0 {
+            owner_id,
+            token_id,
+            deposit_timestamp,
+        } = p;
+        Self {
+            owner_id,
+            position_id: None,
+            token_id,
+            deposit_timestamp,
+        }
+    }
+}
+
+/// V0 legacy version of Asset structure, before the liquidation bonus, the stable borrowing, the
+/// flash loans and the isolation mode were introduced and the NFT pools were moved out of the asset.
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct AssetV0 {
+    pub supplied: Pool,
+    pub borrowed: Pool,
+    pub nft_supplied: Vec<NftPoolV0>,
+    pub reserved: Balance,
+    pub last_update_timestamp: Timestamp,
+    pub config: AssetConfigV0,
+}
+
+impl From<AssetV0> for Asset {
+    /// The NFT pools have to be moved by `migrate_nft_pools` beforehand.
+    fn from(a: AssetV0) -> Self {
+        let AssetV0 {
+            supplied,
+            borrowed,
+            nft_supplied,
+            reserved,
+            last_update_timestamp,
+            config,
+        } = a;
+        assert!(
+            nft_supplied.is_empty(),
+            "The NFT pools of the asset are not migrated"
+        );
+        Self {
+            supplied,
+            borrowed,
+            stable_borrowed: 0,
+            average_stable_rate: BigDecimal::one().into(),
+            flash_loaned: 0,
+            isolated_debt: HashMap::new(),
+            reserved,
+            socialized_bad_debt: 0,
+            last_update_timestamp,
+            config: config.into(),
+        }
+    }
+}
+
+/// V0 legacy version of Config structure, before the close factor, the guardian, the NFT auctions,
+/// the multiple oracles and the price history were introduced.
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct ConfigV0 {
+    pub oracle_account_id: AccountId,
+    pub owner_id: AccountId,
+    pub booster_token_id: TokenId,
+    pub booster_decimals: u8,
+    pub max_num_assets: u32,
+    pub maximum_recency_duration_sec: DurationSec,
+    pub maximum_staleness_duration_sec: DurationSec,
+    pub minimum_staking_duration_sec: DurationSec,
+    pub maximum_staking_duration_sec: DurationSec,
+    pub x_booster_multiplier_at_maximum_staking_duration: u32,
+    pub force_closing_enabled: bool,
+}
+
+/// The max price deviation of the existing deployments, 2%. It's unused with the single oracle.
+const DEFAULT_MAX_PRICE_DEVIATION: u32 = 200;
+
+/// The close factor of the existing deployments, 50%.
+const DEFAULT_CLOSE_FACTOR: u32 = 5000;
+
+/// The NFT auction price ratios of the existing deployments, from 120% down to 50%.
+/// The auctions are disabled by the zero duration.
+const DEFAULT_NFT_AUCTION_START_PRICE_RATIO: u32 = 12000;
+const DEFAULT_NFT_AUCTION_END_PRICE_RATIO: u32 = 5000;
+
+impl From<ConfigV0> for Config {
+    fn from(c: ConfigV0) -> Self {
+        let ConfigV0 {
+            oracle_account_id,
+            owner_id,
+            booster_token_id,
+            booster_decimals,
+            max_num_assets,
+            maximum_recency_duration_sec,
+            maximum_staleness_duration_sec,
+            minimum_staking_duration_sec,
+            maximum_staking_duration_sec,
+            x_booster_multiplier_at_maximum_staking_duration,
+            force_closing_enabled,
+        } = c;
+        Self {
+            oracle_account_id,
+            extra_oracle_account_ids: vec![],
+            max_price_deviation: DEFAULT_MAX_PRICE_DEVIATION,
+            price_history_size: 0,
+            twap_duration_sec: 0,
+            owner_id,
+            guardian_id: None,
+            booster_token_id,
+            booster_decimals,
+            max_num_assets,
+            maximum_recency_duration_sec,
+            maximum_staleness_duration_sec,
+            minimum_staking_duration_sec,
+            maximum_staking_duration_sec,
+            x_booster_multiplier_at_maximum_staking_duration,
+            force_closing_enabled,
+            close_factor: DEFAULT_CLOSE_FACTOR,
+            nft_auction_duration_sec: 0,
+            nft_auction_start_price_ratio: DEFAULT_NFT_AUCTION_START_PRICE_RATIO,
+            nft_auction_end_price_ratio: DEFAULT_NFT_AUCTION_END_PRICE_RATIO,
+        }
+    }
+}
+
+/// V0 legacy version of Contract structure, before the pause status, the NFT valuations, the NFT
+/// pools and the NFT auctions were introduced.
+#[derive(BorshDeserialize)]
+pub struct ContractV0 {
+    pub accounts: UnorderedMap<AccountId, VAccount>,
+    pub storage: LookupMap<AccountId, VStorage>,
+    pub assets: LookupMap<TokenId, VAsset>,
+    pub asset_farms: LookupMap<FarmId, VAssetFarm>,
+    pub asset_ids: UnorderedSet<TokenId>,
+    pub config: LazyOption<Config>,
+}
+
+impl From<ContractV0> for Contract {
+    fn from(c: ContractV0) -> Self {
+        let ContractV0 {
+            accounts,
+            storage,
+            assets,
+            asset_farms,
+            asset_ids,
+            config,
+        } = c;
+        Self {
+            accounts,
+            storage,
+            assets,
+            asset_farms,
+            asset_ids,
+            config,
+            pause_status: PauseStatus::default(),
+            nft_valuations: LookupMap::new(StorageKey::NftValuations),
+            nft_pools: LookupMap::new(StorageKey::NftPools),
+            nft_auctions: UnorderedMap::new(StorageKey::NftAuctions),
+            flash_loans: LookupMap::new(StorageKey::FlashLoans),
+            e_mode_categories: UnorderedMap::new(StorageKey::EModeCategories),
+            oracle_prices: LookupMap::new(StorageKey::OraclePrices),
+            price_history: LookupMap::new(StorageKey::PriceHistory),
+            borrowers: UnorderedSet::new(StorageKey::Borrowers),
+            positions: LookupMap::new(StorageKey::Positions),
         }
     }
 }
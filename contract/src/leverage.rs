@@ -0,0 +1,98 @@
+use crate::*;
+
+impl Contract {
+    /// Returns the collateral amount and the variable debt amount of the asset, and the amount of
+    /// the collateral that keeps the equity at the target leverage.
+    fn internal_get_leverage_position(
+        &self,
+        account: &Account,
+        token_id: &TokenId,
+        target_leverage: u32,
+    ) -> (Balance, Balance, Balance) {
+        assert!(
+            target_leverage >= MAX_RATIO,
+            "The target leverage should be at least 100%"
+        );
+        let asset = self.internal_unwrap_asset(token_id);
+        let collateral_amount = asset
+            .supplied
+            .shares_to_amount(account.get_collateral_shares(token_id), false);
+        let borrowed_amount = asset
+            .borrowed
+            .shares_to_amount(account.get_borrowed_shares(token_id), true);
+        assert!(
+            collateral_amount > borrowed_amount,
+            "The collateral of {} should exceed the debt",
+            token_id
+        );
+        let target_collateral_amount = (U256::from(collateral_amount - borrowed_amount)
+            * U256::from(target_leverage)
+            / U256::from(MAX_RATIO))
+        .as_u128();
+        (collateral_amount, borrowed_amount, target_collateral_amount)
+    }
+
+    /// Borrows the asset and moves the borrowed amount to the collateral, until the collateral
+    /// reaches the target leverage of the equity in this asset. The equity is the collateral
+    /// amount minus the variable debt amount.
+    /// Returns the borrowed amount.
+    pub fn internal_leverage(
+        &mut self,
+        account: &mut Account,
+        token_id: &TokenId,
+        target_leverage: u32,
+    ) -> Balance {
+        let (collateral_amount, _, target_collateral_amount) =
+            self.internal_get_leverage_position(account, token_id, target_leverage);
+        assert!(
+            target_collateral_amount > collateral_amount,
+            "The leverage is already at or above the target"
+        );
+        let amount = self.internal_borrow(
+            account,
+            &AssetAmount {
+                token_id: token_id.clone(),
+                amount: Some((target_collateral_amount - collateral_amount).into()),
+                max_amount: None,
+            },
+        );
+        self.internal_increase_collateral(
+            account,
+            &AssetAmount {
+                token_id: token_id.clone(),
+                amount: None,
+                max_amount: Some(amount.into()),
+            },
+        );
+        amount
+    }
+
+    /// Moves the collateral to the supplied balance and repays the variable debt of the asset
+    /// with it, until the collateral reaches the target leverage of the equity in this asset.
+    /// The leverage of 100% repays the whole debt.
+    /// Returns the repaid amount.
+    pub fn internal_deleverage(
+        &mut self,
+        account: &mut Account,
+        token_id: &TokenId,
+        target_leverage: u32,
+    ) -> Balance {
+        let (collateral_amount, borrowed_amount, target_collateral_amount) =
+            self.internal_get_leverage_position(account, token_id, target_leverage);
+        let amount = std::cmp::min(
+            collateral_amount.saturating_sub(target_collateral_amount),
+            borrowed_amount,
+        );
+        assert!(amount > 0, "The leverage is already at or below the target");
+        let asset_amount = AssetAmount {
+            token_id: token_id.clone(),
+            amount: None,
+            max_amount: Some(amount.into()),
+        };
+        let mut account_asset = account.internal_get_asset_or_default(token_id);
+        self.internal_decrease_collateral(&mut account_asset, account, &asset_amount);
+        let amount = self.internal_repay(&mut account_asset, account, &asset_amount);
+        account.internal_set_asset(token_id, account_asset);
+        amount
+    }
+}
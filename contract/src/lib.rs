@@ -1,6 +1,7 @@
 mod account;
 mod account_asset;
 mod account_farm;
+mod account_health;
 mod account_nft_asset;
 mod account_view;
 mod actions;
@@ -12,14 +13,27 @@ mod asset_view;
 mod big_decimal;
 mod booster_staking;
 mod config;
+mod credit_delegation;
+mod e_mode;
 mod events;
+mod flash_loan;
 mod fungible_token;
+mod isolation;
 mod legacy;
+mod leverage;
+mod liquidation_candidates;
+mod nft_auction;
 mod nft_pool;
+mod nft_valuation;
 mod non_fungible_token;
+mod oracle_prices;
+mod pause;
 mod pool;
+mod positions;
+mod price_history;
 mod price_receiver;
 mod prices;
+mod stable_borrow;
 mod storage;
 mod storage_tracker;
 mod upgrade;
@@ -28,6 +42,7 @@ mod utils;
 pub use crate::account::*;
 pub use crate::account_asset::*;
 pub use crate::account_farm::*;
+pub use crate::account_health::*;
 pub use crate::account_nft_asset::*;
 pub use crate::account_view::*;
 pub use crate::actions::*;
@@ -39,13 +54,24 @@ pub use crate::asset_view::*;
 pub use crate::big_decimal::*;
 pub use crate::booster_staking::*;
 pub use crate::config::*;
+pub use crate::credit_delegation::*;
+pub use crate::e_mode::*;
+pub use crate::flash_loan::*;
 pub use crate::fungible_token::*;
 pub use crate::legacy::*;
+pub use crate::liquidation_candidates::*;
+pub use crate::nft_auction::*;
 pub use crate::nft_pool::*;
+pub use crate::nft_valuation::*;
 pub use crate::non_fungible_token::*;
+pub use crate::oracle_prices::*;
+pub use crate::pause::*;
 pub use crate::pool::*;
+pub use crate::positions::*;
+pub use crate::price_history::*;
 pub use crate::price_receiver::*;
 pub use crate::prices::*;
+pub use crate::stable_borrow::*;
 pub use crate::storage::*;
 use crate::storage_tracker::*;
 use crate::utils::*;
@@ -76,6 +102,16 @@ enum StorageKey {
     AssetIds,
     Config,
     // AccountNftAssets { account_id: AccountId },
+    NftValuations,
+    NftPools,
+    NftPool { nft_contract_id: NFTContractId },
+    NftAuctions,
+    FlashLoans,
+    EModeCategories,
+    OraclePrices,
+    PriceHistory,
+    Borrowers,
+    Positions,
 }
 
 #[near_bindgen]
@@ -87,6 +123,24 @@ pub struct Contract {
     pub asset_farms: LookupMap<FarmId, VAssetFarm>,
     pub asset_ids: UnorderedSet<TokenId>,
     pub config: LazyOption<Config>,
+    pub pause_status: PauseStatus,
+    pub nft_valuations: LookupMap<NFTContractId, NftValuation>,
+    /// The NFTs deposited to each NFT collection, keyed by the token id.
+    pub nft_pools: LookupMap<NFTContractId, UnorderedMap<NFTTokenId, NftPool>>,
+    /// The active auctions of the NFT collateral, keyed by the NFT contract token id.
+    pub nft_auctions: UnorderedMap<NFTContractTokenId, NftAuction>,
+    /// The active flash loans, keyed by the receiver.
+    pub flash_loans: LookupMap<AccountId, FlashLoan>,
+    /// The owner-defined e-mode categories of correlated assets.
+    pub e_mode_categories: UnorderedMap<EModeCategoryId, EModeCategory>,
+    /// The latest prices submitted by each oracle, keyed by the asset.
+    pub oracle_prices: LookupMap<TokenId, HashMap<AccountId, OraclePrice>>,
+    /// The latest aggregated prices of each asset used to compute the TWAP.
+    pub price_history: LookupMap<TokenId, Vec<PricePoint>>,
+    /// The accounts and the positions that have any debt, used to find the liquidation candidates.
+    pub borrowers: UnorderedSet<(AccountId, Option<PositionId>)>,
+    /// The positions of the accounts, keyed by the account ID and the position ID.
+    pub positions: LookupMap<(AccountId, PositionId), VAccount>,
 }
 
 #[near_bindgen]
@@ -102,6 +156,16 @@ impl Contract {
             asset_farms: LookupMap::new(StorageKey::AssetFarms),
             asset_ids: UnorderedSet::new(StorageKey::AssetIds),
             config: LazyOption::new(StorageKey::Config, Some(&config)),
+            pause_status: PauseStatus::default(),
+            nft_valuations: LookupMap::new(StorageKey::NftValuations),
+            nft_pools: LookupMap::new(StorageKey::NftPools),
+            nft_auctions: UnorderedMap::new(StorageKey::NftAuctions),
+            flash_loans: LookupMap::new(StorageKey::FlashLoans),
+            e_mode_categories: UnorderedMap::new(StorageKey::EModeCategories),
+            oracle_prices: LookupMap::new(StorageKey::OraclePrices),
+            price_history: LookupMap::new(StorageKey::PriceHistory),
+            borrowers: UnorderedSet::new(StorageKey::Borrowers),
+            positions: LookupMap::new(StorageKey::Positions),
         }
     }
 }
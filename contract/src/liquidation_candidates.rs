@@ -0,0 +1,208 @@
+use crate::*;
+
+/// The liquidation of the given account suggested by the contract. The liquidator repays the
+/// `repay_amount` of the largest debt and takes the `seize_amount` of the largest collateral.
+#[derive(Serialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug, Deserialize))]
+#[serde(crate = "near_sdk::serde")]
+pub struct LiquidationSuggestion {
+    pub repay_token_id: TokenId,
+    #[serde(with = "u128_dec_format")]
+    pub repay_amount: Balance,
+    pub seize_token_id: TokenId,
+    #[serde(with = "u128_dec_format")]
+    pub seize_amount: Balance,
+}
+
+#[derive(Serialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug, Deserialize))]
+#[serde(crate = "near_sdk::serde")]
+pub struct LiquidationCandidateView {
+    pub account_id: AccountId,
+    /// The position of the account, if the position is the candidate.
+    pub position_id: Option<PositionId>,
+    pub collateral_sum: BigDecimal,
+    pub borrowed_sum: BigDecimal,
+    pub health_factor: Option<BigDecimal>,
+    /// The health deficit of the account, same as in `AccountHealthView`. It's greater than 0 for
+    /// every candidate.
+    pub health_deficit: BigDecimal,
+    /// It's `None`, if the account only has the NFT collateral, that is liquidated separately.
+    pub suggestion: Option<LiquidationSuggestion>,
+}
+
+impl Contract {
+    /// Adds the account or its position to the borrowers, if it has any debt, or removes it
+    /// otherwise.
+    pub fn internal_update_borrowers(&mut self, account_id: &AccountId, account: &Account) {
+        let key = (account_id.clone(), account.position_id);
+        if account.borrowed.is_empty() && account.stable_borrowed.is_empty() {
+            self.borrowers.remove(&key);
+        } else {
+            self.borrowers.insert(&key);
+        }
+    }
+
+    /// Suggests the liquidation that repays the largest debt of the account and takes its largest
+    /// collateral. The repaid value is limited by the close factor, the debt and the collateral
+    /// value reduced by the liquidation bonus.
+    fn internal_suggest_liquidation(
+        &self,
+        account: &Account,
+        account_health: &AccountHealthView,
+        prices: &Prices,
+    ) -> Option<LiquidationSuggestion> {
+        let repay_token_id = account_health
+            .assets
+            .iter()
+            .filter(|view| view.borrowed_value > BigDecimal::zero())
+            .max_by(|a, b| a.borrowed_value.partial_cmp(&b.borrowed_value).unwrap())?
+            .token_id
+            .clone();
+        let seize_token_id = account
+            .collateral
+            .keys()
+            .filter(|token_id| {
+                self.internal_unwrap_asset(token_id)
+                    .config
+                    .can_use_as_collateral
+            })
+            .filter_map(|token_id| {
+                account_health
+                    .assets
+                    .iter()
+                    .find(|view| &view.token_id == token_id)
+            })
+            .max_by(|a, b| a.collateral_value.partial_cmp(&b.collateral_value).unwrap())?
+            .token_id
+            .clone();
+
+        let repay_asset = self.internal_unwrap_asset(&repay_token_id);
+        let repay_price = prices.get_unwrap(&repay_token_id);
+        // The variable debt is repaid first.
+        let debt_amount = match account.borrowed.get(&repay_token_id) {
+            Some(shares) => repay_asset.borrowed.shares_to_amount(*shares, true),
+            None => account.stable_borrowed[&repay_token_id].get_amount(),
+        };
+        let seize_asset = self.internal_unwrap_asset(&seize_token_id);
+        let seize_price = prices.get_unwrap(&seize_token_id);
+        let discount_ratio = MAX_RATIO - seize_asset.config.liquidation_bonus;
+        let collateral_amount = seize_asset
+            .supplied
+            .shares_to_amount(account.collateral[&seize_token_id], false);
+
+        let max_repaid_value = account_health
+            .borrowed_sum
+            .mul_ratio(self.internal_config().close_factor);
+        let debt_value = BigDecimal::from_balance_price(
+            debt_amount,
+            repay_price,
+            repay_asset.config.extra_decimals,
+        );
+        let discounted_collateral_value = BigDecimal::from_balance_price(
+            collateral_amount,
+            seize_price,
+            seize_asset.config.extra_decimals,
+        )
+        .mul_ratio(discount_ratio);
+        let repay_value = [debt_value, discounted_collateral_value].iter().fold(
+            max_repaid_value,
+            |min, value| {
+                if *value < min {
+                    *value
+                } else {
+                    min
+                }
+            },
+        );
+        let repay_amount =
+            repay_value.to_balance_price(repay_price, repay_asset.config.extra_decimals);
+        let repaid_value = BigDecimal::from_balance_price(
+            repay_amount,
+            repay_price,
+            repay_asset.config.extra_decimals,
+        );
+        let seize_amount = std::cmp::min(
+            repaid_value
+                .div_ratio(discount_ratio)
+                .to_balance_price(seize_price, seize_asset.config.extra_decimals),
+            collateral_amount,
+        );
+        if repay_amount == 0 || seize_amount == 0 {
+            return None;
+        }
+        Some(LiquidationSuggestion {
+            repay_token_id,
+            repay_amount,
+            seize_token_id,
+            seize_amount,
+        })
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Returns the number of accounts and positions that have any debt.
+    pub fn get_num_borrowers(&self) -> u64 {
+        self.borrowers.len()
+    }
+
+    /// Returns the accounts and positions that can be liquidated with the prices from the given
+    /// price data, among the accounts and positions with debt from a given index up to a given
+    /// limit. The candidates are sorted by the health deficit in the descending order,
+    /// together with the suggested liquidation.
+    /// The price data is not validated, and the suggested amounts may need to be adjusted, if the
+    /// prices change before the liquidation.
+    /// - Panics if the price of an asset one of the accounts has a position in is missing.
+    pub fn get_liquidation_candidates(
+        &self,
+        price_data: PriceData,
+        from_index: Option<u64>,
+        limit: Option<u64>,
+    ) -> Vec<LiquidationCandidateView> {
+        let prices: Prices = price_data.into();
+        let keys = self.borrowers.as_vector();
+        let from_index = from_index.unwrap_or(0);
+        let limit = limit.unwrap_or(keys.len());
+        let mut candidates: Vec<LiquidationCandidateView> = (from_index
+            ..std::cmp::min(keys.len(), from_index + limit))
+            .filter_map(|index| {
+                let (account_id, position_id) = keys.get(index).unwrap();
+                let account = self.internal_unwrap_account_or_position(&account_id, position_id);
+                let account_health =
+                    self.compute_account_health(account_id.clone(), &account, &prices);
+                if account_health.health_deficit == BigDecimal::zero() {
+                    return None;
+                }
+                let suggestion =
+                    self.internal_suggest_liquidation(&account, &account_health, &prices);
+                Some(LiquidationCandidateView {
+                    account_id,
+                    position_id,
+                    collateral_sum: account_health.collateral_sum,
+                    borrowed_sum: account_health.borrowed_sum,
+                    health_factor: account_health.health_factor,
+                    health_deficit: account_health.health_deficit,
+                    suggestion,
+                })
+            })
+            .collect();
+        candidates.sort_by(|a, b| b.health_deficit.partial_cmp(&a.health_deficit).unwrap());
+        candidates
+    }
+
+    /// Adds the existing accounts with debt from a given index up to a given limit to the
+    /// borrowers. Only needed once for the accounts that haven't changed since the upgrade.
+    /// - Requires one yoctoNEAR.
+    /// - Requires to be called by the contract owner.
+    #[payable]
+    pub fn index_borrowers(&mut self, from_index: Option<u64>, limit: Option<u64>) {
+        assert_one_yocto();
+        self.assert_owner();
+        for (account_id, account) in
+            unordered_map_pagination::<_, _, Account>(&self.accounts, from_index, limit)
+        {
+            self.internal_update_borrowers(&account_id, &account);
+        }
+    }
+}
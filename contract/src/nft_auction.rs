@@ -0,0 +1,297 @@
+use crate::*;
+
+/// A descending-price auction of the NFT collateral of an account at risk.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug))]
+#[serde(crate = "near_sdk::serde")]
+pub struct NftAuction {
+    /// The account that owns the auctioned NFT.
+    pub owner_id: AccountId,
+    /// The position of the owner that has the NFT, or `None` for the main account.
+    pub position_id: Option<PositionId>,
+    pub nft_contract_id: NFTContractId,
+    pub token_id: NFTTokenId,
+    /// When the auction was started.
+    #[serde(with = "u64_dec_format")]
+    pub start_timestamp: Timestamp,
+}
+
+#[derive(Serialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug, Deserialize))]
+#[serde(crate = "near_sdk::serde")]
+pub struct NftAuctionView {
+    #[serde(flatten)]
+    pub auction: NftAuction,
+    /// The current price of the auction relative to the NFT value (multiplied by 10000).
+    pub price_ratio: u32,
+}
+
+impl NftAuction {
+    /// Returns the current price of the auction relative to the NFT value.
+    pub fn get_price_ratio(&self, config: &Config) -> u32 {
+        let elapsed = env::block_timestamp().saturating_sub(self.start_timestamp);
+        let duration = sec_to_nano(config.nft_auction_duration_sec);
+        if elapsed >= duration {
+            config.nft_auction_end_price_ratio
+        } else {
+            let price_decline = (config.nft_auction_start_price_ratio
+                - config.nft_auction_end_price_ratio) as u128
+                * elapsed as u128
+                / duration as u128;
+            config.nft_auction_start_price_ratio - price_decline as u32
+        }
+    }
+}
+
+impl Contract {
+    pub fn internal_start_nft_auction(
+        &mut self,
+        account_id: &AccountId,
+        prices: &Prices,
+        liquidation_account_id: &AccountId,
+        position_id: Option<PositionId>,
+        nft_asset: &NFTAsset,
+    ) {
+        assert!(
+            self.internal_config().nft_auction_duration_sec > 0,
+            "The NFT auctions are not enabled"
+        );
+        let asset = self.internal_unwrap_asset(&nft_asset.nft_contract_id);
+        assert!(
+            asset.config.can_use_as_collateral,
+            "This NFT can't be auctioned, because it's not used as a collateral"
+        );
+        assert_eq!(
+            self.internal_get_nft_owner(&nft_asset.nft_contract_id, &nft_asset.token_id),
+            Some((liquidation_account_id.clone(), position_id)),
+            "NFT not found in the liquidation account"
+        );
+
+        let liquidation_account =
+            self.internal_unwrap_account_or_position(liquidation_account_id, position_id);
+        assert!(
+            self.compute_max_discount(&liquidation_account, &liquidation_account, prices)
+                > BigDecimal::zero(),
+            "The liquidation account is not at risk"
+        );
+
+        let contract_nft_token_id: NFTContractTokenId = format!(
+            "{}{}{}",
+            nft_asset.nft_contract_id, NFT_DELIMETER, nft_asset.token_id
+        );
+        assert!(
+            self.nft_auctions
+                .insert(
+                    &contract_nft_token_id,
+                    &NftAuction {
+                        owner_id: liquidation_account_id.clone(),
+                        position_id,
+                        nft_contract_id: nft_asset.nft_contract_id.clone(),
+                        token_id: nft_asset.token_id.clone(),
+                        start_timestamp: env::block_timestamp(),
+                    },
+                )
+                .is_none(),
+            "The NFT is already auctioned"
+        );
+
+        events::emit::nft_auction_started(
+            account_id,
+            liquidation_account_id,
+            &nft_asset.nft_contract_id,
+            &nft_asset.token_id,
+        );
+    }
+
+    /// Buys the auctioned NFT at the current auction price with the given asset. The price repays
+    /// the debt of the NFT owner in this asset and the surplus is credited to the supplied balance
+    /// of the NFT owner.
+    /// Returns the amount paid.
+    pub fn internal_buy_nft_auction(
+        &mut self,
+        account_id: &AccountId,
+        account: &mut Account,
+        prices: &Prices,
+        nft_asset: &NFTAsset,
+        token_id: &TokenId,
+        max_amount: Option<U128>,
+    ) -> Balance {
+        let contract_nft_token_id: NFTContractTokenId = format!(
+            "{}{}{}",
+            nft_asset.nft_contract_id, NFT_DELIMETER, nft_asset.token_id
+        );
+        let auction = self
+            .nft_auctions
+            .remove(&contract_nft_token_id)
+            .expect("NFT auction not found");
+        assert_ne!(account_id, &auction.owner_id, "Can't buy your own NFT");
+
+        let mut owner_account =
+            self.internal_unwrap_account_or_position(&auction.owner_id, auction.position_id);
+        assert!(
+            self.compute_max_discount(&owner_account, &owner_account, prices) > BigDecimal::zero(),
+            "The NFT owner is not at risk"
+        );
+
+        let nft_asset_config = self
+            .internal_unwrap_asset(&nft_asset.nft_contract_id)
+            .config;
+        let price_value = self
+            .internal_get_nft_valuation(&nft_asset.nft_contract_id)
+            .get_value(
+                &nft_asset.token_id,
+                prices.get_unwrap(&nft_asset.nft_contract_id),
+                nft_asset_config.extra_decimals,
+            )
+            .mul_ratio(auction.get_price_ratio(&self.internal_config()));
+
+        let asset = self.internal_unwrap_asset(token_id);
+        let amount = price_value
+            .to_balance_price_round_up(prices.get_unwrap(token_id), asset.config.extra_decimals);
+        assert!(amount > 0, "The auction price can't be 0");
+        if let Some(max_amount) = max_amount {
+            assert!(
+                amount <= max_amount.0,
+                "The auction price {} is greater than the max amount {}",
+                amount,
+                max_amount.0
+            );
+        }
+        assert!(
+            owner_account.borrowed.contains_key(token_id)
+                || owner_account.stable_borrowed.contains_key(token_id),
+            "The NFT owner doesn't have debt in {}",
+            token_id
+        );
+        let pre_owner_account = owner_account.clone();
+
+        // Take the payment from the supplied balance of the buyer. It repays the variable or the
+        // stable debt of the NFT owner, and the surplus is credited to the NFT owner.
+        let mut account_asset = account.internal_unwrap_asset(token_id);
+        let (repaid_amount, surplus_amount) = self.internal_liquidation_repay_and_credit(
+            &mut account_asset,
+            &mut owner_account,
+            &AssetAmount {
+                token_id: token_id.clone(),
+                amount: Some(amount.into()),
+                max_amount: None,
+            },
+        );
+        account.internal_set_asset(token_id, account_asset);
+        self.internal_update_isolated_debt(&pre_owner_account, &owner_account);
+
+        // Move the NFT to the buyer
+        let account_nft_asset = owner_account
+            .nft_supplied
+            .remove(&contract_nft_token_id)
+            .expect("NFT asset not found");
+        account.internal_set_nft_asset(&contract_nft_token_id, account_nft_asset);
+        self.internal_set_nft_asset(
+            &nft_asset.nft_contract_id,
+            account_id.clone(),
+            account.position_id,
+            nft_asset.token_id.clone(),
+        );
+
+        account.add_affected_farm(FarmId::Supplied(token_id.clone()));
+        account.add_affected_farm(FarmId::SuppliedNFT(nft_asset.nft_contract_id.clone()));
+        owner_account.add_affected_farm(FarmId::Supplied(token_id.clone()));
+        owner_account.add_affected_farm(FarmId::Borrowed(token_id.clone()));
+        owner_account.add_affected_farm(FarmId::SuppliedNFT(nft_asset.nft_contract_id.clone()));
+        self.internal_account_apply_affected_farms(&mut owner_account);
+        self.internal_set_account_or_position(
+            &auction.owner_id,
+            auction.position_id,
+            owner_account,
+        );
+
+        events::emit::nft_auction_bought(
+            account_id,
+            &auction.owner_id,
+            &nft_asset.nft_contract_id,
+            &nft_asset.token_id,
+            amount,
+            repaid_amount,
+            surplus_amount,
+            token_id,
+        );
+
+        amount
+    }
+
+    /// Cancels the auction of the NFT if its owner is no longer at risk.
+    pub fn internal_cancel_nft_auction(
+        &mut self,
+        account_id: &AccountId,
+        prices: &Prices,
+        nft_asset: &NFTAsset,
+    ) {
+        let contract_nft_token_id: NFTContractTokenId = format!(
+            "{}{}{}",
+            nft_asset.nft_contract_id, NFT_DELIMETER, nft_asset.token_id
+        );
+        let auction = self
+            .nft_auctions
+            .remove(&contract_nft_token_id)
+            .expect("NFT auction not found");
+        let owner_account =
+            self.internal_unwrap_account_or_position(&auction.owner_id, auction.position_id);
+        assert!(
+            self.compute_max_discount(&owner_account, &owner_account, prices) == BigDecimal::zero(),
+            "The NFT owner is still at risk"
+        );
+
+        events::emit::nft_auction_canceled(
+            account_id,
+            &auction.owner_id,
+            &nft_asset.nft_contract_id,
+            &nft_asset.token_id,
+        );
+    }
+
+    pub fn internal_is_nft_auctioned(
+        &self,
+        nft_contract_id: &NFTContractId,
+        token_id: &NFTTokenId,
+    ) -> bool {
+        self.nft_auctions
+            .get(&format!("{}{}{}", nft_contract_id, NFT_DELIMETER, token_id))
+            .is_some()
+    }
+
+    fn auction_into_view(&self, auction: NftAuction) -> NftAuctionView {
+        let price_ratio = auction.get_price_ratio(&self.internal_config());
+        NftAuctionView {
+            auction,
+            price_ratio,
+        }
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Returns the auction of the given NFT if it's auctioned.
+    pub fn get_nft_auction(
+        &self,
+        nft_contract_id: NFTContractId,
+        token_id: NFTTokenId,
+    ) -> Option<NftAuctionView> {
+        self.nft_auctions
+            .get(&format!("{}{}{}", nft_contract_id, NFT_DELIMETER, token_id))
+            .map(|auction| self.auction_into_view(auction))
+    }
+
+    /// Returns the active NFT auctions from a given index up to a given limit.
+    pub fn get_nft_auctions_paged(
+        &self,
+        from_index: Option<u64>,
+        limit: Option<u64>,
+    ) -> Vec<NftAuctionView> {
+        let values = self.nft_auctions.values_as_vector();
+        let from_index = from_index.unwrap_or(0);
+        let limit = limit.unwrap_or(values.len());
+        (from_index..std::cmp::min(values.len(), from_index + limit))
+            .map(|index| self.auction_into_view(values.get(index).unwrap()))
+            .collect()
+    }
+}
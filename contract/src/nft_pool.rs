@@ -5,6 +5,8 @@ use crate::*;
 #[serde(crate = "near_sdk::serde")]
 pub struct NftPool {
     pub owner_id: AccountId,
+    /// The position of the owner that has the NFT, or `None` for the main account.
+    pub position_id: Option<PositionId>,
     pub token_id: NFTTokenId,
     #[serde(with = "u64_dec_format")]
     pub deposit_timestamp: Timestamp,
@@ -20,8 +22,94 @@ impl NftPool {
     pub fn new() -> Self {
         Self {
             owner_id: env::current_account_id(),
+            position_id: None,
             token_id: String::from('0'),
             deposit_timestamp: env::block_timestamp(),
         }
     }
 }
+
+impl Contract {
+    pub fn internal_get_nft_pools(
+        &self,
+        nft_contract_id: &NFTContractId,
+    ) -> UnorderedMap<NFTTokenId, NftPool> {
+        self.nft_pools.get(nft_contract_id).unwrap_or_else(|| {
+            UnorderedMap::new(StorageKey::NftPool {
+                nft_contract_id: nft_contract_id.clone(),
+            })
+        })
+    }
+
+    /// Returns the owner of the NFT and the position of the owner that has the NFT from the NFT
+    /// pool of the collection.
+    pub fn internal_get_nft_owner(
+        &self,
+        nft_contract_id: &NFTContractId,
+        token_id: &NFTTokenId,
+    ) -> Option<(AccountId, Option<PositionId>)> {
+        self.internal_get_nft_pools(nft_contract_id)
+            .get(token_id)
+            .map(|nft_pool| (nft_pool.owner_id, nft_pool.position_id))
+    }
+
+    /// Sets the owner of the NFT and the position of the owner that has the NFT in the NFT pool of
+    /// the collection. The deposit timestamp is kept if the NFT is already in the pool.
+    pub fn internal_set_nft_asset(
+        &mut self,
+        nft_contract_id: &NFTContractId,
+        owner_id: AccountId,
+        position_id: Option<PositionId>,
+        token_id: NFTTokenId,
+    ) {
+        let mut nft_pools = self.internal_get_nft_pools(nft_contract_id);
+        let deposit_timestamp = nft_pools
+            .get(&token_id)
+            .map(|nft_pool| nft_pool.deposit_timestamp)
+            .unwrap_or_else(env::block_timestamp);
+        nft_pools.insert(
+            &token_id,
+            &NftPool {
+                owner_id,
+                position_id,
+                token_id: token_id.clone(),
+                deposit_timestamp,
+            },
+        );
+        self.nft_pools.insert(nft_contract_id, &nft_pools);
+    }
+
+    pub fn internal_remove_nft_asset(
+        &mut self,
+        nft_contract_id: &NFTContractId,
+        token_id: &NFTTokenId,
+    ) {
+        let mut nft_pools = self.internal_get_nft_pools(nft_contract_id);
+        nft_pools.remove(token_id);
+        self.nft_pools.insert(nft_contract_id, &nft_pools);
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Returns the NFTs deposited to the collection from a given index up to a given limit.
+    pub fn get_nft_assets_paged(
+        &self,
+        nft_contract_id: NFTContractId,
+        from_index: Option<u64>,
+        limit: Option<u64>,
+    ) -> Vec<NftPool> {
+        let nft_pools = self.internal_get_nft_pools(&nft_contract_id);
+        let values = nft_pools.values_as_vector();
+        let from_index = from_index.unwrap_or(0);
+        let limit = limit.unwrap_or(values.len());
+        (from_index..std::cmp::min(values.len(), from_index + limit))
+            .map(|index| values.get(index).unwrap())
+            .collect()
+    }
+
+    /// Returns the number of NFTs deposited to the collection.
+    pub fn get_num_nfts(&self, nft_contract_id: NFTContractId) -> u32 {
+        self.internal_get_nft_pools(&nft_contract_id).len() as _
+    }
+}
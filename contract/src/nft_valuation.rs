@@ -0,0 +1,105 @@
+use crate::*;
+
+/// Represents a valuation of the NFT collection. Every token is valued as one unit at the oracle
+/// price of the collection, i.e. its floor price, multiplied by the trait multiplier of the token.
+/// Example:
+/// The token "42" is valued at 250% of the floor price, 50% max LTV.
+/// JSON:
+/// ```json
+/// {
+///   "token_multipliers": {
+///     "42": 25000
+///   },
+///   "max_ltv": 5000
+/// }
+/// ```
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug))]
+#[serde(crate = "near_sdk::serde")]
+pub struct NftValuation {
+    /// The multipliers of the floor price for the tokens with rare traits (multiplied by 10000).
+    /// E.g. 25000 means the token is valued at 250% of the floor price.
+    /// The tokens without a multiplier are valued at the floor price.
+    pub token_multipliers: HashMap<NFTTokenId, u32>,
+    /// The maximum loan-to-value ratio of the collection (multiplied by 10000).
+    /// Caps the volatility ratio of the collection when computing the collateral value.
+    /// E.g. 5000 means at most 50% of the NFT value can back borrowing.
+    pub max_ltv: u32,
+}
+
+impl Default for NftValuation {
+    fn default() -> Self {
+        Self {
+            token_multipliers: HashMap::new(),
+            max_ltv: MAX_RATIO,
+        }
+    }
+}
+
+impl NftValuation {
+    pub fn assert_valid(&self) {
+        assert!(
+            self.max_ltv > 0 && self.max_ltv <= MAX_RATIO,
+            "The max LTV should be greater than 0% and no more than 100%"
+        );
+        assert!(
+            self.token_multipliers
+                .values()
+                .all(|multiplier| *multiplier > 0),
+            "The token multiplier should be greater than 0"
+        );
+    }
+
+    /// Returns the value of the given NFT token.
+    pub fn get_value(
+        &self,
+        token_id: &NFTTokenId,
+        price: &Price,
+        extra_decimals: u8,
+    ) -> BigDecimal {
+        // Fix NFT balance is 1 (decimals 24)
+        let balance = 10u128.pow(24);
+        let value = BigDecimal::from_balance_price(balance, price, extra_decimals);
+        match self.token_multipliers.get(token_id) {
+            Some(multiplier) => value.mul_ratio(*multiplier),
+            None => value,
+        }
+    }
+
+    /// Returns the ratio of the NFT value that can be used as a collateral.
+    pub fn get_collateral_ratio(&self, volatility_ratio: u32) -> u32 {
+        std::cmp::min(volatility_ratio, self.max_ltv)
+    }
+}
+
+impl Contract {
+    pub fn internal_get_nft_valuation(&self, nft_contract_id: &NFTContractId) -> NftValuation {
+        self.nft_valuations.get(nft_contract_id).unwrap_or_default()
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Returns the valuation of the NFT collection with the given nft_contract_id.
+    pub fn get_nft_valuation(&self, nft_contract_id: NFTContractId) -> NftValuation {
+        self.internal_get_nft_valuation(&nft_contract_id)
+    }
+
+    /// Updates the valuation of the NFT collection with the given nft_contract_id.
+    /// - Panics if the valuation is invalid.
+    /// - Panics if an asset with the given nft_contract_id doesn't exist.
+    /// - Requires one yoctoNEAR.
+    /// - Requires to be called by the contract owner.
+    #[payable]
+    pub fn update_nft_valuation(
+        &mut self,
+        nft_contract_id: NFTContractId,
+        nft_valuation: NftValuation,
+    ) {
+        assert_one_yocto();
+        nft_valuation.assert_valid();
+        self.assert_owner();
+        assert!(self.assets.contains_key(&nft_contract_id));
+        self.nft_valuations.insert(&nft_contract_id, &nft_valuation);
+    }
+}
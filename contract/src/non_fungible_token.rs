@@ -1,10 +1,22 @@
 use crate::*;
 use near_contract_standards::non_fungible_token::core::NonFungibleTokenReceiver;
 use near_contract_standards::non_fungible_token::TokenId;
-use near_sdk::{env, is_promise_success, log, near_bindgen, AccountId, Gas, PromiseOrValue};
+use near_sdk::{
+    env, is_promise_success, log, near_bindgen, serde_json, AccountId, Gas, PromiseOrValue,
+};
 
 const GAS_FOR_NFT_TRANSFER: Gas = Gas(10_000_000_000_000); // Gas(BASE_GAS) + Gas(PROMISE_CALL);
 
+/// The message of the NFT transfer. The NFT is deposited to the main account of the sender, if the
+/// message is empty.
+#[derive(Deserialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug, Serialize))]
+#[serde(crate = "near_sdk::serde")]
+pub enum NftReceiverMsg {
+    /// Deposits the NFT to the given position of the sender.
+    DepositToPosition { position_id: PositionId },
+}
+
 #[ext_contract(ext_nft_contract)]
 pub trait NonFungibleTokenCore {
     fn nft_transfer(
@@ -44,14 +56,30 @@ impl NonFungibleTokenReceiver for Contract {
         token_id: TokenId,
         msg: String,
     ) -> PromiseOrValue<bool> {
+        self.assert_not_paused();
+        self.assert_operation_not_paused(PausableOperation::NftDeposit);
         let nft_contract_id = env::predecessor_account_id();
         let asset = self.internal_unwrap_asset(&nft_contract_id);
         assert!(
             asset.config.can_deposit,
             "Deposits for this asset are not enabled"
         );
+        assert!(
+            asset.config.can_use_as_collateral,
+            "This NFT can't be used as a collateral"
+        );
 
-        let mut account = self.internal_unwrap_account(&sender_id);
+        let position_id = if msg.is_empty() {
+            None
+        } else {
+            match serde_json::from_str(&msg).expect("Can't parse NftReceiverMsg") {
+                NftReceiverMsg::DepositToPosition { position_id } => Some(position_id),
+            }
+        };
+        let mut account = self.internal_unwrap_account_or_position(&sender_id, position_id);
+        if let Some(e_mode_category) = self.internal_get_e_mode_category(&account) {
+            e_mode_category.assert_contains(&nft_contract_id);
+        }
         account.add_affected_farm(FarmId::SuppliedNFT(nft_contract_id.clone()));
 
         // Add NFT to the account assets
@@ -62,7 +90,7 @@ impl NonFungibleTokenReceiver for Contract {
         self.internal_execute(&sender_id, &mut account, actions, Prices::new());
 
         // Save all change to the account
-        self.internal_set_account(&sender_id, account);
+        self.internal_set_account_or_position(&sender_id, position_id, account);
 
         log!(
             "in nft_on_transfer; sender_id={}, previous_owner_id={}, token_id={}, msg={}",
@@ -73,7 +101,12 @@ impl NonFungibleTokenReceiver for Contract {
         );
         log!("====> message from sender: {:?}", msg);
         // Add NFT to asset
-        self.internal_set_nft_asset(&nft_contract_id, sender_id.clone(), token_id.clone(), asset);
+        self.internal_set_nft_asset(
+            &nft_contract_id,
+            sender_id.clone(),
+            position_id,
+            token_id.clone(),
+        );
         PromiseOrValue::Value(false)
     }
 }
@@ -123,6 +156,8 @@ impl ExtSelf for Contract {
             self.internal_nft_deposit(&mut account, &nft_contract_id, &token_id);
             events::emit::withdraw_nft_failed(&account_id, &nft_contract_id, &token_id);
             self.internal_set_account(&account_id, account);
+            // The NFT is refunded to the main account, even if it was withdrawn from a position.
+            self.internal_set_nft_asset(&nft_contract_id, account_id.clone(), None, token_id);
         } else {
             events::emit::withdraw_nft_succeeded(&account_id, &nft_contract_id, &token_id);
         }
@@ -0,0 +1,174 @@
+use crate::*;
+use std::convert::TryFrom;
+
+/// The latest price of an asset submitted by an oracle.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct OraclePrice {
+    pub price: Price,
+    /// The timestamp of the price data the price was submitted with.
+    #[serde(with = "u64_dec_format")]
+    pub timestamp: Timestamp,
+}
+
+impl Config {
+    pub fn is_oracle(&self, account_id: &AccountId) -> bool {
+        &self.oracle_account_id == account_id || self.extra_oracle_account_ids.contains(account_id)
+    }
+}
+
+/// Returns the median of the given prices. A majority of the prices has to be within the maximum
+/// deviation from the median.
+fn aggregate_prices(prices: Vec<Price>, max_price_deviation: u32) -> Option<Price> {
+    let decimals = prices.iter().map(|price| price.decimals).max()?;
+    let mut multipliers: Vec<U256> = prices
+        .iter()
+        .map(|price| {
+            U256::from(price.multiplier) * U256::exp10((decimals - price.decimals) as usize)
+        })
+        .collect();
+    multipliers.sort();
+    let mid = multipliers.len() / 2;
+    let median = if multipliers.len() % 2 == 1 {
+        multipliers[mid]
+    } else {
+        (multipliers[mid - 1] + multipliers[mid]) / 2
+    };
+    let max_deviation = median * U256::from(max_price_deviation) / U256::from(MAX_RATIO);
+    let num_valid = multipliers
+        .iter()
+        .filter(|multiplier| {
+            let deviation = if **multiplier > median {
+                **multiplier - median
+            } else {
+                median - **multiplier
+            };
+            deviation <= max_deviation
+        })
+        .count();
+    assert!(
+        num_valid * 2 > multipliers.len(),
+        "The oracle prices deviate more than allowed"
+    );
+    Some(Price {
+        multiplier: median.as_u128(),
+        decimals,
+    })
+}
+
+impl Contract {
+    /// Returns the latest prices of the asset submitted by the current oracles, that are not stale.
+    fn internal_get_fresh_oracle_prices(
+        &self,
+        config: &Config,
+        oracle_prices: &HashMap<AccountId, OraclePrice>,
+    ) -> Vec<Price> {
+        let timestamp = env::block_timestamp();
+        let maximum_staleness_duration = to_nano(config.maximum_staleness_duration_sec);
+        oracle_prices
+            .iter()
+            .filter(|(oracle_id, oracle_price)| {
+                config.is_oracle(oracle_id)
+                    && timestamp - oracle_price.timestamp <= maximum_staleness_duration
+            })
+            .map(|(_, oracle_price)| oracle_price.price)
+            .collect()
+    }
+
+    /// Stores the prices submitted by the oracle and returns the prices of the same assets
+    /// aggregated from all oracles. The stale prices are ignored, so the prices of the other
+    /// oracles are used if the submitted price data is stale.
+    /// The aggregated prices are recorded to the price history. If the TWAP is enabled, the TWAP
+    /// prices are returned instead.
+    pub fn internal_aggregate_price_data(
+        &mut self,
+        oracle_id: &AccountId,
+        data: PriceData,
+    ) -> Prices {
+        let config = self.internal_config();
+        let timestamp = env::block_timestamp();
+        let is_fresh = timestamp - data.timestamp <= to_nano(config.maximum_staleness_duration_sec);
+        let mut prices = Prices::new();
+        for AssetOptionalPrice { asset_id, price } in data.prices {
+            let token_id = AccountId::try_from(asset_id).expect("Asset is not a valid token ID");
+            let mut oracle_prices = self.oracle_prices.get(&token_id).unwrap_or_default();
+            if let (Some(price), true) = (price, is_fresh) {
+                price.assert_valid();
+                oracle_prices.insert(
+                    oracle_id.clone(),
+                    OraclePrice {
+                        price,
+                        timestamp: data.timestamp,
+                    },
+                );
+                self.oracle_prices.insert(&token_id, &oracle_prices);
+            }
+            let fresh_prices = self.internal_get_fresh_oracle_prices(&config, &oracle_prices);
+            if let Some(price) = aggregate_prices(fresh_prices, config.max_price_deviation) {
+                self.internal_record_price(&config, &token_id, price);
+                if config.twap_duration_sec > 0 {
+                    let twap_price = self.internal_get_twap_price(&config, &token_id).unwrap();
+                    prices.insert(token_id, twap_price);
+                } else {
+                    prices.insert(token_id, price);
+                }
+            }
+        }
+        prices
+    }
+
+    /// Returns the prices of the assets the account has positions in, aggregated from the latest
+    /// prices of all oracles that are not stale. If the TWAP is enabled, the TWAP prices are
+    /// returned instead. The assets without fresh prices are skipped.
+    pub fn internal_get_account_prices(&self, account: &Account) -> Prices {
+        let config = self.internal_config();
+        let mut prices = Prices::new();
+        let token_ids = account
+            .collateral
+            .keys()
+            .chain(
+                account
+                    .nft_supplied
+                    .values()
+                    .map(|nft_asset| &nft_asset.nft_contract_id),
+            )
+            .chain(account.borrowed.keys())
+            .chain(account.stable_borrowed.keys());
+        for token_id in token_ids {
+            if prices.get(token_id).is_some() {
+                continue;
+            }
+            let oracle_prices = self.oracle_prices.get(token_id).unwrap_or_default();
+            let fresh_prices = self.internal_get_fresh_oracle_prices(&config, &oracle_prices);
+            if let Some(price) = aggregate_prices(fresh_prices, config.max_price_deviation) {
+                if config.twap_duration_sec > 0 {
+                    let twap_price = self.internal_get_twap_price(&config, token_id).unwrap();
+                    prices.insert(token_id.clone(), twap_price);
+                } else {
+                    prices.insert(token_id.clone(), price);
+                }
+            }
+        }
+        prices
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Returns the price of the asset aggregated from the latest prices of all oracles, that are
+    /// not stale. The price is the median of these prices.
+    /// - Panics if most of the prices deviate from the median more than allowed.
+    pub fn get_price(&self, token_id: TokenId) -> Option<Price> {
+        let config = self.internal_config();
+        let oracle_prices = self.oracle_prices.get(&token_id).unwrap_or_default();
+        aggregate_prices(
+            self.internal_get_fresh_oracle_prices(&config, &oracle_prices),
+            config.max_price_deviation,
+        )
+    }
+
+    /// Returns the latest prices of the asset submitted by each oracle.
+    pub fn get_oracle_prices(&self, token_id: TokenId) -> HashMap<AccountId, OraclePrice> {
+        self.oracle_prices.get(&token_id).unwrap_or_default()
+    }
+}
@@ -0,0 +1,92 @@
+use crate::*;
+
+/// An operation that can be paused individually by the guardian.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug))]
+#[serde(crate = "near_sdk::serde")]
+pub enum PausableOperation {
+    /// Deposits of fungible tokens, except deposits to the reserve.
+    Deposit,
+    Borrow,
+    /// Liquidations of fungible token and NFT collateral, including force closing.
+    Liquidate,
+    FarmClaim,
+    NftDeposit,
+    FlashLoan,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Default, Clone)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug))]
+#[serde(crate = "near_sdk::serde")]
+pub struct PauseStatus {
+    /// Whether the whole contract is paused.
+    pub paused: bool,
+    /// The list of individually paused operations.
+    pub paused_operations: Vec<PausableOperation>,
+}
+
+impl Contract {
+    pub fn assert_not_paused(&self) {
+        assert!(!self.pause_status.paused, "The contract is paused");
+    }
+
+    pub fn assert_operation_not_paused(&self, operation: PausableOperation) {
+        assert!(
+            !self.pause_status.paused_operations.contains(&operation),
+            "This operation is paused"
+        );
+    }
+
+    pub fn assert_guardian_or_owner(&self) {
+        let config = self.internal_config();
+        let predecessor_id = env::predecessor_account_id();
+        assert!(
+            predecessor_id == config.owner_id
+                || config.guardian_id.as_ref() == Some(&predecessor_id),
+            "Not a guardian or an owner"
+        );
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Returns the current pause status.
+    pub fn get_pause_status(&self) -> PauseStatus {
+        self.pause_status.clone()
+    }
+
+    /// Pauses the given operation or the whole contract if the operation is not given.
+    /// - Requires one yoctoNEAR.
+    /// - Requires to be called by the guardian or the contract owner.
+    #[payable]
+    pub fn pause(&mut self, operation: Option<PausableOperation>) {
+        assert_one_yocto();
+        self.assert_guardian_or_owner();
+        if let Some(operation) = operation {
+            if !self.pause_status.paused_operations.contains(&operation) {
+                self.pause_status.paused_operations.push(operation);
+            }
+        } else {
+            self.pause_status.paused = true;
+        }
+        events::emit::pause(&env::predecessor_account_id(), operation);
+    }
+
+    /// Unpauses the given operation or the whole contract if the operation is not given.
+    /// Unpausing the whole contract doesn't unpause individually paused operations.
+    /// - Requires one yoctoNEAR.
+    /// - Requires to be called by the contract owner.
+    #[payable]
+    pub fn unpause(&mut self, operation: Option<PausableOperation>) {
+        assert_one_yocto();
+        self.assert_owner();
+        if let Some(operation) = operation {
+            self.pause_status
+                .paused_operations
+                .retain(|paused_operation| paused_operation != &operation);
+        } else {
+            self.pause_status.paused = false;
+        }
+        events::emit::unpause(&env::predecessor_account_id(), operation);
+    }
+}
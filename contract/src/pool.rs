@@ -42,12 +42,13 @@ impl Pool {
     }
 
     pub fn shares_to_amount(&self, shares: Shares, round_up: bool) -> Balance {
-        if shares.0 >= self.balance || shares.0 == self.shares.0 {
+        if shares.0 == self.shares.0 || (shares.0 >= self.balance && self.balance >= self.shares.0)
+        {
             self.balance
         } else {
             assert!(
                 shares.0 < self.shares.0,
-                "Invariant: balance should not be less than the number of shares in the pool"
+                "The number of shares is greater than the number of shares in the pool"
             );
             let extra = if round_up {
                 U256::from(self.shares.0 - 1)
@@ -74,3 +75,43 @@ impl Pool {
         self.balance -= amount;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The pool after 10% of the balance is written off.
+    fn written_off_pool() -> Pool {
+        Pool {
+            shares: 1000.into(),
+            balance: 900,
+        }
+    }
+
+    #[test]
+    fn test_shares_to_amount_written_off() {
+        let pool = written_off_pool();
+        assert_eq!(pool.shares_to_amount(101.into(), false), 90);
+        assert_eq!(pool.shares_to_amount(101.into(), true), 91);
+        // The number of shares above the balance is still converted at the pool rate.
+        assert_eq!(pool.shares_to_amount(950.into(), false), 855);
+        assert_eq!(pool.shares_to_amount(1000.into(), false), 900);
+    }
+
+    #[test]
+    fn test_amount_to_shares_written_off() {
+        let pool = written_off_pool();
+        assert_eq!(pool.amount_to_shares(90, false), 100.into());
+        assert_eq!(pool.amount_to_shares(91, false), 101.into());
+        assert_eq!(pool.amount_to_shares(91, true), 102.into());
+        assert_eq!(pool.amount_to_shares(900, false), 1000.into());
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "The number of shares is greater than the number of shares in the pool"
+    )]
+    fn test_shares_to_amount_written_off_too_many_shares() {
+        written_off_pool().shares_to_amount(1001.into(), false);
+    }
+}
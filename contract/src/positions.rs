@@ -0,0 +1,273 @@
+use crate::*;
+
+/// The ID of a position of an account. The positions are numbered from 1, the main account has no
+/// position ID.
+pub type PositionId = u32;
+
+#[derive(Serialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug, Deserialize))]
+#[serde(crate = "near_sdk::serde")]
+pub struct AccountPositionsView {
+    pub account_id: AccountId,
+    /// The main account followed by the open positions.
+    pub positions: Vec<AccountDetailedView>,
+    /// The total supplied balances of the main account and all positions.
+    pub supplied: Vec<AssetView>,
+    /// The total collateral of the main account and all positions.
+    pub collateral: Vec<AssetView>,
+    /// The total borrowed balances of the main account and all positions.
+    pub borrowed: Vec<AssetView>,
+    /// The total stable debt of the main account and all positions. The APR is the average of the
+    /// stable APRs weighted by the debt.
+    pub stable_borrowed: Vec<StableDebtView>,
+}
+
+/// Adds the shares of the given account to the total shares by the asset.
+fn add_shares(total: &mut Vec<(TokenId, Shares)>, shares: &HashMap<TokenId, Shares>) {
+    for (token_id, shares) in shares {
+        match total.iter_mut().find(|(id, _)| id == token_id) {
+            Some((_, total_shares)) => total_shares.0 += shares.0,
+            None => total.push((token_id.clone(), *shares)),
+        }
+    }
+}
+
+impl Contract {
+    pub fn internal_get_position(
+        &self,
+        account_id: &AccountId,
+        position_id: PositionId,
+    ) -> Option<Account> {
+        self.positions
+            .get(&(account_id.clone(), position_id))
+            .map(|o| o.into())
+    }
+
+    pub fn internal_unwrap_position(
+        &self,
+        account_id: &AccountId,
+        position_id: PositionId,
+    ) -> Account {
+        self.internal_get_position(account_id, position_id)
+            .expect("Position is not found")
+    }
+
+    /// Saves the position. The storage is charged to the main account.
+    pub fn internal_set_position(
+        &mut self,
+        account_id: &AccountId,
+        position_id: PositionId,
+        mut position: Account,
+    ) {
+        let mut storage = self.internal_unwrap_storage(account_id);
+        storage
+            .storage_tracker
+            .consume(&mut position.storage_tracker);
+        storage.storage_tracker.start();
+        self.internal_update_borrowers(account_id, &position);
+        self.positions
+            .insert(&(account_id.clone(), position_id), &position.into());
+        storage.storage_tracker.stop();
+        self.internal_set_storage(account_id, storage);
+    }
+
+    /// Returns the given position of the account, or the main account if the position ID is not
+    /// given.
+    pub fn internal_unwrap_account_or_position(
+        &self,
+        account_id: &AccountId,
+        position_id: Option<PositionId>,
+    ) -> Account {
+        match position_id {
+            Some(position_id) => self.internal_unwrap_position(account_id, position_id),
+            None => self.internal_unwrap_account(account_id),
+        }
+    }
+
+    pub fn internal_set_account_or_position(
+        &mut self,
+        account_id: &AccountId,
+        position_id: Option<PositionId>,
+        account: Account,
+    ) {
+        match position_id {
+            Some(position_id) => self.internal_set_position(account_id, position_id, account),
+            None => self.internal_set_account(account_id, account),
+        }
+    }
+
+    /// Moves the supplied shares of the given account or position to another position of the
+    /// same account, or to the main account if the position ID is not given.
+    pub fn internal_transfer_to_position(
+        &mut self,
+        account_id: &AccountId,
+        account: &mut Account,
+        position_id: Option<PositionId>,
+        asset_amount: &AssetAmount,
+    ) -> Balance {
+        assert_ne!(
+            account.position_id, position_id,
+            "Can't transfer to the same position"
+        );
+        let token_id = &asset_amount.token_id;
+        let asset = self.internal_unwrap_asset(token_id);
+        let mut account_asset = account.internal_unwrap_asset(token_id);
+        let (shares, amount) =
+            asset_amount_to_shares(&asset.supplied, account_asset.shares, asset_amount, false);
+        account_asset.withdraw_shares(shares);
+        account.internal_set_asset(token_id, account_asset);
+
+        let mut target_account = self.internal_unwrap_account_or_position(account_id, position_id);
+        target_account.add_affected_farm(FarmId::Supplied(token_id.clone()));
+        let mut target_asset = target_account.internal_get_asset_or_default(token_id);
+        target_asset.deposit_shares(shares);
+        target_account.internal_set_asset(token_id, target_asset);
+        self.internal_account_apply_affected_farms(&mut target_account);
+        self.internal_set_account_or_position(account_id, position_id, target_account);
+
+        amount
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Opens a new position of the predecessor account and returns its ID. The position has its
+    /// own assets, NFTs, debt, farms and health factor. The storage of the position is charged to
+    /// the account. The IDs of the closed positions are not reused.
+    /// - Requires one yoctoNEAR.
+    #[payable]
+    pub fn open_position(&mut self) -> PositionId {
+        assert_one_yocto();
+        self.assert_not_paused();
+        let account_id = env::predecessor_account_id();
+        let mut account = self.internal_unwrap_account(&account_id);
+        account.last_position_id += 1;
+        let position_id = account.last_position_id;
+        account.position_ids.push(position_id);
+
+        let mut position = Account::new(&account_id);
+        position.position_id = Some(position_id);
+        self.internal_set_position(&account_id, position_id, position);
+        self.internal_set_account(&account_id, account);
+        events::emit::open_position(&account_id, position_id);
+        position_id
+    }
+
+    /// Closes the position of the predecessor account and releases its storage.
+    /// - Panics if the position has any assets or debt.
+    /// - Requires one yoctoNEAR.
+    #[payable]
+    pub fn close_position(&mut self, position_id: PositionId) {
+        assert_one_yocto();
+        self.assert_not_paused();
+        let account_id = env::predecessor_account_id();
+        let mut account = self.internal_unwrap_account(&account_id);
+        let position = self.internal_unwrap_position(&account_id, position_id);
+        assert!(
+            position.supplied.is_empty()
+                && position.collateral.is_empty()
+                && position.borrowed.is_empty()
+                && position.stable_borrowed.is_empty()
+                && position.nft_supplied.is_empty(),
+            "The position has assets or debt"
+        );
+        account.position_ids.retain(|id| *id != position_id);
+
+        let mut storage = self.internal_unwrap_storage(&account_id);
+        storage.storage_tracker.start();
+        self.positions.remove(&(account_id.clone(), position_id));
+        storage.storage_tracker.stop();
+        self.internal_set_storage(&account_id, storage);
+        self.internal_set_account(&account_id, account);
+        events::emit::close_position(&account_id, position_id);
+    }
+
+    /// Executes a given list actions on behalf of the predecessor account in the given position.
+    /// - Requires one yoctoNEAR.
+    #[payable]
+    pub fn execute_position(&mut self, position_id: PositionId, actions: Vec<Action>) {
+        assert_one_yocto();
+        self.assert_not_paused();
+        let account_id = env::predecessor_account_id();
+        let mut position = self.internal_unwrap_position(&account_id, position_id);
+        self.internal_execute(&account_id, &mut position, actions, Prices::new());
+        self.internal_set_position(&account_id, position_id, position);
+    }
+
+    /// Returns detailed information about the given position of the account.
+    pub fn get_position(
+        &self,
+        account_id: AccountId,
+        position_id: PositionId,
+    ) -> Option<AccountDetailedView> {
+        self.internal_get_position(&account_id, position_id)
+            .map(|position| self.account_into_detailed_view(position))
+    }
+
+    /// Returns the main account and all positions of the given account, together with their total
+    /// balances.
+    pub fn get_account_positions(&self, account_id: AccountId) -> Option<AccountPositionsView> {
+        let account = self.internal_get_account(&account_id)?;
+        let accounts: Vec<Account> = std::iter::once(account.clone())
+            .chain(
+                account
+                    .position_ids
+                    .iter()
+                    .map(|position_id| self.internal_unwrap_position(&account_id, *position_id)),
+            )
+            .collect();
+
+        let mut supplied = vec![];
+        let mut collateral = vec![];
+        let mut borrowed = vec![];
+        let mut stable_borrowed: Vec<(TokenId, Balance, BigDecimal)> = vec![];
+        for account in accounts.iter() {
+            add_shares(&mut supplied, &account.supplied);
+            add_shares(&mut collateral, &account.collateral);
+            add_shares(&mut borrowed, &account.borrowed);
+            for (token_id, stable_debt) in account.stable_borrowed.iter() {
+                let amount = stable_debt.get_amount();
+                let weighted_apr = stable_debt.get_apr() * BigDecimal::from(amount);
+                match stable_borrowed.iter_mut().find(|(id, _, _)| id == token_id) {
+                    Some((_, total_amount, total_weighted_apr)) => {
+                        *total_amount += amount;
+                        *total_weighted_apr = *total_weighted_apr + weighted_apr;
+                    }
+                    None => stable_borrowed.push((token_id.clone(), amount, weighted_apr)),
+                }
+            }
+        }
+
+        Some(AccountPositionsView {
+            account_id,
+            positions: accounts
+                .into_iter()
+                .map(|account| self.account_into_detailed_view(account))
+                .collect(),
+            supplied: supplied
+                .into_iter()
+                .map(|(token_id, shares)| self.get_asset_view(token_id, shares, false))
+                .collect(),
+            collateral: collateral
+                .into_iter()
+                .map(|(token_id, shares)| self.get_asset_view(token_id, shares, false))
+                .collect(),
+            borrowed: borrowed
+                .into_iter()
+                .map(|(token_id, shares)| self.get_asset_view(token_id, shares, true))
+                .collect(),
+            stable_borrowed: stable_borrowed
+                .into_iter()
+                .map(|(token_id, amount, weighted_apr)| StableDebtView {
+                    token_id,
+                    balance: amount,
+                    apr: if amount > 0 {
+                        weighted_apr / BigDecimal::from(amount)
+                    } else {
+                        BigDecimal::zero()
+                    },
+                })
+                .collect(),
+        })
+    }
+}
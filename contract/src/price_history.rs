@@ -0,0 +1,102 @@
+use crate::*;
+
+/// An aggregated price of an asset recorded at the given block timestamp.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PricePoint {
+    pub price: Price,
+    #[serde(with = "u64_dec_format")]
+    pub timestamp: Timestamp,
+}
+
+/// Returns the time-weighted average of the price history over the given duration ending at the
+/// given timestamp. Every price is weighted by the time until the next price, so the latest price
+/// is weighted by the time since it was recorded.
+fn compute_twap(
+    price_history: &[PricePoint],
+    timestamp: Timestamp,
+    duration: Duration,
+) -> Option<Price> {
+    let last_point = price_history.last()?;
+    let decimals = price_history
+        .iter()
+        .map(|point| point.price.decimals)
+        .max()
+        .unwrap();
+    let window_start = timestamp.saturating_sub(duration);
+    let mut weighted_sum = U256::zero();
+    let mut total_weight = 0u64;
+    for (i, point) in price_history.iter().enumerate() {
+        let start = std::cmp::max(point.timestamp, window_start);
+        let end = price_history
+            .get(i + 1)
+            .map(|next_point| next_point.timestamp)
+            .unwrap_or(timestamp);
+        if end <= start {
+            continue;
+        }
+        let multiplier = U256::from(point.price.multiplier)
+            * U256::exp10((decimals - point.price.decimals) as usize);
+        weighted_sum += multiplier * U256::from(end - start);
+        total_weight += end - start;
+    }
+    if total_weight == 0 {
+        // All prices were recorded in the current block.
+        return Some(last_point.price);
+    }
+    Some(Price {
+        multiplier: (weighted_sum / U256::from(total_weight)).as_u128(),
+        decimals,
+    })
+}
+
+impl Contract {
+    pub fn internal_get_price_history(&self, token_id: &TokenId) -> Vec<PricePoint> {
+        self.price_history.get(token_id).unwrap_or_default()
+    }
+
+    /// Records the aggregated price of the asset. The price recorded earlier in the same block is
+    /// replaced, and only the latest `price_history_size` prices are kept.
+    pub fn internal_record_price(&mut self, config: &Config, token_id: &TokenId, price: Price) {
+        if config.price_history_size == 0 {
+            return;
+        }
+        let timestamp = env::block_timestamp();
+        let mut price_history = self.internal_get_price_history(token_id);
+        if price_history
+            .last()
+            .map(|point| point.timestamp == timestamp)
+            .unwrap_or(false)
+        {
+            price_history.pop();
+        }
+        price_history.push(PricePoint { price, timestamp });
+        let num_extra = price_history
+            .len()
+            .saturating_sub(config.price_history_size as usize);
+        price_history.drain(..num_extra);
+        self.price_history.insert(token_id, &price_history);
+    }
+
+    /// Returns the TWAP of the asset over the `twap_duration_sec`, if the asset has a price history.
+    pub fn internal_get_twap_price(&self, config: &Config, token_id: &TokenId) -> Option<Price> {
+        compute_twap(
+            &self.internal_get_price_history(token_id),
+            env::block_timestamp(),
+            to_nano(config.twap_duration_sec),
+        )
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Returns the latest aggregated prices of the asset, from the oldest to the newest.
+    pub fn get_price_history(&self, token_id: TokenId) -> Vec<PricePoint> {
+        self.internal_get_price_history(&token_id)
+    }
+
+    /// Returns the time-weighted average price of the asset over the `twap_duration_sec`.
+    pub fn get_twap_price(&self, token_id: TokenId) -> Option<Price> {
+        self.internal_get_twap_price(&self.internal_config(), &token_id)
+    }
+}
@@ -5,7 +5,14 @@ use near_sdk::serde_json;
 #[cfg_attr(not(target_arch = "wasm32"), derive(Debug, Serialize))]
 #[serde(crate = "near_sdk::serde")]
 pub enum PriceReceiverMsg {
-    Execute { actions: Vec<Action> },
+    Execute {
+        actions: Vec<Action>,
+    },
+    /// Executes the actions in the given position of the sender.
+    ExecutePosition {
+        position_id: PositionId,
+        actions: Vec<Action>,
+    },
 }
 
 impl Contract {
@@ -20,28 +27,36 @@ impl Contract {
             data.timestamp <= timestamp,
             "Price data timestamp is in the future"
         );
-        assert!(
-            timestamp - data.timestamp <= to_nano(config.maximum_staleness_duration_sec),
-            "Price data timestamp is too stale"
-        );
     }
 }
 
 #[near_bindgen]
 impl OraclePriceReceiver for Contract {
-    /// The method will execute a given list of actions in the msg using the prices from the `data`
-    /// provided by the oracle on behalf of the sender_id.
-    /// - Requires to be called by the oracle account ID.
+    /// The method will execute a given list of actions in the msg on behalf of the sender_id using
+    /// the prices from the `data` provided by the oracle, aggregated with the latest prices of the
+    /// other oracles.
+    /// - Requires to be called by one of the oracle account IDs.
     fn oracle_on_call(&mut self, sender_id: AccountId, data: PriceData, msg: String) {
-        assert_eq!(env::predecessor_account_id(), self.get_oracle_account_id());
+        let oracle_id = env::predecessor_account_id();
+        assert!(
+            self.internal_config().is_oracle(&oracle_id),
+            "Not an oracle"
+        );
+        self.assert_not_paused();
 
-        let actions = match serde_json::from_str(&msg).expect("Can't parse PriceReceiverMsg") {
-            PriceReceiverMsg::Execute { actions } => actions,
-        };
+        let (position_id, actions) =
+            match serde_json::from_str(&msg).expect("Can't parse PriceReceiverMsg") {
+                PriceReceiverMsg::Execute { actions } => (None, actions),
+                PriceReceiverMsg::ExecutePosition {
+                    position_id,
+                    actions,
+                } => (Some(position_id), actions),
+            };
 
-        let mut account = self.internal_unwrap_account(&sender_id);
+        let mut account = self.internal_unwrap_account_or_position(&sender_id, position_id);
         self.validate_price_data(&data);
-        self.internal_execute(&sender_id, &mut account, actions, data.into());
-        self.internal_set_account(&sender_id, account);
+        let prices = self.internal_aggregate_price_data(&oracle_id, data);
+        self.internal_execute(&sender_id, &mut account, actions, prices);
+        self.internal_set_account_or_position(&sender_id, position_id, account);
     }
 }
@@ -18,9 +18,17 @@ impl Prices {
         }
     }
 
+    pub fn get(&self, token_id: &TokenId) -> Option<&Price> {
+        self.prices.get(token_id)
+    }
+
     pub fn get_unwrap(&self, token_id: &TokenId) -> &Price {
         self.prices.get(token_id).expect("Asset price is missing")
     }
+
+    pub fn insert(&mut self, token_id: TokenId, price: Price) {
+        self.prices.insert(token_id, price);
+    }
 }
 
 impl From<PriceData> for Prices {
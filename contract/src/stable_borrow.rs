@@ -0,0 +1,315 @@
+use crate::*;
+
+/// The debt of an account borrowed at a stable rate.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug))]
+#[serde(crate = "near_sdk::serde")]
+pub struct StableDebt {
+    /// The borrowed amount including the interest accrued until the last update.
+    #[serde(with = "u128_dec_format")]
+    pub amount: Balance,
+    /// The compounding rate fixed at the borrow time or at the last rebalance.
+    pub rate: LowU128,
+    /// When the debt was last updated.
+    #[serde(with = "u64_dec_format")]
+    pub last_update_timestamp: Timestamp,
+}
+
+impl StableDebt {
+    pub fn new(rate: BigDecimal) -> Self {
+        Self {
+            amount: 0,
+            rate: rate.into(),
+            last_update_timestamp: env::block_timestamp(),
+        }
+    }
+
+    /// Accrues the interest since the last update.
+    pub fn update(&mut self) {
+        let time_diff_ms = nano_to_ms(env::block_timestamp() - self.last_update_timestamp);
+        if time_diff_ms > 0 {
+            self.last_update_timestamp += ms_to_nano(time_diff_ms);
+            self.amount = BigDecimal::from(self.rate)
+                .pow(time_diff_ms)
+                .round_mul_u128(self.amount);
+        }
+    }
+
+    /// Returns the current debt including the accrued interest.
+    pub fn get_amount(&self) -> Balance {
+        let mut stable_debt = self.clone();
+        stable_debt.update();
+        stable_debt.amount
+    }
+
+    pub fn get_apr(&self) -> BigDecimal {
+        BigDecimal::from(self.rate).pow(MS_PER_YEAR) - BigDecimal::one()
+    }
+}
+
+impl Asset {
+    /// Adds the stable debt with the given rate and updates the average stable rate.
+    pub fn increase_stable_borrowed(&mut self, amount: Balance, rate: BigDecimal) {
+        let total = self.stable_borrowed + amount;
+        let average_stable_rate = (BigDecimal::from(self.average_stable_rate)
+            * BigDecimal::from(self.stable_borrowed)
+            + rate * BigDecimal::from(amount))
+            / BigDecimal::from(total);
+        self.average_stable_rate = average_stable_rate.into();
+        self.stable_borrowed = total;
+    }
+
+    /// Removes the stable debt with the given rate and updates the average stable rate.
+    pub fn decrease_stable_borrowed(&mut self, amount: Balance, rate: BigDecimal) {
+        let weighted_total =
+            BigDecimal::from(self.average_stable_rate) * BigDecimal::from(self.stable_borrowed);
+        let weighted_amount = rate * BigDecimal::from(amount);
+        if amount >= self.stable_borrowed || weighted_amount >= weighted_total {
+            // The total stable debt is compounded at the average rate, so it may slightly
+            // diverge from the sum of the account debts.
+            self.stable_borrowed = 0;
+            self.average_stable_rate = BigDecimal::one().into();
+        } else {
+            self.stable_borrowed -= amount;
+            self.average_stable_rate = ((weighted_total - weighted_amount)
+                / BigDecimal::from(self.stable_borrowed))
+            .into();
+        }
+    }
+
+    pub fn get_stable_borrow_apr(&self) -> BigDecimal {
+        BigDecimal::from(self.average_stable_rate).pow(MS_PER_YEAR) - BigDecimal::one()
+    }
+}
+
+impl Account {
+    pub fn internal_get_stable_debt(&self, token_id: &TokenId) -> Option<StableDebt> {
+        self.stable_borrowed.get(token_id).map(|stable_debt| {
+            let mut stable_debt = stable_debt.clone();
+            stable_debt.update();
+            stable_debt
+        })
+    }
+
+    pub fn internal_unwrap_stable_debt(&self, token_id: &TokenId) -> StableDebt {
+        self.internal_get_stable_debt(token_id)
+            .expect("Stable borrowed asset not found")
+    }
+
+    pub fn internal_set_stable_debt(&mut self, token_id: &TokenId, stable_debt: StableDebt) {
+        if stable_debt.amount == 0 {
+            self.stable_borrowed.remove(token_id);
+        } else {
+            self.stable_borrowed.insert(token_id.clone(), stable_debt);
+        }
+    }
+}
+
+impl Contract {
+    /// Borrows the asset at the stable rate. The rate is fixed at the current borrowing rate of
+    /// the asset, including the new debt.
+    pub fn internal_borrow_stable(
+        &mut self,
+        account: &mut Account,
+        asset_amount: &AssetAmount,
+    ) -> Balance {
+        let mut asset = self.internal_unwrap_asset(&asset_amount.token_id);
+        assert!(
+            asset.config.can_borrow && asset.config.can_borrow_stable,
+            "This asset can't be borrowed at a stable rate"
+        );
+
+        let available_amount = asset.available_amount();
+        let amount = asset_amount
+            .amount
+            .or(asset_amount.max_amount)
+            .map(|amount| amount.0)
+            .unwrap_or(available_amount);
+        assert!(amount > 0, "Amount can't be 0");
+        assert!(
+            amount <= available_amount,
+            "Borrow error: Exceeded available amount {} of {}",
+            available_amount,
+            &asset_amount.token_id
+        );
+
+        let mut account_asset = account.internal_get_asset_or_default(&asset_amount.token_id);
+        let supplied_shares: Shares = asset.supplied.amount_to_shares(amount, false);
+        asset.supplied.deposit(supplied_shares, amount);
+        account_asset.deposit_shares(supplied_shares);
+        account.internal_set_asset(&asset_amount.token_id, account_asset);
+
+        // The new debt is included into the utilization before the rate is fixed.
+        asset.stable_borrowed += amount;
+        let rate = asset.get_rate();
+        asset.stable_borrowed -= amount;
+        asset.increase_stable_borrowed(amount, rate);
+        asset.assert_borrow_cap(&asset_amount.token_id);
+        self.internal_set_asset(&asset_amount.token_id, asset);
+
+        // The existing stable debt is merged at the weighted average rate.
+        let mut stable_debt = account
+            .internal_get_stable_debt(&asset_amount.token_id)
+            .unwrap_or_else(|| StableDebt::new(rate));
+        let total = stable_debt.amount + amount;
+        stable_debt.rate = ((BigDecimal::from(stable_debt.rate)
+            * BigDecimal::from(stable_debt.amount)
+            + rate * BigDecimal::from(amount))
+            / BigDecimal::from(total))
+        .into();
+        stable_debt.amount = total;
+        account.internal_set_stable_debt(&asset_amount.token_id, stable_debt);
+
+        amount
+    }
+
+    /// Repays the stable debt from the supplied balance of the given account asset.
+    pub fn internal_repay_stable(
+        &mut self,
+        account_asset: &mut AccountAsset,
+        account: &mut Account,
+        asset_amount: &AssetAmount,
+    ) -> Balance {
+        let mut asset = self.internal_unwrap_asset(&asset_amount.token_id);
+        let mut stable_debt = account.internal_unwrap_stable_debt(&asset_amount.token_id);
+
+        let available_amount = asset.supplied.shares_to_amount(account_asset.shares, false);
+        let mut amount = asset_amount
+            .amount
+            .or(asset_amount.max_amount)
+            .map(|amount| amount.0)
+            .unwrap_or(stable_debt.amount);
+        amount = std::cmp::min(std::cmp::min(amount, stable_debt.amount), available_amount);
+        if let Some(min_amount) = &asset_amount.amount {
+            assert!(amount >= min_amount.0, "Not enough supplied balance");
+        }
+        assert!(amount > 0, "Repayment amount can't be 0");
+
+        let mut supplied_shares = asset.supplied.amount_to_shares(amount, true);
+        if supplied_shares.0 > account_asset.shares.0 {
+            supplied_shares = account_asset.shares;
+        }
+        asset.supplied.withdraw(supplied_shares, amount);
+        account_asset.withdraw_shares(supplied_shares);
+
+        asset.decrease_stable_borrowed(amount, stable_debt.rate.into());
+        self.internal_set_asset(&asset_amount.token_id, asset);
+
+        stable_debt.amount -= amount;
+        account.internal_set_stable_debt(&asset_amount.token_id, stable_debt);
+
+        amount
+    }
+
+    /// Repays the stable debt of the account with the given amount of tokens, that are not in the
+    /// supplied balance.
+    /// Returns the repaid amount, that is not greater than the given amount.
+    pub fn internal_repay_stable_amount(
+        &mut self,
+        account: &mut Account,
+        token_id: &TokenId,
+        amount: Balance,
+    ) -> Balance {
+        let mut asset = self.internal_unwrap_asset(token_id);
+        let mut stable_debt = account.internal_unwrap_stable_debt(token_id);
+        let amount = std::cmp::min(amount, stable_debt.amount);
+
+        asset.decrease_stable_borrowed(amount, stable_debt.rate.into());
+        self.internal_set_asset(token_id, asset);
+
+        stable_debt.amount -= amount;
+        account.internal_set_stable_debt(token_id, stable_debt);
+
+        amount
+    }
+
+    /// Returns the total value of the stable debt of the given account.
+    pub fn compute_stable_borrowed_sum(
+        &self,
+        account: &Account,
+        prices: &Prices,
+        adjust_by_volatility: bool,
+    ) -> BigDecimal {
+        let e_mode_category = if adjust_by_volatility {
+            self.internal_get_e_mode_category(account)
+        } else {
+            None
+        };
+        account
+            .stable_borrowed
+            .iter()
+            .fold(BigDecimal::zero(), |sum, (token_id, stable_debt)| {
+                let asset = self.internal_unwrap_asset(token_id);
+                let value = BigDecimal::from_balance_price(
+                    stable_debt.get_amount(),
+                    prices.get_unwrap(token_id),
+                    asset.config.extra_decimals,
+                );
+                if adjust_by_volatility {
+                    sum + value.div_ratio(get_borrow_ratio(
+                        e_mode_category.as_ref(),
+                        token_id,
+                        &asset.config,
+                    ))
+                } else {
+                    sum + value
+                }
+            })
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Resets the stable rate of the given account debt to the current borrowing rate of the
+    /// asset. It can only be done when the spread between the current borrowing APR and the stable
+    /// APR of the debt exceeds `stable_rate_rebalance_threshold` of the asset.
+    /// If `position_id` is given, the stable debt of this position of the account is rebalanced.
+    /// - Panics if the position doesn't exist.
+    /// - Panics if the account doesn't have the stable debt in the given asset.
+    /// - Panics if the spread is within the threshold.
+    /// - Requires one yoctoNEAR.
+    /// - Requires to be called by the contract owner.
+    #[payable]
+    pub fn rebalance_stable_rate(
+        &mut self,
+        account_id: AccountId,
+        position_id: Option<PositionId>,
+        token_id: TokenId,
+    ) {
+        assert_one_yocto();
+        self.assert_owner();
+        let mut account = self.internal_unwrap_account_or_position(&account_id, position_id);
+        let mut stable_debt = account.internal_unwrap_stable_debt(&token_id);
+        let mut asset = self.internal_unwrap_asset(&token_id);
+
+        let borrow_apr = asset.get_borrow_apr();
+        let stable_apr = stable_debt.get_apr();
+        let spread = if borrow_apr > stable_apr {
+            borrow_apr - stable_apr
+        } else {
+            stable_apr - borrow_apr
+        };
+        assert!(
+            spread > BigDecimal::from_ratio(asset.config.stable_rate_rebalance_threshold),
+            "The spread {} between the borrow APR and the stable APR is within the rebalance threshold",
+            spread
+        );
+
+        let rate = asset.get_rate();
+        asset.decrease_stable_borrowed(stable_debt.amount, stable_debt.rate.into());
+        asset.increase_stable_borrowed(stable_debt.amount, rate);
+        self.internal_set_asset(&token_id, asset);
+
+        let old_rate = stable_debt.rate;
+        stable_debt.rate = rate.into();
+        events::emit::stable_rate_rebalanced(
+            &account_id,
+            position_id,
+            &token_id,
+            old_rate,
+            stable_debt.rate,
+        );
+        account.internal_set_stable_debt(&token_id, stable_debt);
+        self.internal_set_account_or_position(&account_id, position_id, account);
+    }
+}
@@ -7,10 +7,55 @@ impl Contract {
     #[private]
     #[init(ignore_state)]
     pub fn migrate_state() -> Self {
-        let contract: Self = env::state_read().unwrap();
+        let old_contract: ContractV0 = env::state_read().unwrap();
+        let mut contract: Self = old_contract.into();
+        let old_config: LazyOption<ConfigV0> = LazyOption::new(StorageKey::Config, None);
+        contract.config.set(&old_config.get().unwrap().into());
         contract
     }
 
+    /// Moves a page of the NFT pools deposited to the legacy asset with the given token_id, from
+    /// a given index up to a given limit, into the NFT pools. The page that reaches the last NFT
+    /// pool also stores the asset in the current version. Returns the number of the NFT pools
+    /// after the page.
+    /// The asset can't be used until its NFT pools are moved, so it has to be called for every
+    /// legacy asset with NFTs after the upgrade.
+    /// - Panics if the asset with the given token_id is not a legacy asset.
+    /// - Requires one yoctoNEAR.
+    /// - Requires to be called by the contract owner.
+    #[payable]
+    pub fn migrate_nft_pools(
+        &mut self,
+        token_id: TokenId,
+        from_index: Option<u64>,
+        limit: Option<u64>,
+    ) -> u64 {
+        assert_one_yocto();
+        self.assert_owner();
+        let mut asset = match self.assets.get(&token_id) {
+            Some(VAsset::V0(asset)) => asset,
+            _ => env::panic_str("The asset is not a legacy asset"),
+        };
+        let num_nft_pools = asset.nft_supplied.len();
+        let from_index = std::cmp::min(from_index.unwrap_or(0) as usize, num_nft_pools);
+        let to_index = limit
+            .map(|limit| std::cmp::min(from_index + limit as usize, num_nft_pools))
+            .unwrap_or(num_nft_pools);
+
+        let mut nft_pools = self.internal_get_nft_pools(&token_id);
+        for nft_pool in asset.nft_supplied.drain(from_index..to_index) {
+            nft_pools.insert(&nft_pool.token_id.clone(), &nft_pool.into());
+        }
+        self.nft_pools.insert(&token_id, &nft_pools);
+
+        if to_index == num_nft_pools {
+            asset.nft_supplied.clear();
+            let asset: Asset = asset.into();
+            self.assets.insert(&token_id, &asset.into());
+        }
+        (num_nft_pools - to_index) as u64
+    }
+
     /// Returns semver of this contract.
     pub fn get_version(&self) -> String {
         env!("CARGO_PKG_VERSION").to_string()
@@ -54,7 +54,11 @@ near call $CONTRACT_ID --accountId=$CONTRACT_ID new '{
     "minimum_staking_duration_sec": 2678400,
     "maximum_staking_duration_sec": 31536000,
     "x_booster_multiplier_at_maximum_staking_duration": 40000,
-    "force_closing_enabled": true
+    "force_closing_enabled": true,
+    "close_factor": 5000,
+    "nft_auction_duration_sec": 0,
+    "nft_auction_start_price_ratio": 12000,
+    "nft_auction_end_price_ratio": 5000
   }
 }'
 
@@ -96,6 +100,7 @@ near call $CONTRACT_ID --accountId=$OWNER_ID add_asset '{
     "target_utilization_rate": "1000000000001243680655546223",
     "max_utilization_rate": "1000000000017745300226420217",
     "volatility_ratio": 9500,
+    "liquidation_bonus": 500,
     "extra_decimals": 0,
     "can_deposit": true,
     "can_withdraw": true,
@@ -113,6 +118,7 @@ near call $CONTRACT_ID --accountId=$OWNER_ID add_asset '{
     "target_utilization_rate": "0",
     "max_utilization_rate": "0",
     "volatility_ratio": 6000,
+    "liquidation_bonus": 500,
     "extra_decimals": 0,
     "can_deposit": true,
     "can_withdraw": true,
@@ -52,7 +52,11 @@ near call $CONTRACT_ID --accountId=$CONTRACT_ID new '{
     "minimum_staking_duration_sec": 2678400,
     "maximum_staking_duration_sec": 31536000,
     "x_booster_multiplier_at_maximum_staking_duration": 40000,
-    "force_closing_enabled": true
+    "force_closing_enabled": true,
+    "close_factor": 5000,
+    "nft_auction_duration_sec": 0,
+    "nft_auction_start_price_ratio": 12000,
+    "nft_auction_end_price_ratio": 5000
   }
 }'
 
@@ -161,6 +165,7 @@ near call $CONTRACT_ID --accountId=$MAIN_ACCOUNT add_asset '{
     "target_utilization_rate": "1000000000001243680655546223",
     "max_utilization_rate": "1000000000017745300226420217",
     "volatility_ratio": 9500,
+    "liquidation_bonus": 500,
     "extra_decimals": 0,
     "can_deposit": true,
     "can_withdraw": true,
@@ -177,6 +182,7 @@ near call $CONTRACT_ID --accountId=$MAIN_ACCOUNT add_asset '{
 #     "target_utilization_rate": "1000000000001243680655546223",
 #     "max_utilization_rate": "1000000000017745300226420217",
 #     "volatility_ratio": 9500,
+#     "liquidation_bonus": 500,
 #     "extra_decimals": 0,
 #     "can_deposit": true,
 #     "can_withdraw": true,
@@ -194,6 +200,7 @@ near call $CONTRACT_ID --accountId=$MAIN_ACCOUNT add_asset '{
     "target_utilization_rate": "0",
     "max_utilization_rate": "0",
     "volatility_ratio": 3000,
+    "liquidation_bonus": 500,
     "extra_decimals": 0,
     "can_deposit": true,
     "can_withdraw": true,
@@ -210,6 +217,7 @@ near call $CONTRACT_ID --accountId=$MAIN_ACCOUNT add_asset '{
 #     "target_utilization_rate": "0",
 #     "max_utilization_rate": "0",
 #     "volatility_ratio": 3000,
+#     "liquidation_bonus": 500,
 #     "extra_decimals": 0,
 #     "can_deposit": true,
 #     "can_withdraw": true,
@@ -229,6 +237,7 @@ near call $CONTRACT_ID --accountId=$MAIN_ACCOUNT add_asset '{
     "target_utilization_rate": "1000000000001547125956667610",
     "max_utilization_rate": "1000000000039724853136740579",
     "volatility_ratio": 7500,
+    "liquidation_bonus": 500,
     "extra_decimals": 0,
     "can_deposit": true,
     "can_withdraw": true,
@@ -246,6 +255,7 @@ near call $CONTRACT_ID --accountId=$MAIN_ACCOUNT add_asset '{
 #     "target_utilization_rate": "1000000000001547125956667610",
 #     "max_utilization_rate": "1000000000039724853136740579",
 #     "volatility_ratio": 7500,
+#     "liquidation_bonus": 500,
 #     "extra_decimals": 0,
 #     "can_deposit": true,
 #     "can_withdraw": true,
@@ -265,6 +275,7 @@ near call $CONTRACT_ID --accountId=$MAIN_ACCOUNT add_asset '{
     "target_utilization_rate": "1000000000001243680655546223",
     "max_utilization_rate": "1000000000017745300226420217",
     "volatility_ratio": 9500,
+    "liquidation_bonus": 500,
     "extra_decimals": 12,
     "can_deposit": true,
     "can_withdraw": true,
@@ -281,6 +292,7 @@ near call $CONTRACT_ID --accountId=$MAIN_ACCOUNT add_asset '{
 #     "target_utilization_rate": "1000000000001243680655546223",
 #     "max_utilization_rate": "1000000000017745300226420217",
 #     "volatility_ratio": 9500,
+#     "liquidation_bonus": 500,
 #     "extra_decimals": 12,
 #     "can_deposit": true,
 #     "can_withdraw": true,
@@ -300,6 +312,7 @@ near call $CONTRACT_ID --accountId=$MAIN_ACCOUNT add_asset '{
     "target_utilization_rate": "1000000000001243680655546223",
     "max_utilization_rate": "1000000000017745300226420217",
     "volatility_ratio": 9500,
+    "liquidation_bonus": 500,
     "extra_decimals": 12,
     "can_deposit": true,
     "can_withdraw": true,
@@ -319,6 +332,7 @@ near call $CONTRACT_ID --accountId=$MAIN_ACCOUNT add_asset '{
     "target_utilization_rate": "1000000000003593629036885046",
     "max_utilization_rate": "1000000000039724853136740579",
     "volatility_ratio": 7500,
+    "liquidation_bonus": 500,
     "extra_decimals": 0,
     "can_deposit": true,
     "can_withdraw": true,
@@ -337,6 +351,7 @@ near call $CONTRACT_ID --accountId=$MAIN_ACCOUNT add_asset '{
 #     "target_utilization_rate": "1000000000001547125956667610",
 #     "max_utilization_rate": "1000000000039724853136740579",
 #     "volatility_ratio": 6000,
+#     "liquidation_bonus": 500,
 #     "extra_decimals": 0,
 #     "can_deposit": true,
 #     "can_withdraw": true,
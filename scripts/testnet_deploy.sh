@@ -103,6 +103,7 @@ GAS="200000000000000"
 #    "target_utilization_rate": "1000000000008319516250272147",
 #    "max_utilization_rate": "1000000000039724853136740579",
 #    "volatility_ratio": 2000,
+#    "liquidation_bonus": 500,
 #    "extra_decimals": 0,
 #    "can_deposit": true,
 #    "can_withdraw": true,
@@ -122,6 +123,7 @@ near call $CONTRACT_ID --accountId=$OWNER_ID add_asset '{
     "target_utilization_rate": "1000000000001547125956667610",
     "max_utilization_rate": "1000000000039724853136740579",
     "volatility_ratio": 6000,
+    "liquidation_bonus": 500,
     "extra_decimals": 0,
     "can_deposit": true,
     "can_withdraw": true,
@@ -140,6 +142,7 @@ near call $CONTRACT_ID --accountId=$OWNER_ID add_asset '{
     "target_utilization_rate": "1000000000002440418605283556",
     "max_utilization_rate": "1000000000039724853136740579",
     "volatility_ratio": 9500,
+    "liquidation_bonus": 500,
     "extra_decimals": 0,
     "can_deposit": true,
     "can_withdraw": true,
@@ -159,6 +162,7 @@ near call $CONTRACT_ID --accountId=$OWNER_ID add_asset '{
     "target_utilization_rate": "1000000000002440418605283556",
     "max_utilization_rate": "1000000000039724853136740579",
     "volatility_ratio": 9500,
+    "liquidation_bonus": 500,
     "extra_decimals": 12,
     "can_deposit": true,
     "can_withdraw": true,
@@ -179,6 +183,7 @@ near call $CONTRACT_ID --accountId=$OWNER_ID add_asset '{
     "target_utilization_rate": "1000000000002440418605283556",
     "max_utilization_rate": "1000000000039724853136740579",
     "volatility_ratio": 9500,
+    "liquidation_bonus": 500,
     "extra_decimals": 12,
     "can_deposit": true,
     "can_withdraw": true,
@@ -198,6 +203,7 @@ near call $CONTRACT_ID --accountId=$OWNER_ID add_asset '{
     "target_utilization_rate": "1000000000003593629036885046",
     "max_utilization_rate": "1000000000039724853136740579",
     "volatility_ratio": 6000,
+    "liquidation_bonus": 500,
     "extra_decimals": 0,
     "can_deposit": true,
     "can_withdraw": true,
@@ -0,0 +1,60 @@
+use common::*;
+
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, ext_contract, near_bindgen, AccountId, Gas, PromiseOrValue};
+
+const GAS_FOR_FT_TRANSFER_CALL: Gas = Gas(Gas::ONE_TERA.0 * 100);
+
+const REPAY_MSG: &str = "\"FlashLoanRepay\"";
+
+#[ext_contract(ext_fungible_token)]
+pub trait ExtFungibleToken {
+    fn ft_transfer_call(
+        &mut self,
+        receiver_id: AccountId,
+        amount: U128,
+        memo: Option<String>,
+        msg: String,
+    ) -> U128;
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct OnFlashLoanArgs {
+    pub sender_id: AccountId,
+    pub token_id: AccountId,
+    pub amount: U128,
+    pub fee: U128,
+    pub msg: String,
+}
+
+/// A flash loan receiver for the tests. The `msg` of the loan defines how much is repaid:
+/// - `repay` repays the amount and the fee.
+/// - `repay_amount` repays only the amount without the fee.
+/// - anything else doesn't repay the loan.
+#[near_bindgen]
+#[derive(BorshDeserialize, BorshSerialize, Default)]
+pub struct Contract {}
+
+#[near_bindgen]
+impl Contract {
+    pub fn on_flash_loan(&mut self, args: OnFlashLoanArgs) -> PromiseOrValue<U128> {
+        let repay_amount = match args.msg.as_str() {
+            "repay" => args.amount.0 + args.fee.0,
+            "repay_amount" => args.amount.0,
+            _ => return PromiseOrValue::Value(U128(0)),
+        };
+        ext_fungible_token::ft_transfer_call(
+            env::predecessor_account_id(),
+            repay_amount.into(),
+            None,
+            REPAY_MSG.to_string(),
+            args.token_id,
+            ONE_YOCTO,
+            GAS_FOR_FT_TRANSFER_CALL,
+        )
+        .into()
+    }
+}
@@ -0,0 +1,78 @@
+mod setup;
+
+use crate::setup::*;
+
+use contract::{AssetHealthView, BigDecimal};
+
+fn find_asset_health<'a>(
+    account_health: &'a AccountHealthView,
+    token: &UserAccount,
+) -> &'a AssetHealthView {
+    account_health
+        .assets
+        .iter()
+        .find(|view| view.token_id == token.account_id())
+        .expect("Missing asset")
+}
+
+/// Alice puts 100 NEAR at 10$ as a collateral and borrows 200 DAI.
+#[test]
+fn test_account_health() {
+    let (e, tokens, users) = basic_setup();
+
+    e.supply_to_collateral(&users.alice, &tokens.wnear, d(100, 24))
+        .assert_success();
+    e.borrow(
+        &users.alice,
+        &tokens.ndai,
+        price_data(&tokens, Some(100000), None, None),
+        d(200, 18),
+    )
+    .assert_success();
+
+    let account_health =
+        e.get_account_health(&users.alice, price_data(&tokens, Some(100000), None, None));
+    assert_eq!(account_health.collateral_sum, BigDecimal::from(1000u32));
+    assert_eq!(
+        account_health.adjusted_collateral_sum,
+        BigDecimal::from(600u32)
+    );
+    assert!(account_health.health_factor.unwrap() > BigDecimal::one());
+    assert_eq!(account_health.health_deficit, BigDecimal::zero());
+
+    let wnear_health = find_asset_health(&account_health, &tokens.wnear);
+    assert_eq!(wnear_health.collateral_value, BigDecimal::from(1000u32));
+    let dai_health = find_asset_health(&account_health, &tokens.ndai);
+    assert!(dai_health.borrowed_value >= BigDecimal::from(200u32));
+    // (600$ - 200$ / 95%) * 95% = 370$
+    almost_eq(dai_health.max_borrow_amount, d(370, 18), 9);
+
+    // At 2.5$ per NEAR the account can be liquidated.
+    let account_health =
+        e.get_account_health(&users.alice, price_data(&tokens, Some(25000), None, None));
+    assert!(account_health.health_factor.unwrap() < BigDecimal::one());
+    assert!(account_health.health_deficit > BigDecimal::zero());
+    assert_eq!(
+        find_asset_health(&account_health, &tokens.ndai).max_borrow_amount,
+        0
+    );
+}
+
+/// The account without debt has no health factor.
+#[test]
+fn test_account_health_no_debt() {
+    let (e, tokens, users) = basic_setup();
+
+    e.supply_to_collateral(&users.alice, &tokens.ndai, d(100, 18))
+        .assert_success();
+
+    let account_health = e.get_account_health(&users.alice, price_data(&tokens, None, None, None));
+    assert!(account_health.health_factor.is_none());
+    assert_eq!(account_health.borrowed_sum, BigDecimal::zero());
+    // 100$ * 95% * 95% = 90.25$
+    almost_eq(
+        find_asset_health(&account_health, &tokens.nusdc).max_borrow_amount,
+        d(9025, 16),
+        9,
+    );
+}
@@ -58,7 +58,7 @@ fn test_withdraw() {
     let (e, tokens, users) = basic_setup();
 
     let supply_amount = d(100, 24);
-    e.supply_to_collateral(&users.alice, &tokens.wnear, supply_amount)
+    e.contract_ft_transfer_call(&tokens.wnear, &users.alice, supply_amount, "")
         .assert_success();
 
     let withdraw_amount = d(20, 24);
@@ -85,7 +85,7 @@ fn test_withdraw_fail() {
     let (e, tokens, users) = basic_setup();
 
     let supply_amount = d(100, 24);
-    e.supply_to_collateral(&users.alice, &tokens.wnear, supply_amount)
+    e.contract_ft_transfer_call(&tokens.wnear, &users.alice, supply_amount, "")
         .assert_success();
 
     let withdraw_amount = d(110, 24);
@@ -154,9 +154,11 @@ fn test_borrow() {
 
     // view account alice
     let account = e.get_account(&users.alice);
-    assert_eq!(account.supplied[1].balance, borrow_amount);
-    assert_eq!(account.supplied[1].token_id, tokens.ndai.account_id());
-    assert!(account.supplied[1].apr > BigDecimal::zero());
+    assert_eq!(account.collateral[0].balance, supply_amount);
+    assert_eq!(account.collateral[0].token_id, tokens.wnear.account_id());
+    assert_eq!(account.supplied[0].balance, borrow_amount);
+    assert_eq!(account.supplied[0].token_id, tokens.ndai.account_id());
+    assert!(account.supplied[0].apr > BigDecimal::zero());
     assert_eq!(account.borrowed[0].balance, borrow_amount);
     assert_eq!(account.borrowed[0].token_id, tokens.ndai.account_id());
     assert!(account.borrowed[0].apr > BigDecimal::zero());
@@ -297,6 +299,65 @@ fn test_repay_partial() {
     assert!(account.borrowed[0].apr > BigDecimal::zero());
 }
 
+/// The transferred amount repays the borrowed shares directly, and the amount exceeding the debt
+/// is supplied.
+#[test]
+fn test_transfer_and_repay() {
+    let (e, tokens, users) = basic_setup();
+    let supply_amount = d(10000, 24);
+    e.supply_to_collateral(&users.alice, &tokens.wnear, supply_amount)
+        .assert_success();
+
+    let borrow_amount = d(8000, 18);
+    e.borrow_and_withdraw(
+        &users.alice,
+        &tokens.ndai,
+        price_data(&tokens, Some(100000), None, None),
+        borrow_amount,
+    )
+    .assert_success();
+
+    // Alice repays 3000 DAI, nothing is supplied.
+    let repay_amount = d(3000, 18);
+    e.transfer_and_repay(&users.alice, &tokens.ndai, repay_amount)
+        .assert_success();
+
+    let account = e.get_account(&users.alice);
+    assert_eq!(account.borrowed[0].balance, borrow_amount - repay_amount);
+    assert!(account.supplied.is_empty());
+
+    // Alice closes the debt with 1 day of interest, and the rest is supplied.
+    e.skip_time(24 * 60 * 60);
+    e.transfer_and_repay(&users.alice, &tokens.ndai, d(6000, 18))
+        .assert_success();
+
+    let account = e.get_account(&users.alice);
+    assert!(account.borrowed.is_empty());
+    let supplied_amount = account.supplied[0].balance;
+    assert!(supplied_amount < d(1000, 18));
+    assert!(supplied_amount > d(990, 18));
+
+    let asset = e.get_asset(&tokens.ndai);
+    assert_eq!(asset.borrowed.balance, 0);
+    assert_eq!(asset.borrowed.shares.0, 0);
+}
+
+/// Without the debt in the asset, the `Repay` message deposits the whole amount.
+#[test]
+fn test_transfer_and_repay_without_debt() {
+    let (e, tokens, users) = basic_setup();
+
+    e.transfer_and_repay(&users.alice, &tokens.ndai, d(100, 18))
+        .assert_success();
+
+    let account = e.get_account(&users.alice);
+    assert!(account.borrowed.is_empty());
+    assert_balances(
+        &account.supplied,
+        &[av(tokens.ndai.account_id(), d(100, 18))],
+    );
+}
+
 #[test]
 fn test_interest() {
     let (e, tokens, users) = basic_setup();
@@ -332,3 +393,161 @@ fn test_interest() {
     );
     assert_eq!(account.borrowed[0].token_id, tokens.ndai.account_id());
 }
+
+/// Alice puts 100 NEAR without moving it to collateral. She can't borrow against it.
+#[test]
+fn test_borrow_without_collateral_fails() {
+    let (e, tokens, users) = basic_setup();
+
+    let supply_amount = d(100, 24);
+    e.contract_ft_transfer_call(&tokens.wnear, &users.alice, supply_amount, "")
+        .assert_success();
+
+    let borrow_amount = d(200, 18);
+    let result = e.borrow(
+        &users.alice,
+        &tokens.ndai,
+        price_data(&tokens, Some(100000), None, None),
+        borrow_amount,
+    );
+    assert!(!result.is_ok());
+
+    let asset = e.get_asset(&tokens.ndai);
+    assert_eq!(asset.borrowed.balance, 0);
+
+    let account = e.get_account(&users.alice);
+    assert!(account.collateral.is_empty());
+    assert_eq!(account.supplied[0].balance, supply_amount);
+}
+
+/// Alice puts 100 NEAR without moving it to collateral, then moves it to collateral and borrows 200
+/// DAI in the same batch of actions. The borrow is checked against the new collateral.
+#[test]
+fn test_increase_collateral_and_borrow() {
+    let (e, tokens, users) = basic_setup();
+
+    let supply_amount = d(100, 24);
+    e.contract_ft_transfer_call(&tokens.wnear, &users.alice, supply_amount, "")
+        .assert_success();
+
+    let borrow_amount = d(200, 18);
+    e.oracle_call(
+        &users.alice,
+        price_data(&tokens, Some(100000), None, None),
+        PriceReceiverMsg::Execute {
+            actions: vec![
+                Action::IncreaseCollateral(AssetAmount {
+                    token_id: tokens.wnear.account_id(),
+                    amount: None,
+                    max_amount: None,
+                }),
+                Action::Borrow(asset_amount(&tokens.ndai, borrow_amount)),
+            ],
+        },
+    )
+    .assert_success();
+
+    let account = e.get_account(&users.alice);
+    assert_eq!(account.collateral[0].balance, supply_amount);
+    assert_eq!(account.borrowed[0].balance, borrow_amount);
+    assert_eq!(account.supplied[0].balance, borrow_amount);
+}
+
+/// Alice puts 100 NEAR to collateral, borrows 200 DAI and takes 20 NEAR back from the collateral.
+#[test]
+fn test_decrease_collateral_and_withdraw() {
+    let (e, tokens, users) = basic_setup();
+
+    let supply_amount = d(100, 24);
+    e.supply_to_collateral(&users.alice, &tokens.wnear, supply_amount)
+        .assert_success();
+
+    let borrow_amount = d(200, 18);
+    e.borrow_and_withdraw(
+        &users.alice,
+        &tokens.ndai,
+        price_data(&tokens, Some(100000), None, None),
+        borrow_amount,
+    )
+    .assert_success();
+
+    let withdraw_amount = d(20, 24);
+    e.decrease_collateral_and_withdraw(
+        &users.alice,
+        &tokens.wnear,
+        price_data(&tokens, Some(100000), None, None),
+        withdraw_amount,
+    )
+    .assert_success();
+
+    let asset = e.get_asset(&tokens.wnear);
+    assert_eq!(asset.supplied.balance, supply_amount - withdraw_amount);
+
+    let account = e.get_account(&users.alice);
+    assert_eq!(
+        account.collateral[0].balance,
+        supply_amount - withdraw_amount
+    );
+    assert!(account.supplied.is_empty());
+
+    // Taking the rest of the collateral would leave the account under-collateralized.
+    let result = e.decrease_collateral_and_withdraw(
+        &users.alice,
+        &tokens.wnear,
+        price_data(&tokens, Some(100000), None, None),
+        supply_amount - withdraw_amount,
+    );
+    assert!(!result.is_ok());
+}
+
+/// The booster token can't be used as a collateral.
+#[test]
+fn test_increase_collateral_disabled_asset() {
+    let (e, _tokens, users) = basic_setup();
+
+    let supply_amount = d(100, BOOSTER_TOKEN_DECIMALS);
+    let result = e.supply_to_collateral(&users.alice, &e.booster_token, supply_amount);
+    assert!(!result.is_ok());
+
+    let account = e.get_account(&users.alice);
+    assert!(account.collateral.is_empty());
+}
+
+/// Alice puts 100 NEAR to collateral and borrows 200 DAI. The owner disables NEAR as a collateral.
+/// Alice can't borrow anymore and the NEAR is reported as a disabled collateral.
+#[test]
+fn test_disable_collateral_asset() {
+    let (e, tokens, users) = basic_setup();
+
+    let supply_amount = d(100, 24);
+    e.supply_to_collateral(&users.alice, &tokens.wnear, supply_amount)
+        .assert_success();
+
+    let borrow_amount = d(200, 18);
+    e.borrow_and_withdraw(
+        &users.alice,
+        &tokens.ndai,
+        price_data(&tokens, Some(100000), None, None),
+        borrow_amount,
+    )
+    .assert_success();
+
+    let account = e.get_account(&users.alice);
+    assert!(account.disabled_collateral.is_empty());
+
+    let mut asset_config = e.get_asset(&tokens.wnear).config;
+    asset_config.can_use_as_collateral = false;
+    e.update_asset(tokens.wnear.account_id(), asset_config);
+
+    let account = e.get_account(&users.alice);
+    assert_eq!(account.collateral[0].balance, supply_amount);
+    assert_eq!(account.disabled_collateral, vec![tokens.wnear.account_id()]);
+
+    let result = e.borrow(
+        &users.alice,
+        &tokens.ndai,
+        price_data(&tokens, Some(100000), None, None),
+        d(1, 18),
+    );
+    assert!(!result.is_ok());
+}
@@ -0,0 +1,72 @@
+mod setup;
+
+use crate::setup::*;
+
+use contract::BigDecimal;
+
+fn set_caps(
+    e: &Env,
+    token: &UserAccount,
+    supply_cap: Option<Balance>,
+    borrow_cap: Option<Balance>,
+) {
+    let mut asset_config = e.get_asset(token).config;
+    asset_config.supply_cap = supply_cap.map(|cap| cap.into());
+    asset_config.borrow_cap = borrow_cap.map(|cap| cap.into());
+    e.update_asset(token.account_id(), asset_config);
+}
+
+/// The deposit that exceeds the supply cap is rejected and refunded.
+#[test]
+fn test_supply_cap() {
+    let (e, tokens, users) = basic_setup();
+    set_caps(&e, &tokens.ndai, Some(d(1000, 18)), None);
+
+    e.supply_to_collateral(&users.alice, &tokens.ndai, d(800, 18))
+        .assert_success();
+    let asset = e.get_asset(&tokens.ndai);
+    assert_eq!(asset.supplied.balance, d(800, 18));
+    assert_eq!(
+        asset.supply_cap_usage.unwrap(),
+        BigDecimal::from_ratio(8000)
+    );
+
+    let balance = e.get_balance(&tokens.ndai, &users.bob).0;
+    e.supply_to_collateral(&users.bob, &tokens.ndai, d(300, 18));
+    assert_eq!(e.get_balance(&tokens.ndai, &users.bob).0, balance);
+    assert_eq!(e.get_asset(&tokens.ndai).supplied.balance, d(800, 18));
+
+    e.supply_to_collateral(&users.bob, &tokens.ndai, d(200, 18))
+        .assert_success();
+    assert_eq!(e.get_asset(&tokens.ndai).supplied.balance, d(1000, 18));
+}
+
+/// The borrow that exceeds the borrow cap fails.
+#[test]
+fn test_borrow_cap() {
+    let (e, tokens, users) = basic_setup();
+    set_caps(&e, &tokens.ndai, None, Some(d(100, 18)));
+
+    e.supply_to_collateral(&users.alice, &tokens.wnear, d(100, 24))
+        .assert_success();
+
+    e.borrow_and_withdraw(
+        &users.alice,
+        &tokens.ndai,
+        price_data(&tokens, Some(100000), None, None),
+        d(60, 18),
+    )
+    .assert_success();
+
+    let result = e.borrow(
+        &users.alice,
+        &tokens.ndai,
+        price_data(&tokens, Some(100000), None, None),
+        d(50, 18),
+    );
+    assert!(!result.is_ok());
+
+    let asset = e.get_asset(&tokens.ndai);
+    almost_eq(asset.borrowed.balance, d(60, 18), 18);
+    assert!(asset.borrow_cap_usage.unwrap() >= BigDecimal::from_ratio(6000));
+}
@@ -0,0 +1,139 @@
+mod setup;
+
+use crate::setup::*;
+
+/// Alice puts 100 NEAR as a collateral (NEAR at 10$) and allows Bob to borrow 300 DAI against it.
+/// Bob borrows 200 DAI, the debt is recorded on Alice.
+#[test]
+fn test_borrow_delegated() {
+    let (e, tokens, users) = basic_setup();
+
+    e.supply_to_collateral(&users.alice, &tokens.wnear, d(100, 24))
+        .assert_success();
+    e.approve_borrow_allowance(&users.alice, &users.bob, &tokens.ndai, d(300, 18))
+        .assert_success();
+    assert_eq!(
+        e.get_borrow_allowance(&users.alice, &users.bob, &tokens.ndai),
+        d(300, 18)
+    );
+
+    e.borrow_delegated(
+        &users.bob,
+        &users.alice,
+        &tokens.ndai,
+        price_data(&tokens, Some(100000), None, None),
+        d(200, 18),
+    )
+    .assert_success();
+
+    let event = find_event(&e, "borrow_delegated");
+    assert_eq!(
+        event["data"][0]["delegator_id"].as_str(),
+        Some(users.alice.account_id().as_str())
+    );
+
+    let account = e.get_account(&users.alice);
+    assert_balances(
+        &account.borrowed,
+        &[av(tokens.ndai.account_id(), d(200, 18))],
+    );
+    assert!(account.supplied.is_empty());
+    assert_eq!(account.borrow_allowances.len(), 1);
+    assert_eq!(
+        account.borrow_allowances[0].delegatee_id,
+        users.bob.account_id()
+    );
+    assert_eq!(account.borrow_allowances[0].allowance, d(100, 18));
+
+    let account = e.get_account(&users.bob);
+    assert_balances(
+        &account.supplied,
+        &[av(tokens.ndai.account_id(), d(200, 18))],
+    );
+    assert!(account.borrowed.is_empty());
+
+    // The remaining allowance is 100 DAI.
+    let result = e.borrow_delegated(
+        &users.bob,
+        &users.alice,
+        &tokens.ndai,
+        price_data(&tokens, Some(100000), None, None),
+        d(150, 18),
+    );
+    assert!(!result.is_ok());
+    assert_eq!(
+        e.get_borrow_allowance(&users.alice, &users.bob, &tokens.ndai),
+        d(100, 18)
+    );
+}
+
+/// The allowance doesn't let the delegatee borrow more than the collateral of the delegator allows.
+#[test]
+fn test_borrow_delegated_risk_check() {
+    let (e, tokens, users) = basic_setup();
+
+    e.supply_to_collateral(&users.alice, &tokens.wnear, d(100, 24))
+        .assert_success();
+    e.approve_borrow_allowance(&users.alice, &users.bob, &tokens.ndai, d(1000, 18))
+        .assert_success();
+
+    let result = e.borrow_delegated(
+        &users.bob,
+        &users.alice,
+        &tokens.ndai,
+        price_data(&tokens, Some(100000), None, None),
+        d(700, 18),
+    );
+    assert!(!result.is_ok());
+
+    let account = e.get_account(&users.alice);
+    assert!(account.borrowed.is_empty());
+    assert_eq!(
+        e.get_borrow_allowance(&users.alice, &users.bob, &tokens.ndai),
+        d(1000, 18)
+    );
+}
+
+/// Only the approved delegatee can borrow, and the zero allowance removes the approval.
+#[test]
+fn test_borrow_delegated_not_approved() {
+    let (e, tokens, users) = basic_setup();
+
+    e.supply_to_collateral(&users.alice, &tokens.wnear, d(100, 24))
+        .assert_success();
+    e.approve_borrow_allowance(&users.alice, &users.bob, &tokens.ndai, d(300, 18))
+        .assert_success();
+
+    let result = e.borrow_delegated(
+        &users.charlie,
+        &users.alice,
+        &tokens.ndai,
+        price_data(&tokens, Some(100000), None, None),
+        d(100, 18),
+    );
+    assert!(!result.is_ok());
+
+    // The allowance is per asset.
+    let result = e.borrow_delegated(
+        &users.bob,
+        &users.alice,
+        &tokens.nusdc,
+        price_data(&tokens, Some(100000), None, None),
+        d(100, 6),
+    );
+    assert!(!result.is_ok());
+
+    e.approve_borrow_allowance(&users.alice, &users.bob, &tokens.ndai, 0)
+        .assert_success();
+    let account = e.get_account(&users.alice);
+    assert!(account.borrow_allowances.is_empty());
+
+    let result = e.borrow_delegated(
+        &users.bob,
+        &users.alice,
+        &tokens.ndai,
+        price_data(&tokens, Some(100000), None, None),
+        d(100, 18),
+    );
+    assert!(!result.is_ok());
+}
@@ -0,0 +1,105 @@
+mod setup;
+
+use crate::setup::*;
+
+const STABLECOINS: EModeCategoryId = 1;
+
+fn add_stablecoins_category(e: &Env, tokens: &Tokens) {
+    e.update_e_mode_category(
+        STABLECOINS,
+        EModeCategory {
+            label: "Stablecoins".to_string(),
+            token_ids: vec![
+                tokens.ndai.account_id(),
+                tokens.nusdc.account_id(),
+                tokens.nusdt.account_id(),
+            ],
+            collateral_ratio: 9700,
+            borrow_ratio: 9700,
+        },
+    )
+    .assert_success();
+}
+
+/// Alice puts 1000 DAI as a collateral and borrows 920 USDC, which is only possible in the e-mode.
+#[test]
+fn test_e_mode_borrow() {
+    let (e, tokens, users) = basic_setup();
+    add_stablecoins_category(&e, &tokens);
+
+    e.supply_to_collateral(&users.alice, &tokens.ndai, d(1000, 18))
+        .assert_success();
+
+    let borrow_amount = d(920, 18);
+    let result = e.borrow(
+        &users.alice,
+        &tokens.nusdc,
+        price_data(&tokens, None, None, None),
+        borrow_amount,
+    );
+    assert!(!result.is_ok());
+
+    e.set_e_mode(
+        &users.alice,
+        price_data(&tokens, None, None, None),
+        Some(STABLECOINS),
+    )
+    .assert_success();
+    let account = e.get_account(&users.alice);
+    assert_eq!(account.e_mode_category_id, Some(STABLECOINS));
+
+    e.borrow(
+        &users.alice,
+        &tokens.nusdc,
+        price_data(&tokens, None, None, None),
+        borrow_amount,
+    )
+    .assert_success();
+
+    // Leaving the e-mode brings the account below the health factor.
+    let result = e.set_e_mode(&users.alice, price_data(&tokens, None, None, None), None);
+    assert!(!result.is_ok());
+    let account = e.get_account(&users.alice);
+    assert_eq!(account.e_mode_category_id, Some(STABLECOINS));
+}
+
+/// The account in the e-mode can't use the assets outside of its category.
+#[test]
+fn test_e_mode_other_assets() {
+    let (e, tokens, users) = basic_setup();
+    add_stablecoins_category(&e, &tokens);
+
+    e.supply_to_collateral(&users.alice, &tokens.ndai, d(1000, 18))
+        .assert_success();
+    e.set_e_mode(
+        &users.alice,
+        price_data(&tokens, None, None, None),
+        Some(STABLECOINS),
+    )
+    .assert_success();
+
+    e.supply_to_collateral(&users.alice, &tokens.wnear, d(10, 24));
+    let account = e.get_account(&users.alice);
+    assert_balances(
+        &account.collateral,
+        &[av(tokens.ndai.account_id(), d(1000, 18))],
+    );
+
+    let result = e.borrow(
+        &users.alice,
+        &tokens.wnear,
+        price_data(&tokens, Some(100000), None, None),
+        d(1, 24),
+    );
+    assert!(!result.is_ok());
+
+    // The account with the positions outside of the category can't opt into it.
+    e.supply_to_collateral(&users.bob, &tokens.wnear, d(10, 24))
+        .assert_success();
+    let result = e.set_e_mode(
+        &users.bob,
+        price_data(&tokens, Some(100000), None, None),
+        Some(STABLECOINS),
+    );
+    assert!(!result.is_ok());
+}
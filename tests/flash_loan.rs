@@ -0,0 +1,191 @@
+mod setup;
+
+use crate::setup::*;
+
+use near_sdk_sim::transaction::ExecutionStatus;
+
+fn enable_flash_loan(e: &Env, tokens: &Tokens, flash_loan_fee: u32) {
+    let mut asset_config = e.get_asset(&tokens.wnear).config;
+    asset_config.can_flash_loan = true;
+    asset_config.flash_loan_fee = flash_loan_fee;
+    e.update_asset(tokens.wnear.account_id(), asset_config);
+}
+
+/// Alice puts 1000 DAI as a collateral (NEAR at 10$), so she can cover the flash loan of 50 NEAR.
+fn setup_alice_collateral(e: &Env, tokens: &Tokens, users: &Users) {
+    e.supply_to_collateral(&users.alice, &tokens.ndai, d(1000, 18))
+        .assert_success();
+    e.submit_prices(&users.alice, price_data(tokens, Some(100000), None, None))
+        .assert_success();
+}
+
+/// Alice takes a flash loan of 50 NEAR with 0.09% fee and the receiver repays it with the fee.
+#[test]
+fn test_flash_loan_repaid() {
+    let (e, tokens, users) = basic_setup();
+    enable_flash_loan(&e, &tokens, 9);
+    let receiver = e.deploy_flash_loan_receiver(&tokens);
+    setup_alice_collateral(&e, &tokens, &users);
+
+    let asset = e.get_asset(&tokens.wnear);
+    let amount = d(50, 24);
+    let fee = d(45, 21);
+    let reserved_fee = fee / 4;
+
+    e.flash_loan(&users.alice, &tokens.wnear, amount, &receiver, "repay")
+        .assert_success();
+
+    let value = find_event(&e, "flash_loan_repaid");
+    assert_eq!(
+        value["data"][0]["receiver_id"].as_str().unwrap(),
+        receiver.account_id().as_str()
+    );
+    assert_eq!(value["data"][0]["fee"].as_str().unwrap(), fee.to_string());
+
+    let new_asset = e.get_asset(&tokens.wnear);
+    assert_eq!(new_asset.reserved, asset.reserved + reserved_fee);
+    assert_eq!(
+        new_asset.supplied.balance,
+        asset.supplied.balance + fee - reserved_fee
+    );
+    assert_eq!(
+        e.get_balance(&tokens.wnear, &receiver).0,
+        d(1_000_000, 24) - fee
+    );
+
+    let account = e.get_account(&users.alice);
+    assert!(account.borrowed.is_empty());
+}
+
+/// The receiver doesn't pay the fee, so the fee becomes the debt of Alice. The unpaid fee is owed
+/// to the suppliers and the reserve.
+#[test]
+fn test_flash_loan_defaulted() {
+    let (e, tokens, users) = basic_setup();
+    enable_flash_loan(&e, &tokens, 9);
+    let receiver = e.deploy_flash_loan_receiver(&tokens);
+    setup_alice_collateral(&e, &tokens, &users);
+
+    let asset = e.get_asset(&tokens.wnear);
+    let amount = d(50, 24);
+    let fee = d(45, 21);
+    e.flash_loan(
+        &users.alice,
+        &tokens.wnear,
+        amount,
+        &receiver,
+        "repay_amount",
+    )
+    .assert_success();
+
+    let value = find_event(&e, "flash_loan_defaulted");
+    assert_eq!(
+        value["data"][0]["unpaid_amount"].as_str().unwrap(),
+        fee.to_string()
+    );
+
+    let account = e.get_account(&users.alice);
+    assert_balances(&account.borrowed, &[av(tokens.wnear.account_id(), fee)]);
+    let new_asset = e.get_asset(&tokens.wnear);
+    assert_eq!(new_asset.borrowed.balance, fee);
+    assert_eq!(new_asset.reserved, asset.reserved + fee / 4);
+    assert_eq!(
+        new_asset.supplied.balance,
+        asset.supplied.balance + fee - fee / 4
+    );
+    // The unpaid fee doesn't reduce the available amount, only the unpaid loan amount does.
+    assert_eq!(
+        new_asset.supplied.balance + new_asset.reserved - new_asset.borrowed.balance,
+        asset.supplied.balance + asset.reserved - asset.borrowed.balance
+    );
+}
+
+/// The receiver doesn't repay anything, so the whole loan with the fee becomes the debt of Alice.
+#[test]
+fn test_flash_loan_not_repaid() {
+    let (e, tokens, users) = basic_setup();
+    enable_flash_loan(&e, &tokens, 9);
+    let receiver = e.deploy_flash_loan_receiver(&tokens);
+    setup_alice_collateral(&e, &tokens, &users);
+
+    let asset = e.get_asset(&tokens.wnear);
+    let amount = d(50, 24);
+    let fee = d(45, 21);
+    e.flash_loan(&users.alice, &tokens.wnear, amount, &receiver, "")
+        .assert_success();
+
+    let value = find_event(&e, "flash_loan_defaulted");
+    assert_eq!(
+        value["data"][0]["unpaid_amount"].as_str().unwrap(),
+        (amount + fee).to_string()
+    );
+
+    let account = e.get_account(&users.alice);
+    assert_balances(
+        &account.borrowed,
+        &[av(tokens.wnear.account_id(), amount + fee)],
+    );
+    let new_asset = e.get_asset(&tokens.wnear);
+    assert_eq!(new_asset.reserved, asset.reserved + fee / 4);
+    assert_eq!(
+        new_asset.supplied.balance,
+        asset.supplied.balance + fee - fee / 4
+    );
+}
+
+/// The account without the collateral can't take a flash loan, so the receiver can't drain the
+/// pool by not repaying it. The loan also can't exceed what the collateral covers.
+#[test]
+fn test_flash_loan_without_collateral() {
+    let (e, tokens, users) = basic_setup();
+    enable_flash_loan(&e, &tokens, 9);
+    let receiver = e.deploy_flash_loan_receiver(&tokens);
+    setup_alice_collateral(&e, &tokens, &users);
+
+    let asset = e.get_asset(&tokens.wnear);
+    let res = e.flash_loan(&users.bob, &tokens.wnear, d(50, 24), &receiver, "");
+    let err = match res.status() {
+        ExecutionStatus::Failure(e) => e.to_string(),
+        _ => panic!("Should fail without the collateral"),
+    };
+    assert!(err.contains("The account doesn't have enough collateral for the flash loan"));
+
+    // 200 NEAR at 10$ exceeds 1000 DAI of the collateral.
+    let res = e.flash_loan(&users.alice, &tokens.wnear, d(200, 24), &receiver, "");
+    assert!(!res.is_ok());
+
+    let new_asset = e.get_asset(&tokens.wnear);
+    assert_eq!(new_asset.reserved, asset.reserved);
+    assert_eq!(new_asset.borrowed.balance, 0);
+    assert!(e.get_account(&users.bob).borrowed.is_empty());
+    assert!(e.get_account(&users.alice).borrowed.is_empty());
+}
+
+/// The flash loan can't exceed the available amount and requires the asset to allow it.
+#[test]
+fn test_flash_loan_limits() {
+    let (e, tokens, users) = basic_setup();
+    let receiver = e.deploy_flash_loan_receiver(&tokens);
+
+    let res = e.flash_loan(&users.alice, &tokens.wnear, d(1, 24), &receiver, "repay");
+    let err = match res.status() {
+        ExecutionStatus::Failure(e) => e.to_string(),
+        _ => panic!("Should fail with the flash loans disabled"),
+    };
+    assert!(err.contains("This asset can't be flash loaned"));
+
+    enable_flash_loan(&e, &tokens, 9);
+    let available_amount = e.get_asset(&tokens.wnear).reserved;
+    let res = e.flash_loan(
+        &users.alice,
+        &tokens.wnear,
+        available_amount + 1,
+        &receiver,
+        "repay",
+    );
+    let err = match res.status() {
+        ExecutionStatus::Failure(e) => e.to_string(),
+        _ => panic!("Should fail with exceeded available amount"),
+    };
+    assert!(err.contains("Exceeded available amount"));
+}
@@ -0,0 +1,171 @@
+mod setup;
+
+use crate::setup::*;
+
+/// Makes wNEAR an isolated collateral with the given debt ceiling in USD and allows to borrow DAI
+/// against it.
+fn isolate_wnear(e: &Env, tokens: &Tokens, isolated_debt_ceiling: u64) {
+    let mut asset_config = e.get_asset(&tokens.wnear).config;
+    asset_config.isolated = true;
+    asset_config.isolated_debt_ceiling = isolated_debt_ceiling;
+    e.update_asset(tokens.wnear.account_id(), asset_config);
+
+    let mut asset_config = e.get_asset(&tokens.ndai).config;
+    asset_config.can_borrow_in_isolation = true;
+    e.update_asset(tokens.ndai.account_id(), asset_config);
+}
+
+/// Alice puts 100 NEAR as an isolated collateral and borrows DAI up to the debt ceiling.
+#[test]
+fn test_isolated_debt_ceiling() {
+    let (e, tokens, users) = basic_setup();
+    isolate_wnear(&e, &tokens, 300);
+
+    let supply_amount = d(100, 24);
+    e.supply_to_collateral(&users.alice, &tokens.wnear, supply_amount)
+        .assert_success();
+
+    let borrow_amount = d(200, 18);
+    e.borrow_and_withdraw(
+        &users.alice,
+        &tokens.ndai,
+        price_data(&tokens, Some(100000), None, None),
+        borrow_amount,
+    )
+    .assert_success();
+
+    let asset = e.get_asset(&tokens.wnear);
+    assert_eq!(
+        asset
+            .isolated_debt
+            .get(&tokens.ndai.account_id())
+            .unwrap()
+            .0,
+        borrow_amount
+    );
+
+    // Bob's debt against wNEAR counts towards the same ceiling.
+    e.supply_to_collateral(&users.bob, &tokens.wnear, supply_amount)
+        .assert_success();
+    let result = e.borrow(
+        &users.bob,
+        &tokens.ndai,
+        price_data(&tokens, Some(100000), None, None),
+        d(150, 18),
+    );
+    assert!(!result.is_ok());
+
+    e.borrow(
+        &users.bob,
+        &tokens.ndai,
+        price_data(&tokens, Some(100000), None, None),
+        d(50, 18),
+    )
+    .assert_success();
+
+    // The repaid debt frees the ceiling.
+    e.deposit_and_repay(&users.alice, &tokens.ndai, borrow_amount)
+        .assert_success();
+    let asset = e.get_asset(&tokens.wnear);
+    almost_eq(
+        asset
+            .isolated_debt
+            .get(&tokens.ndai.account_id())
+            .unwrap()
+            .0,
+        d(50, 18),
+        18,
+    );
+
+    e.borrow(
+        &users.bob,
+        &tokens.ndai,
+        price_data(&tokens, Some(100000), None, None),
+        d(150, 18),
+    )
+    .assert_success();
+}
+
+/// The leverage can't borrow past the debt ceiling either. Alice puts 100 NEAR as an isolated
+/// collateral (NEAR at 10$) with the ceiling of 300$.
+#[test]
+fn test_isolated_debt_ceiling_leverage() {
+    let (e, tokens, users) = basic_setup();
+    isolate_wnear(&e, &tokens, 300);
+    let mut asset_config = e.get_asset(&tokens.wnear).config;
+    asset_config.can_borrow_in_isolation = true;
+    e.update_asset(tokens.wnear.account_id(), asset_config);
+
+    e.supply_to_collateral(&users.alice, &tokens.wnear, d(100, 24))
+        .assert_success();
+
+    // 2x leverage borrows 100 NEAR, that is 1000$ of debt.
+    let result = e.leverage(
+        &users.alice,
+        &tokens.wnear,
+        price_data(&tokens, Some(100000), None, None),
+        20000,
+    );
+    assert!(!result.is_ok());
+    assert!(e.get_account(&users.alice).borrowed.is_empty());
+
+    // 1.2x leverage borrows 20 NEAR, that is 200$ of debt.
+    e.leverage(
+        &users.alice,
+        &tokens.wnear,
+        price_data(&tokens, Some(100000), None, None),
+        12000,
+    )
+    .assert_success();
+    let asset = e.get_asset(&tokens.wnear);
+    almost_eq(
+        asset
+            .isolated_debt
+            .get(&tokens.wnear.account_id())
+            .unwrap()
+            .0,
+        d(20, 24),
+        6,
+    );
+}
+
+/// Only the whitelisted assets can be borrowed against the isolated collateral.
+#[test]
+fn test_isolated_borrow_not_whitelisted() {
+    let (e, tokens, users) = basic_setup();
+    isolate_wnear(&e, &tokens, 1000);
+
+    let supply_amount = d(100, 24);
+    e.supply_to_collateral(&users.alice, &tokens.wnear, supply_amount)
+        .assert_success();
+
+    let result = e.borrow(
+        &users.alice,
+        &tokens.nusdc,
+        price_data(&tokens, Some(100000), None, None),
+        d(100, 18),
+    );
+    assert!(!result.is_ok());
+
+    let account = e.get_account(&users.alice);
+    assert!(account.borrowed.is_empty());
+}
+
+/// The isolated collateral can't be combined with other collateral.
+#[test]
+fn test_isolated_collateral_not_combined() {
+    let (e, tokens, users) = basic_setup();
+    isolate_wnear(&e, &tokens, 1000);
+
+    let supply_amount = d(100, 24);
+    e.supply_to_collateral(&users.alice, &tokens.wnear, supply_amount)
+        .assert_success();
+
+    e.supply_to_collateral(&users.alice, &tokens.ndai, d(100, 18));
+
+    let account = e.get_account(&users.alice);
+    assert_balances(
+        &account.collateral,
+        &[av(tokens.wnear.account_id(), supply_amount)],
+    );
+}
@@ -0,0 +1,140 @@
+mod setup;
+
+use crate::setup::*;
+
+/// Alice puts 100 DAI as a collateral and leverages it 5x, borrowing 400 DAI and putting them
+/// as a collateral in the same action.
+#[test]
+fn test_leverage() {
+    let (e, tokens, users) = basic_setup();
+
+    e.supply_to_collateral(&users.alice, &tokens.ndai, d(100, 18))
+        .assert_success();
+
+    e.leverage(
+        &users.alice,
+        &tokens.ndai,
+        price_data(&tokens, None, None, None),
+        50000,
+    )
+    .assert_success();
+
+    let event = find_event(&e, "leverage");
+    almost_eq(
+        event["data"][0]["amount"]
+            .as_str()
+            .unwrap()
+            .parse()
+            .unwrap(),
+        d(400, 18),
+        6,
+    );
+
+    let account = e.get_account(&users.alice);
+    almost_eq(
+        find_asset(&account.collateral, &tokens.ndai.account_id()).balance,
+        d(500, 18),
+        6,
+    );
+    almost_eq(
+        find_asset(&account.borrowed, &tokens.ndai.account_id()).balance,
+        d(400, 18),
+        6,
+    );
+}
+
+/// The leverage can't bring the account below the health factor. 12x leverage of DAI would have
+/// 1100 DAI of debt against 1200 DAI of collateral, that is more than the collateral ratio allows.
+#[test]
+fn test_leverage_too_high() {
+    let (e, tokens, users) = basic_setup();
+
+    e.supply_to_collateral(&users.alice, &tokens.ndai, d(100, 18))
+        .assert_success();
+
+    let result = e.leverage(
+        &users.alice,
+        &tokens.ndai,
+        price_data(&tokens, None, None, None),
+        120000,
+    );
+    assert!(!result.is_ok());
+
+    let account = e.get_account(&users.alice);
+    assert_balances(
+        &account.collateral,
+        &[av(tokens.ndai.account_id(), d(100, 18))],
+    );
+    assert!(account.borrowed.is_empty());
+
+    // The leverage below 1x is not allowed.
+    let result = e.leverage(
+        &users.alice,
+        &tokens.ndai,
+        price_data(&tokens, None, None, None),
+        5000,
+    );
+    assert!(!result.is_ok());
+}
+
+/// Alice leverages 100 DAI 5x, then deleverages it to 2x and finally repays the whole debt with
+/// the collateral.
+#[test]
+fn test_deleverage() {
+    let (e, tokens, users) = basic_setup();
+
+    e.supply_to_collateral(&users.alice, &tokens.ndai, d(100, 18))
+        .assert_success();
+    e.leverage(
+        &users.alice,
+        &tokens.ndai,
+        price_data(&tokens, None, None, None),
+        50000,
+    )
+    .assert_success();
+
+    e.deleverage(
+        &users.alice,
+        &tokens.ndai,
+        price_data(&tokens, None, None, None),
+        20000,
+    )
+    .assert_success();
+
+    let account = e.get_account(&users.alice);
+    almost_eq(
+        find_asset(&account.collateral, &tokens.ndai.account_id()).balance,
+        d(200, 18),
+        6,
+    );
+    almost_eq(
+        find_asset(&account.borrowed, &tokens.ndai.account_id()).balance,
+        d(100, 18),
+        6,
+    );
+
+    // The deleverage can't increase the leverage.
+    let result = e.deleverage(
+        &users.alice,
+        &tokens.ndai,
+        price_data(&tokens, None, None, None),
+        30000,
+    );
+    assert!(!result.is_ok());
+
+    e.deleverage(
+        &users.alice,
+        &tokens.ndai,
+        price_data(&tokens, None, None, None),
+        10000,
+    )
+    .assert_success();
+
+    let account = e.get_account(&users.alice);
+    assert!(account.borrowed.is_empty());
+    almost_eq(
+        find_asset(&account.collateral, &tokens.ndai.account_id()).balance,
+        d(100, 18),
+        6,
+    );
+}
@@ -32,7 +32,7 @@ fn test_liquidation_alice_by_bob() {
 
     let account = e.get_account(&users.alice);
     assert_balances(
-        &account.supplied,
+        &account.collateral,
         &[av(tokens.nusdc.account_id(), supply_amount)],
     );
     assert_balances(
@@ -52,7 +52,7 @@ fn test_liquidation_alice_by_bob() {
     );
     assert!(find_asset(&account.supplied, &tokens.wnear.account_id()).apr > BigDecimal::zero());
 
-    // Assuming 2% discount for 5 NEAR at 12$.
+    // Assuming 5% liquidation bonus of USDC for 4.9 NEAR at 12$.
     let wnear_amount_in = d(49, 23);
     let usdc_amount_out = d(60, 18);
     let res = e.liquidate(
@@ -67,7 +67,7 @@ fn test_liquidation_alice_by_bob() {
 
     let account = e.get_account(&users.alice);
     assert_balances(
-        &account.supplied,
+        &account.collateral,
         &[av(
             tokens.nusdc.account_id(),
             supply_amount - usdc_amount_out,
@@ -132,7 +132,7 @@ fn test_liquidation_decrease_health_factor() {
 
     let account = e.get_account(&users.alice);
     assert_balances(
-        &account.supplied,
+        &account.collateral,
         &[av(tokens.nusdc.account_id(), supply_amount)],
     );
     assert_balances(
@@ -169,7 +169,7 @@ fn test_liquidation_decrease_health_factor() {
     assert!(find_asset(&account.supplied, &tokens.wnear.account_id()).apr > BigDecimal::zero());
     assert!(find_asset(&account.supplied, &tokens.nusdt.account_id()).apr > BigDecimal::zero());
 
-    // Assuming 2% discount for NEAR at 12$. Paying 49 USDT for 50 USDC.
+    // Assuming 5% liquidation bonus of USDC for NEAR at 12$. Paying 49 USDT for 50 USDC.
     let usdt_amount_in = d(49, 18);
     let usdc_amount_out = d(50, 18);
     let res = e.liquidate(
@@ -185,7 +185,7 @@ fn test_liquidation_decrease_health_factor() {
     };
     assert!(err.contains("The health factor of liquidation account can't decrease."));
 
-    // Assuming ~2% discount for 5 NEAR at 12$. 50 USDT -> ~51 USDC, 4.9 NEAR -> 60 USDC.
+    // Assuming 5% liquidation bonus of USDC for 4.9 NEAR at 12$. 50 USDT -> ~51 USDC, 4.9 NEAR -> 60 USDC.
     let wnear_amount_in = d(49, 23);
     let usdt_amount_in = d(50, 18);
     let usdc_amount_out = d(111, 18);
@@ -225,7 +225,7 @@ fn test_liquidation_decrease_health_factor() {
 
     let account = e.get_account(&users.alice);
     assert_balances(
-        &account.supplied,
+        &account.collateral,
         &[av(
             tokens.nusdc.account_id(),
             supply_amount - usdc_amount_out,
@@ -264,6 +264,62 @@ fn test_liquidation_decrease_health_factor() {
     );
 }
 
+/// Bob attempts to repay more than the close factor allows in a single liquidation.
+#[test]
+fn test_liquidation_close_factor() {
+    let (e, tokens, users) = basic_setup();
+
+    let extra_decimals_mult = d(1, 12);
+
+    let supply_amount = d(1000, 18);
+    e.supply_to_collateral(
+        &users.alice,
+        &tokens.nusdc,
+        supply_amount / extra_decimals_mult,
+    )
+    .assert_success();
+
+    let borrow_amount = d(50, 24);
+    e.borrow_and_withdraw(
+        &users.alice,
+        &tokens.wnear,
+        price_data(&tokens, Some(100000), None, None),
+        borrow_amount,
+    )
+    .assert_success();
+
+    let bobs_amount = d(100, 24);
+    e.contract_ft_transfer_call(&tokens.wnear, &users.bob, bobs_amount, "")
+        .assert_success();
+
+    // The borrowed sum is 600$ at 12$, so the close factor of 50% allows to repay up to 300$.
+    // Repaying 26 NEAR (312$) for 312 USDC.
+    let wnear_amount_in = d(26, 24);
+    let usdc_amount_out = d(312, 18);
+    let res = e.liquidate(
+        &users.bob,
+        &users.alice,
+        price_data(&tokens, Some(120000), None, None),
+        vec![asset_amount(&tokens.wnear, wnear_amount_in)],
+        vec![asset_amount(&tokens.nusdc, usdc_amount_out)],
+    );
+    let err = match res.status() {
+        ExecutionStatus::Failure(e) => e.to_string(),
+        _ => panic!("Should fail with close factor error"),
+    };
+    assert!(err.contains("The liquidation amount is too large"));
+
+    let account = e.get_account(&users.alice);
+    assert_balances(
+        &account.collateral,
+        &[av(tokens.nusdc.account_id(), supply_amount)],
+    );
+    assert_balances(
+        &account.borrowed,
+        &[av(tokens.wnear.account_id(), borrow_amount)],
+    );
+}
+
 /// Force closing the account with bad debt.
 #[test]
 fn test_force_close() {
@@ -332,7 +388,7 @@ fn test_force_close() {
     assert_eq!(value["data"][0]["repaid_sum"].as_str().unwrap(), "1250.0");
 
     let account = e.get_account(&users.alice);
-    assert!(account.supplied.is_empty());
+    assert!(account.collateral.is_empty());
     assert!(account.borrowed.is_empty());
 
     let asset = e.get_asset(&tokens.nusdc);
@@ -341,3 +397,80 @@ fn test_force_close() {
     let asset = e.get_asset(&tokens.wnear);
     assert_eq!(asset.reserved, wnear_reserve - borrow_amount);
 }
+
+/// Force closing the account with bad debt, that the reserve can't cover.
+/// The shortfall is written off against the NEAR suppliers.
+#[test]
+fn test_force_close_socialize_bad_debt() {
+    let (e, tokens, users) = basic_setup();
+
+    // Only 20 NEAR are left in the reserve.
+    let wnear_reserve = e.get_asset(&tokens.wnear).reserved;
+    e.withdraw_reserve(
+        &e.owner,
+        &tokens.wnear,
+        &e.owner,
+        Some(wnear_reserve - d(20, 24)),
+    )
+    .assert_success();
+
+    let bobs_amount = d(100, 24);
+    e.contract_ft_transfer_call(&tokens.wnear, &users.bob, bobs_amount, "")
+        .assert_success();
+
+    let extra_decimals_mult = d(1, 12);
+    e.supply_to_collateral(
+        &users.alice,
+        &tokens.nusdc,
+        d(1000, 18) / extra_decimals_mult,
+    )
+    .assert_success();
+
+    let borrow_amount = d(50, 24);
+    e.borrow_and_withdraw(
+        &users.alice,
+        &tokens.wnear,
+        price_data(&tokens, Some(100000), None, None),
+        borrow_amount,
+    )
+    .assert_success();
+
+    let asset = e.get_asset(&tokens.wnear);
+    let reserved = asset.reserved;
+    let supplied_balance = asset.supplied.balance;
+
+    e.force_close(
+        &users.bob,
+        &users.alice,
+        price_data(&tokens, Some(250000), None, None),
+    )
+    .assert_success();
+
+    let account = e.get_account(&users.alice);
+    assert!(account.borrowed.is_empty());
+
+    // The reserve covers 20 NEAR, the rest 30 NEAR is socialized.
+    let asset = e.get_asset(&tokens.wnear);
+    assert_eq!(asset.reserved, 0);
+    let socialized_amount = asset.socialized_bad_debt;
+    almost_eq(socialized_amount, borrow_amount - reserved, 6);
+    assert_eq!(asset.supplied.balance, supplied_balance - socialized_amount);
+
+    let value = find_event(&e, "bad_debt_socialized");
+    assert_eq!(
+        value["data"][0]["account_id"].as_str().unwrap(),
+        users.alice.account_id().as_str()
+    );
+    assert_eq!(
+        value["data"][0]["amount"].as_str().unwrap(),
+        socialized_amount.to_string()
+    );
+
+    // Bob shares the loss.
+    let account = e.get_account(&users.bob);
+    almost_eq(
+        find_asset(&account.supplied, &tokens.wnear.account_id()).balance,
+        bobs_amount - socialized_amount,
+        6,
+    );
+}
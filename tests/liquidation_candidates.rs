@@ -0,0 +1,90 @@
+mod setup;
+
+use crate::setup::*;
+
+use contract::BigDecimal;
+
+/// Alice and Bob put 100 NEAR as a collateral. Alice borrows 500 DAI and Bob borrows 100 DAI.
+fn setup_borrowers(e: &Env, tokens: &Tokens, users: &Users) {
+    for (user, borrow_amount) in [(&users.alice, d(500, 18)), (&users.bob, d(100, 18))] {
+        e.supply_to_collateral(user, &tokens.wnear, d(100, 24))
+            .assert_success();
+        e.borrow(
+            user,
+            &tokens.ndai,
+            price_data(tokens, Some(100000), None, None),
+            borrow_amount,
+        )
+        .assert_success();
+    }
+    // Charlie has no debt.
+    e.contract_ft_transfer_call(&tokens.ndai, &users.charlie, d(1000, 18), "")
+        .assert_success();
+}
+
+/// Only the accounts with debt are indexed, and only the accounts at risk are returned.
+#[test]
+fn test_liquidation_candidates() {
+    let (e, tokens, users) = basic_setup();
+    setup_borrowers(&e, &tokens, &users);
+
+    assert_eq!(e.get_num_borrowers(), 2);
+
+    let candidates =
+        e.get_liquidation_candidates(price_data(&tokens, Some(100000), None, None), None, None);
+    assert!(candidates.is_empty());
+
+    // At 6$ per NEAR Alice can be liquidated, but not Bob.
+    let candidates =
+        e.get_liquidation_candidates(price_data(&tokens, Some(60000), None, None), None, None);
+    assert_eq!(candidates.len(), 1);
+    let candidate = &candidates[0];
+    assert_eq!(candidate.account_id, users.alice.account_id());
+    assert_eq!(candidate.position_id, None);
+    assert!(candidate.health_deficit > BigDecimal::zero());
+    assert!(candidate.health_factor.unwrap() < BigDecimal::one());
+
+    // At 1.5$ per NEAR both can be liquidated, and Alice's health deficit is larger.
+    let candidates =
+        e.get_liquidation_candidates(price_data(&tokens, Some(15000), None, None), None, None);
+    let account_ids: Vec<_> = candidates
+        .iter()
+        .map(|candidate| candidate.account_id.clone())
+        .collect();
+    assert_eq!(
+        account_ids,
+        vec![users.alice.account_id(), users.bob.account_id()]
+    );
+    assert!(candidates[0].health_deficit > candidates[1].health_deficit);
+}
+
+/// Charlie liquidates Alice with the suggested amounts.
+#[test]
+fn test_liquidation_suggestion() {
+    let (e, tokens, users) = basic_setup();
+    setup_borrowers(&e, &tokens, &users);
+
+    let candidates =
+        e.get_liquidation_candidates(price_data(&tokens, Some(60000), None, None), None, None);
+    let suggestion = candidates[0].suggestion.as_ref().unwrap();
+    assert_eq!(suggestion.repay_token_id, tokens.ndai.account_id());
+    assert_eq!(suggestion.seize_token_id, tokens.wnear.account_id());
+    // The close factor limits the repaid debt to 50%.
+    almost_eq(suggestion.repay_amount, d(250, 18), 9);
+
+    e.liquidate(
+        &users.charlie,
+        &users.alice,
+        price_data(&tokens, Some(60000), None, None),
+        vec![asset_amount(&tokens.ndai, suggestion.repay_amount)],
+        vec![asset_amount(&tokens.wnear, suggestion.seize_amount)],
+    )
+    .assert_success();
+
+    let account = e.get_account(&users.charlie);
+    almost_eq(
+        find_asset(&account.supplied, &tokens.wnear.account_id()).balance,
+        suggestion.seize_amount,
+        18,
+    );
+}
@@ -3,7 +3,9 @@ mod setup;
 use crate::setup::*;
 use contract::BigDecimal;
 use near_sdk::json_types::U128;
+use near_sdk::serde_json;
 use near_sdk_sim::transaction::ExecutionStatus;
+use std::collections::HashMap;
 
 /// Alice has 2 NFTs in her account and Alice deposited 1 NFT ($30) to the pool.
 /// Expect results:
@@ -28,9 +30,9 @@ fn test_deposit_nft() {
     assert_eq!(account.nft_supplied[0].nft_token_id, "2".to_string());
 
     // Check NFT asset
-    let asset = e.get_asset(&e.nft_contract);
-    assert_eq!(asset.nft_supplied[0].owner_id, users.alice.account_id());
-    assert_eq!(asset.nft_supplied[0].token_id, "2".to_string());
+    let nft_pools = e.get_nft_pools();
+    assert_eq!(nft_pools[0].owner_id, users.alice.account_id());
+    assert_eq!(nft_pools[0].token_id, "2".to_string());
 }
 
 /// Alice has 1 NFT in her account and Alice tried to deposit 1 NFT she is not the owner.
@@ -59,8 +61,8 @@ fn test_deposit_nft_fail() {
     assert_eq!(account.nft_supplied.len(), 0);
 
     // Check NFT asset
-    let asset = e.get_asset(&e.nft_contract);
-    assert_eq!(asset.nft_supplied.len(), 0);
+    let nft_pools = e.get_nft_pools();
+    assert_eq!(nft_pools.len(), 0);
 }
 
 /// Alice has 2 NFTs in her account and Alice deposited 1 NFT ($30) to the pool.
@@ -88,9 +90,9 @@ fn test_withdraw_nft() {
     assert_eq!(account.nft_supplied[0].nft_token_id, "1".to_string());
 
     // Check NFT asset
-    let asset = e.get_asset(&e.nft_contract);
-    assert_eq!(asset.nft_supplied[0].owner_id, users.alice.account_id());
-    assert_eq!(asset.nft_supplied[0].token_id, "1".to_string());
+    let nft_pools = e.get_nft_pools();
+    assert_eq!(nft_pools[0].owner_id, users.alice.account_id());
+    assert_eq!(nft_pools[0].token_id, "1".to_string());
 
     // Withdraw NFT
     e.withdraw_nft(
@@ -106,8 +108,8 @@ fn test_withdraw_nft() {
     assert_eq!(account.nft_supplied.len(), 0);
 
     // Check NFT asset
-    let asset = e.get_asset(&e.nft_contract);
-    assert_eq!(asset.nft_supplied.len(), 0);
+    let nft_pools = e.get_nft_pools();
+    assert_eq!(nft_pools.len(), 0);
 }
 
 /// Alice has 2 NFTs in her account and Alice deposited 1 NFT ($30) to the pool.
@@ -133,9 +135,9 @@ fn test_withdraw_nft_not_in_pool() {
     assert_eq!(account.nft_supplied[0].nft_token_id, "2".to_string());
 
     // Check NFT asset
-    let asset = e.get_asset(&e.nft_contract);
-    assert_eq!(asset.nft_supplied[0].owner_id, users.alice.account_id());
-    assert_eq!(asset.nft_supplied[0].token_id, "2".to_string());
+    let nft_pools = e.get_nft_pools();
+    assert_eq!(nft_pools[0].owner_id, users.alice.account_id());
+    assert_eq!(nft_pools[0].token_id, "2".to_string());
 
     // Withdraw NFT
     let res = e.withdraw_nft(
@@ -160,9 +162,9 @@ fn test_withdraw_nft_not_in_pool() {
     assert_eq!(account.nft_supplied[0].nft_token_id, "2".to_string());
 
     // Check NFT asset
-    let asset = e.get_asset(&e.nft_contract);
-    assert_eq!(asset.nft_supplied[0].owner_id, users.alice.account_id());
-    assert_eq!(asset.nft_supplied[0].token_id, "2".to_string());
+    let nft_pools = e.get_nft_pools();
+    assert_eq!(nft_pools[0].owner_id, users.alice.account_id());
+    assert_eq!(nft_pools[0].token_id, "2".to_string());
 }
 
 /// Alice has 1 NFT in her account and Alice deposited 1 NFT to the pool
@@ -188,9 +190,9 @@ fn test_withdraw_nft_not_owner() {
     assert_eq!(account.nft_supplied[0].nft_token_id, "1".to_string());
 
     // Check NFT asset
-    let asset = e.get_asset(&e.nft_contract);
-    assert_eq!(asset.nft_supplied[0].owner_id, users.alice.account_id());
-    assert_eq!(asset.nft_supplied[0].token_id, "1".to_string());
+    let nft_pools = e.get_nft_pools();
+    assert_eq!(nft_pools[0].owner_id, users.alice.account_id());
+    assert_eq!(nft_pools[0].token_id, "1".to_string());
 
     e.supply_nft_to_collateral(&users.bob, e.nft_contract.account_id(), "2".to_string())
         .assert_success();
@@ -204,9 +206,9 @@ fn test_withdraw_nft_not_owner() {
     assert_eq!(account.nft_supplied[0].nft_token_id, "2".to_string());
 
     // Check NFT asset
-    let asset = e.get_asset(&e.nft_contract);
-    assert_eq!(asset.nft_supplied[1].owner_id, users.bob.account_id());
-    assert_eq!(asset.nft_supplied[1].token_id, "2".to_string());
+    let nft_pools = e.get_nft_pools();
+    assert_eq!(nft_pools[1].owner_id, users.bob.account_id());
+    assert_eq!(nft_pools[1].token_id, "2".to_string());
 
     // Withdraw NFT
     let res = e.withdraw_nft(
@@ -239,12 +241,12 @@ fn test_withdraw_nft_not_owner() {
     assert_eq!(account.nft_supplied[0].nft_token_id, "2".to_string());
 
     // Check NFT asset
-    let asset = e.get_asset(&e.nft_contract);
-    assert_eq!(asset.nft_supplied[0].owner_id, users.alice.account_id());
-    assert_eq!(asset.nft_supplied[0].token_id, "1".to_string());
+    let nft_pools = e.get_nft_pools();
+    assert_eq!(nft_pools[0].owner_id, users.alice.account_id());
+    assert_eq!(nft_pools[0].token_id, "1".to_string());
 
-    assert_eq!(asset.nft_supplied[1].owner_id, users.bob.account_id());
-    assert_eq!(asset.nft_supplied[1].token_id, "2".to_string());
+    assert_eq!(nft_pools[1].owner_id, users.bob.account_id());
+    assert_eq!(nft_pools[1].token_id, "2".to_string());
 }
 
 /// Alice supply 10 NEAR at $10 and 1 NFT at $30
@@ -375,9 +377,9 @@ fn test_borrow_and_withdraw_nft() {
     );
     assert_eq!(account.nft_supplied[0].nft_token_id, "1".to_string());
 
-    let asset = e.get_asset(&e.nft_contract);
-    assert_eq!(asset.nft_supplied[0].owner_id, users.alice.account_id());
-    assert_eq!(asset.nft_supplied[0].token_id, "1".to_string());
+    let nft_pools = e.get_nft_pools();
+    assert_eq!(nft_pools[0].owner_id, users.alice.account_id());
+    assert_eq!(nft_pools[0].token_id, "1".to_string());
 
     let borrow_amount = d(200, 18);
     e.borrow_and_withdraw_nft(
@@ -439,7 +441,7 @@ fn test_liquidation_nft_alice_by_bob() {
 
     let account = e.get_account(&users.alice);
     assert_balances(
-        &account.supplied,
+        &account.collateral,
         &[av(tokens.nusdc.account_id(), supply_amount)],
     );
     assert_balances(
@@ -517,12 +519,23 @@ fn test_liquidation_nft_decrease_health_factor() {
             target_utilization: 8000,
             target_utilization_rate: U128(1000000000003593629036885046),
             max_utilization_rate: U128(1000000000039724853136740579),
+            rate_breakpoints: vec![],
             volatility_ratio: 9500, // Change to 95%
+            liquidation_bonus: 500,
             extra_decimals: 0,
             can_deposit: true,
             can_withdraw: true,
             can_use_as_collateral: true,
             can_borrow: true,
+            can_borrow_stable: false,
+            stable_rate_rebalance_threshold: 0,
+            can_flash_loan: false,
+            flash_loan_fee: 0,
+            isolated: false,
+            isolated_debt_ceiling: 0,
+            can_borrow_in_isolation: false,
+            supply_cap: None,
+            borrow_cap: None,
         },
     );
 
@@ -534,12 +547,23 @@ fn test_liquidation_nft_decrease_health_factor() {
             target_utilization: 8000,
             target_utilization_rate: U128(0),
             max_utilization_rate: U128(0),
+            rate_breakpoints: vec![],
             volatility_ratio: 9500, // Change to 95%
+            liquidation_bonus: 1000,
             extra_decimals: 0,
             can_deposit: true,
             can_withdraw: true,
             can_use_as_collateral: true,
             can_borrow: false,
+            can_borrow_stable: false,
+            stable_rate_rebalance_threshold: 0,
+            can_flash_loan: false,
+            flash_loan_fee: 0,
+            isolated: false,
+            isolated_debt_ceiling: 0,
+            can_borrow_in_isolation: false,
+            supply_cap: None,
+            borrow_cap: None,
         },
     );
 
@@ -582,7 +606,7 @@ fn test_liquidation_nft_decrease_health_factor() {
     // Check Alice account: Supplied: 1000 USDC
     let account = e.get_account(&users.alice);
     assert_balances(
-        &account.supplied,
+        &account.collateral,
         &[av(tokens.nusdc.account_id(), supply_amount)],
     );
     // Check Alice account: Borrowed: 50 NEAR, 50 USDT
@@ -666,3 +690,428 @@ fn test_liquidation_nft_decrease_health_factor() {
 
     assert_eq!(account.nft_supplied.len(), 0);
 }
+
+/// Alice puts 1 NFT ($30) and borrows 8 DAI. The NFT price goes down to $20.
+/// Bob liquidates the NFT for its discounted value of 18 DAI over the close factor limit.
+/// Expect results:
+/// 1. The debt of Alice is repaid in full
+/// 2. The rest 10 DAI are credited to Alice
+#[test]
+fn test_liquidation_nft_credit() {
+    let (e, tokens, users) = basic_setup();
+
+    e.mint_nft(&users.alice, "1".to_string());
+    e.supply_nft_to_collateral(&users.alice, e.nft_contract.account_id(), "1".to_string())
+        .assert_success();
+
+    let borrow_amount = d(8, 18);
+    e.borrow_and_withdraw(
+        &users.alice,
+        &tokens.ndai,
+        price_data(&tokens, Some(100000), None, Some(300000)),
+        borrow_amount,
+    )
+    .assert_success();
+
+    let bobs_amount = d(100, 18);
+    e.contract_ft_transfer_call(&tokens.ndai, &users.bob, bobs_amount, "")
+        .assert_success();
+
+    // $20 NFT with 10% liquidation bonus.
+    let dai_amount_in = d(18, 18);
+    e.liquidate_nft(
+        &users.bob,
+        &users.alice,
+        price_data(&tokens, Some(100000), None, Some(200000)),
+        vec![asset_amount(&tokens.ndai, dai_amount_in)],
+        vec![nft_asset(e.nft_contract.account_id(), "1".to_string())],
+    )
+    .assert_success();
+
+    let account = e.get_account(&users.alice);
+    assert!(account.borrowed.is_empty());
+    assert_eq!(account.nft_supplied.len(), 0);
+    let credited_amount = find_asset(&account.supplied, &tokens.ndai.account_id()).balance;
+    almost_eq(credited_amount, d(10, 18), 15);
+
+    let account = e.get_account(&users.bob);
+    almost_eq(
+        find_asset(&account.supplied, &tokens.ndai.account_id()).balance,
+        bobs_amount - dai_amount_in,
+        15,
+    );
+    assert_eq!(account.nft_supplied[0].nft_token_id, "1".to_string());
+
+    let value = find_event(&e, "liquidation_credit");
+    assert_eq!(
+        value["data"][0]["account_id"].as_str().unwrap(),
+        users.alice.account_id().as_str()
+    );
+    almost_eq(
+        value["data"][0]["amount"]
+            .as_str()
+            .unwrap()
+            .parse()
+            .unwrap(),
+        credited_amount,
+        15,
+    );
+}
+
+/// Alice puts 1 NFT ($30) and borrows 4 DAI at the variable rate and 4 DAI at the stable rate. The
+/// NFT price goes down to $20. Bob liquidates the NFT for 18 DAI.
+/// Expect results:
+/// 1. Both the variable and the stable debt of Alice are repaid in full
+/// 2. The rest 10 DAI are credited to Alice
+#[test]
+fn test_liquidation_nft_credit_stable_debt() {
+    let (e, tokens, users) = basic_setup();
+    let mut asset_config = e.get_asset(&tokens.ndai).config;
+    asset_config.can_borrow_stable = true;
+    e.update_asset(tokens.ndai.account_id(), asset_config);
+
+    e.mint_nft(&users.alice, "1".to_string());
+    e.supply_nft_to_collateral(&users.alice, e.nft_contract.account_id(), "1".to_string())
+        .assert_success();
+
+    let borrow_amount = d(4, 18);
+    e.borrow_and_withdraw(
+        &users.alice,
+        &tokens.ndai,
+        price_data(&tokens, Some(100000), None, Some(300000)),
+        borrow_amount,
+    )
+    .assert_success();
+    e.borrow_stable(
+        &users.alice,
+        &tokens.ndai,
+        price_data(&tokens, Some(100000), None, Some(300000)),
+        borrow_amount,
+    )
+    .assert_success();
+
+    e.contract_ft_transfer_call(&tokens.ndai, &users.bob, d(100, 18), "")
+        .assert_success();
+
+    let dai_amount_in = d(18, 18);
+    e.liquidate_nft(
+        &users.bob,
+        &users.alice,
+        price_data(&tokens, Some(100000), None, Some(200000)),
+        vec![asset_amount(&tokens.ndai, dai_amount_in)],
+        vec![nft_asset(e.nft_contract.account_id(), "1".to_string())],
+    )
+    .assert_success();
+
+    let account = e.get_account(&users.alice);
+    assert!(account.borrowed.is_empty());
+    assert!(account.stable_borrowed.is_empty());
+    // The stable borrowed 4 DAI stay in the supplied balance of Alice.
+    let supplied_amount = find_asset(&account.supplied, &tokens.ndai.account_id()).balance;
+    almost_eq(supplied_amount, borrow_amount + d(10, 18), 15);
+}
+
+/// Alice supplies 1 NFT at $30 with a rare trait valued at 200% of the floor price.
+/// Expect results:
+/// 1. Borrowing 15 DAI fails without the token multiplier
+/// 2. Borrowing 15 DAI succeeds with the token multiplier
+#[test]
+fn test_nft_valuation_token_multiplier() {
+    let (e, tokens, users) = basic_setup();
+
+    e.mint_nft(&users.alice, "1".to_string());
+    e.supply_nft_to_collateral(&users.alice, e.nft_contract.account_id(), "1".to_string())
+        .assert_success();
+
+    let borrow_amount = d(15, 18);
+    let res = e.borrow(
+        &users.alice,
+        &tokens.ndai,
+        price_data(&tokens, Some(100000), None, Some(300000)),
+        borrow_amount,
+    );
+    let err = match res.status() {
+        ExecutionStatus::Failure(e) => e.to_string(),
+        _ => panic!("Should fail with health error"),
+    };
+    assert!(err.contains("compute_max_discount"));
+
+    let mut token_multipliers = HashMap::new();
+    token_multipliers.insert("1".to_string(), 20000);
+    e.update_nft_valuation(NftValuation {
+        token_multipliers,
+        max_ltv: 10000,
+    });
+
+    e.borrow(
+        &users.alice,
+        &tokens.ndai,
+        price_data(&tokens, Some(100000), None, Some(300000)),
+        borrow_amount,
+    )
+    .assert_success();
+
+    let account = e.get_account(&users.alice);
+    assert_eq!(account.borrowed[0].balance, borrow_amount);
+    assert_eq!(account.borrowed[0].token_id, tokens.ndai.account_id());
+}
+
+/// Alice supplies 1 NFT at $30. The max LTV of the collection is 20%.
+/// Expect results:
+/// 1. Borrowing 8 DAI fails, because only $6 of the NFT value can back borrowing
+#[test]
+fn test_nft_valuation_max_ltv() {
+    let (e, tokens, users) = basic_setup();
+
+    e.update_nft_valuation(NftValuation {
+        token_multipliers: HashMap::new(),
+        max_ltv: 2000,
+    });
+
+    e.mint_nft(&users.alice, "1".to_string());
+    e.supply_nft_to_collateral(&users.alice, e.nft_contract.account_id(), "1".to_string())
+        .assert_success();
+
+    let borrow_amount = d(8, 18);
+    let res = e.borrow(
+        &users.alice,
+        &tokens.ndai,
+        price_data(&tokens, Some(100000), None, Some(300000)),
+        borrow_amount,
+    );
+    let err = match res.status() {
+        ExecutionStatus::Failure(e) => e.to_string(),
+        _ => panic!("Should fail with health error"),
+    };
+    assert!(err.contains("compute_max_discount"));
+
+    let account = e.get_account(&users.alice);
+    assert!(account.borrowed.is_empty());
+}
+
+fn enable_nft_auctions(e: &Env) {
+    let mut config = e.get_config();
+    config.nft_auction_duration_sec = 3600;
+    config.nft_auction_start_price_ratio = 12000;
+    config.nft_auction_end_price_ratio = 5000;
+    e.update_config(config);
+}
+
+/// Alice puts 1 NFT ($30) and borrows 8 DAI. The NFT price drops to $25. REKT
+/// Bob starts the auction of the NFT and buys it once the price declines to the end price.
+/// Expect results:
+/// 1. The auction starts at about 120% of the NFT value
+/// 2. Bob pays 50% of the NFT value, 12.5 DAI
+/// 3. The debt of Alice is fully repaid and the surplus is credited to her supplied DAI
+/// 4. Bob owns the NFT
+#[test]
+fn test_nft_auction_buy() {
+    let (e, tokens, users) = basic_setup();
+    enable_nft_auctions(&e);
+
+    e.mint_nft(&users.alice, "1".to_string());
+    e.supply_nft_to_collateral(&users.alice, e.nft_contract.account_id(), "1".to_string())
+        .assert_success();
+    // Collateral sum = $30 nft * 30% = $9
+
+    let borrow_amount = d(8, 18);
+    e.borrow(
+        &users.alice,
+        &tokens.ndai,
+        price_data(&tokens, Some(100000), None, Some(300000)),
+        borrow_amount,
+    )
+    .assert_success();
+    // Borrow sum = $8 / 95% = $8.42
+
+    let bobs_amount = d(100, 18);
+    e.contract_ft_transfer_call(&tokens.ndai, &users.bob, bobs_amount, "")
+        .assert_success();
+
+    // Collateral sum = $25 nft * 30% = $7.5
+    let res = e.liquidate_nft(
+        &users.bob,
+        &users.alice,
+        price_data(&tokens, Some(100000), None, Some(250000)),
+        vec![asset_amount(&tokens.ndai, d(1, 18))],
+        vec![nft_asset(e.nft_contract.account_id(), "1".to_string())],
+    );
+    let err = match res.status() {
+        ExecutionStatus::Failure(e) => e.to_string(),
+        _ => panic!("Should fail"),
+    };
+    assert!(err.contains("The NFT collateral is liquidated through auctions"));
+
+    e.start_nft_auction(
+        &users.bob,
+        &users.alice,
+        price_data(&tokens, Some(100000), None, Some(250000)),
+        nft_asset(e.nft_contract.account_id(), "1".to_string()),
+    )
+    .assert_success();
+
+    let auction = e.get_nft_auction("1".to_string()).unwrap();
+    assert_eq!(auction.auction.owner_id, users.alice.account_id());
+    assert!(auction.price_ratio <= 12000 && auction.price_ratio > 11900);
+
+    e.skip_time(3600);
+    assert_eq!(
+        e.get_nft_auction("1".to_string()).unwrap().price_ratio,
+        5000
+    );
+
+    let payment_amount = d(125, 17);
+    e.buy_nft_auction(
+        &users.bob,
+        price_data(&tokens, Some(100000), None, Some(250000)),
+        nft_asset(e.nft_contract.account_id(), "1".to_string()),
+        &tokens.ndai,
+        Some(payment_amount),
+    )
+    .assert_success();
+
+    let event = get_logs(&e.near.borrow_runtime())
+        .iter()
+        .filter(|log| log.starts_with(EVENT_JSON))
+        .map(|log| serde_json::from_str::<serde_json::Value>(&log[EVENT_JSON.len()..]).unwrap())
+        .find(|value| value["event"].as_str() == Some("nft_auction_bought"))
+        .expect("Event not found");
+    let data = &event["data"][0];
+    assert_eq!(data["amount"].as_str().unwrap(), payment_amount.to_string());
+    let repaid_amount: u128 = data["repaid_amount"].as_str().unwrap().parse().unwrap();
+    let surplus_amount: u128 = data["surplus_amount"].as_str().unwrap().parse().unwrap();
+    assert!(repaid_amount >= borrow_amount);
+    assert_eq!(repaid_amount + surplus_amount, payment_amount);
+
+    let account = e.get_account(&users.alice);
+    assert!(account.borrowed.is_empty());
+    assert!(account.nft_supplied.is_empty());
+    let surplus_balance = find_asset(&account.supplied, &tokens.ndai.account_id()).balance;
+    assert!(surplus_balance <= surplus_amount && surplus_balance + 1 >= surplus_amount);
+
+    let account = e.get_account(&users.bob);
+    assert_eq!(account.nft_supplied[0].nft_token_id, "1".to_string());
+    let nft_pools = e.get_nft_pools();
+    assert_eq!(nft_pools[0].owner_id, users.bob.account_id());
+    assert!(e.get_nft_auction("1".to_string()).is_none());
+}
+
+/// Alice puts 1 NFT ($30) and borrows 8 DAI at a stable rate. The NFT price drops to $25.
+/// Bob buys the auctioned NFT, and the payment repays the stable debt of Alice.
+#[test]
+fn test_nft_auction_buy_stable_debt() {
+    let (e, tokens, users) = basic_setup();
+    enable_nft_auctions(&e);
+    let mut asset_config = e.get_asset(&tokens.ndai).config;
+    asset_config.can_borrow_stable = true;
+    e.update_asset(tokens.ndai.account_id(), asset_config);
+
+    e.mint_nft(&users.alice, "1".to_string());
+    e.supply_nft_to_collateral(&users.alice, e.nft_contract.account_id(), "1".to_string())
+        .assert_success();
+
+    let borrow_amount = d(8, 18);
+    e.borrow_stable(
+        &users.alice,
+        &tokens.ndai,
+        price_data(&tokens, Some(100000), None, Some(300000)),
+        borrow_amount,
+    )
+    .assert_success();
+
+    e.contract_ft_transfer_call(&tokens.ndai, &users.bob, d(100, 18), "")
+        .assert_success();
+    e.start_nft_auction(
+        &users.bob,
+        &users.alice,
+        price_data(&tokens, Some(100000), None, Some(250000)),
+        nft_asset(e.nft_contract.account_id(), "1".to_string()),
+    )
+    .assert_success();
+    e.skip_time(3600);
+
+    let payment_amount = d(125, 17);
+    e.buy_nft_auction(
+        &users.bob,
+        price_data(&tokens, Some(100000), None, Some(250000)),
+        nft_asset(e.nft_contract.account_id(), "1".to_string()),
+        &tokens.ndai,
+        Some(payment_amount),
+    )
+    .assert_success();
+
+    let account = e.get_account(&users.alice);
+    assert!(account.stable_borrowed.is_empty());
+    assert!(account.nft_supplied.is_empty());
+    let surplus_balance = find_asset(&account.supplied, &tokens.ndai.account_id()).balance;
+    assert!(surplus_balance > 0 && surplus_balance < payment_amount - borrow_amount);
+
+    let account = e.get_account(&users.bob);
+    assert_eq!(account.nft_supplied[0].nft_token_id, "1".to_string());
+}
+
+/// Alice puts 1 NFT ($30) and borrows 8 DAI. The NFT price drops to $25. REKT
+/// Bob starts the auction of the NFT, then the NFT price recovers.
+/// Expect results:
+/// 1. Alice can't withdraw the auctioned NFT
+/// 2. The auction can't be canceled while Alice is at risk
+/// 3. Bob cancels the auction once Alice is no longer at risk
+#[test]
+fn test_nft_auction_cancel() {
+    let (e, tokens, users) = basic_setup();
+    enable_nft_auctions(&e);
+
+    e.mint_nft(&users.alice, "1".to_string());
+    e.supply_nft_to_collateral(&users.alice, e.nft_contract.account_id(), "1".to_string())
+        .assert_success();
+
+    e.borrow(
+        &users.alice,
+        &tokens.ndai,
+        price_data(&tokens, Some(100000), None, Some(300000)),
+        d(8, 18),
+    )
+    .assert_success();
+
+    e.start_nft_auction(
+        &users.bob,
+        &users.alice,
+        price_data(&tokens, Some(100000), None, Some(250000)),
+        nft_asset(e.nft_contract.account_id(), "1".to_string()),
+    )
+    .assert_success();
+
+    let res = e.withdraw_nft(
+        &users.alice,
+        price_data(&tokens, Some(100000), None, Some(250000)),
+        e.nft_contract.account_id(),
+        "1".to_string(),
+    );
+    let err = match res.status() {
+        ExecutionStatus::Failure(e) => e.to_string(),
+        _ => panic!("Should fail"),
+    };
+    assert!(err.contains("The NFT is being auctioned"));
+
+    let res = e.cancel_nft_auction(
+        &users.bob,
+        price_data(&tokens, Some(100000), None, Some(250000)),
+        nft_asset(e.nft_contract.account_id(), "1".to_string()),
+    );
+    let err = match res.status() {
+        ExecutionStatus::Failure(e) => e.to_string(),
+        _ => panic!("Should fail"),
+    };
+    assert!(err.contains("The NFT owner is still at risk"));
+
+    e.cancel_nft_auction(
+        &users.bob,
+        price_data(&tokens, Some(100000), None, Some(300000)),
+        nft_asset(e.nft_contract.account_id(), "1".to_string()),
+    )
+    .assert_success();
+    assert!(e.get_nft_auction("1".to_string()).is_none());
+
+    let account = e.get_account(&users.alice);
+    assert_eq!(account.nft_supplied.len(), 1);
+}
@@ -42,9 +42,11 @@ fn test_nft_farm_supplied() {
     assert_eq!(asset.supplied.balance, 0);
 
     // Check if the NFT asset has been deposited.
+    let nft_pools = e.get_nft_pools();
+    assert_eq!(nft_pools[0].owner_id, users.alice.account_id());
+    assert_eq!(nft_pools[0].token_id, "2".to_string());
+
     let asset = e.get_asset(&e.nft_contract);
-    assert_eq!(asset.nft_supplied[0].owner_id, users.alice.account_id());
-    assert_eq!(asset.nft_supplied[0].token_id, "2".to_string());
 
     let booster_reward = asset.farms[0]
         .rewards
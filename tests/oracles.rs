@@ -0,0 +1,96 @@
+mod setup;
+
+use crate::setup::*;
+
+/// Adds the extra oracle with the given max price deviation.
+fn add_extra_oracle(e: &Env, max_price_deviation: u32) -> UserAccount {
+    let oracle = e.deploy_extra_oracle();
+    let mut config = e.get_config();
+    config.extra_oracle_account_ids = vec![oracle.account_id()];
+    config.max_price_deviation = max_price_deviation;
+    e.update_config(config);
+    oracle
+}
+
+fn no_actions() -> PriceReceiverMsg {
+    PriceReceiverMsg::Execute { actions: vec![] }
+}
+
+/// The prices of both oracles are aggregated into the median.
+#[test]
+fn test_oracle_median_price() {
+    let (e, tokens, users) = basic_setup();
+    let oracle = add_extra_oracle(&e, 1000);
+
+    e.extra_oracle_call(
+        &oracle,
+        &users.alice,
+        price_data(&tokens, Some(105000), None, None),
+        no_actions(),
+    )
+    .assert_success();
+    let price = e.get_price(&tokens.wnear).unwrap();
+    assert_eq!(price.multiplier, 105000);
+
+    e.oracle_call(
+        &users.alice,
+        price_data(&tokens, Some(95000), None, None),
+        no_actions(),
+    )
+    .assert_success();
+    let price = e.get_price(&tokens.wnear).unwrap();
+    assert_eq!(price.multiplier, 100000);
+    assert_eq!(price.decimals, 28);
+}
+
+/// The oracle call fails if the prices deviate from the median more than allowed.
+#[test]
+fn test_oracle_price_deviation() {
+    let (e, tokens, users) = basic_setup();
+    let oracle = add_extra_oracle(&e, 1000);
+
+    e.extra_oracle_call(
+        &oracle,
+        &users.alice,
+        price_data(&tokens, Some(150000), None, None),
+        no_actions(),
+    )
+    .assert_success();
+
+    let result = e.oracle_call(
+        &users.alice,
+        price_data(&tokens, Some(100000), None, None),
+        no_actions(),
+    );
+    assert!(!result.is_ok());
+}
+
+/// The stale price data of the main oracle is ignored and the prices of the extra oracle are used.
+#[test]
+fn test_oracle_stale_fallback() {
+    let (e, tokens, users) = basic_setup();
+    let oracle = add_extra_oracle(&e, 1000);
+
+    e.supply_to_collateral(&users.alice, &tokens.wnear, d(100, 24))
+        .assert_success();
+
+    e.extra_oracle_call(
+        &oracle,
+        &users.bob,
+        price_data(&tokens, Some(100000), None, None),
+        no_actions(),
+    )
+    .assert_success();
+
+    e.skip_time(10);
+
+    // 100 NEAR at $5 would only allow to borrow less than 300 DAI.
+    let mut stale_price_data = price_data(&tokens, Some(50000), None, None);
+    stale_price_data.timestamp -= to_nano(20);
+    e.borrow(&users.alice, &tokens.ndai, stale_price_data, d(300, 18))
+        .assert_success();
+
+    let oracle_prices = e.get_oracle_prices(&tokens.wnear);
+    assert_eq!(oracle_prices.len(), 1);
+    assert!(oracle_prices.contains_key(&oracle.account_id()));
+}
@@ -0,0 +1,128 @@
+mod setup;
+
+use crate::setup::*;
+use near_sdk::serde_json;
+use near_sdk_sim::transaction::ExecutionStatus;
+use near_sdk_sim::{ExecutionResult, UserAccount};
+
+fn failure(res: ExecutionResult) -> String {
+    match res.status() {
+        ExecutionStatus::Failure(e) => e.to_string(),
+        _ => panic!("Should fail"),
+    }
+}
+
+fn set_guardian(e: &Env, guardian: &UserAccount) {
+    let mut config = e.get_config();
+    config.guardian_id = Some(guardian.account_id());
+    e.update_config(config);
+}
+
+/// The guardian pauses borrowing. Only the owner can unpause it.
+#[test]
+fn test_pause_borrow_by_guardian() {
+    let (e, tokens, users) = basic_setup();
+    set_guardian(&e, &users.charlie);
+
+    e.pause(&users.charlie, Some(PausableOperation::Borrow))
+        .assert_success();
+
+    let logs = get_logs(&e.near.borrow_runtime());
+    let event = &logs[0];
+    assert!(event.starts_with(EVENT_JSON));
+
+    let value: serde_json::Value =
+        serde_json::from_str(&event[EVENT_JSON.len()..]).expect("Failed to parse the event");
+    assert_eq!(value["event"].as_str().unwrap(), "pause");
+    assert_eq!(
+        value["data"][0]["account_id"].as_str().unwrap(),
+        users.charlie.account_id().as_str()
+    );
+    assert_eq!(value["data"][0]["operation"].as_str().unwrap(), "Borrow");
+
+    let pause_status = e.get_pause_status();
+    assert!(!pause_status.paused);
+    assert_eq!(
+        pause_status.paused_operations,
+        vec![PausableOperation::Borrow]
+    );
+
+    let supply_amount = d(100, 18);
+    e.supply_to_collateral(&users.alice, &tokens.ndai, supply_amount)
+        .assert_success();
+
+    let borrow_amount = d(50, 18);
+    let res = e.borrow(
+        &users.alice,
+        &tokens.nusdc,
+        price_data(&tokens, None, None, None),
+        borrow_amount,
+    );
+    assert!(failure(res).contains("This operation is paused"));
+
+    let res = e.unpause(&users.charlie, Some(PausableOperation::Borrow));
+    assert!(failure(res).contains("Not an owner"));
+
+    e.unpause(&e.owner, Some(PausableOperation::Borrow))
+        .assert_success();
+    assert!(e.get_pause_status().paused_operations.is_empty());
+
+    e.borrow(
+        &users.alice,
+        &tokens.nusdc,
+        price_data(&tokens, None, None, None),
+        borrow_amount,
+    )
+    .assert_success();
+
+    let account = e.get_account(&users.alice);
+    assert_balances(
+        &account.borrowed,
+        &[av(tokens.nusdc.account_id(), borrow_amount)],
+    );
+}
+
+/// The guardian pauses the whole contract. Deposits are refunded and farms can't be claimed.
+#[test]
+fn test_pause_contract_by_guardian() {
+    let (e, tokens, users) = basic_setup();
+    set_guardian(&e, &users.charlie);
+
+    e.pause(&users.charlie, None).assert_success();
+    assert!(e.get_pause_status().paused);
+
+    let amount = d(100, 24);
+    let balance = e.get_balance(&tokens.wnear, &users.alice);
+    e.contract_ft_transfer_call(&tokens.wnear, &users.alice, amount, "")
+        .assert_success();
+    // The deposit is refunded
+    assert_eq!(e.get_balance(&tokens.wnear, &users.alice), balance);
+    assert!(e.get_account(&users.alice).supplied.is_empty());
+
+    let res = e.account_farm_claim_all(&users.alice);
+    assert!(failure(res).contains("The contract is paused"));
+
+    e.unpause(&e.owner, None).assert_success();
+    assert!(!e.get_pause_status().paused);
+
+    e.contract_ft_transfer_call(&tokens.wnear, &users.alice, amount, "")
+        .assert_success();
+    let account = e.get_account(&users.alice);
+    assert_balances(&account.supplied, &[av(tokens.wnear.account_id(), amount)]);
+}
+
+#[test]
+fn test_pause_not_guardian() {
+    let (e, _tokens, users) = basic_setup();
+    set_guardian(&e, &users.charlie);
+
+    let res = e.pause(&users.alice, None);
+    assert!(failure(res).contains("Not a guardian or an owner"));
+
+    e.pause(&e.owner, Some(PausableOperation::Deposit))
+        .assert_success();
+    assert_eq!(
+        e.get_pause_status().paused_operations,
+        vec![PausableOperation::Deposit]
+    );
+}
@@ -0,0 +1,397 @@
+mod setup;
+
+use crate::setup::*;
+use near_sdk_sim::transaction::ExecutionStatus;
+
+/// Alice opens a position, moves 1000 USDC into it and puts them as a collateral of the position.
+fn setup_alice_position(e: &Env, tokens: &Tokens, users: &Users) -> PositionId {
+    let position_id: PositionId = e.open_position(&users.alice).unwrap_json();
+    e.contract_ft_transfer_call(&tokens.nusdc, &users.alice, d(1000, 6), "")
+        .assert_success();
+    e.transfer_to_position(
+        &users.alice,
+        &tokens.nusdc,
+        None,
+        Some(position_id),
+        d(1000, 18),
+    )
+    .assert_success();
+    e.execute_position(
+        &users.alice,
+        position_id,
+        vec![Action::IncreaseCollateral(AssetAmount {
+            token_id: tokens.nusdc.account_id(),
+            amount: None,
+            max_amount: None,
+        })],
+    )
+    .assert_success();
+    position_id
+}
+
+/// The position has its own collateral, and the supplied balance is moved between the main
+/// account and the position.
+#[test]
+fn test_open_position() {
+    let (e, tokens, users) = basic_setup();
+
+    let position_id = setup_alice_position(&e, &tokens, &users);
+    assert_eq!(position_id, 1);
+    let event = find_event(&e, "transfer_to_position");
+    assert_eq!(event["data"][0]["to_position_id"].as_u64(), Some(1));
+
+    let account = e.get_account(&users.alice);
+    assert!(account.supplied.is_empty());
+    assert!(account.collateral.is_empty());
+    assert_eq!(account.position_ids, vec![1]);
+
+    let position = e.get_position(&users.alice, position_id).unwrap();
+    assert_eq!(position.position_id, Some(1));
+    assert_balances(
+        &position.collateral,
+        &[av(tokens.nusdc.account_id(), d(1000, 18))],
+    );
+
+    // The next position gets the next ID.
+    let position_id: PositionId = e.open_position(&users.alice).unwrap_json();
+    assert_eq!(position_id, 2);
+    assert!(e.get_position(&users.bob, 1).is_none());
+}
+
+/// The risk of the position is checked separately from the main account. Alice can't borrow 1000
+/// DAI in the position with 1000 USDC of collateral, even with 100 NEAR of collateral in the main
+/// account.
+#[test]
+fn test_position_risk_check() {
+    let (e, tokens, users) = basic_setup();
+
+    e.supply_to_collateral(&users.alice, &tokens.wnear, d(100, 24))
+        .assert_success();
+    let position_id = setup_alice_position(&e, &tokens, &users);
+    e.contract_ft_transfer_call(&tokens.ndai, &users.bob, d(2000, 18), "")
+        .assert_success();
+
+    let result = e.borrow_in_position(
+        &users.alice,
+        position_id,
+        &tokens.ndai,
+        price_data(&tokens, Some(100000), None, None),
+        d(1000, 18),
+    );
+    assert!(!result.is_ok());
+
+    e.borrow_in_position(
+        &users.alice,
+        position_id,
+        &tokens.ndai,
+        price_data(&tokens, Some(100000), None, None),
+        d(500, 18),
+    )
+    .assert_success();
+
+    let position = e.get_position(&users.alice, position_id).unwrap();
+    assert_balances(
+        &position.borrowed,
+        &[av(tokens.ndai.account_id(), d(500, 18))],
+    );
+    let account = e.get_account(&users.alice);
+    assert!(account.borrowed.is_empty());
+
+    let view = e.get_account_positions(&users.alice);
+    assert_eq!(view.positions.len(), 2);
+    assert_balances(
+        &view.collateral,
+        &[
+            av(tokens.wnear.account_id(), d(100, 24)),
+            av(tokens.nusdc.account_id(), d(1000, 18)),
+        ],
+    );
+    assert_balances(&view.borrowed, &[av(tokens.ndai.account_id(), d(500, 18))]);
+}
+
+/// Alice borrows 50 NEAR at 10$ in the position with 1000 USDC of collateral. NEAR goes up to
+/// 12$ and Bob liquidates the position. The main account of Alice is not affected.
+#[test]
+fn test_liquidate_position() {
+    let (e, tokens, users) = basic_setup();
+
+    e.supply_to_collateral(&users.alice, &tokens.ndai, d(1000, 18))
+        .assert_success();
+    let position_id = setup_alice_position(&e, &tokens, &users);
+    e.contract_ft_transfer_call(&tokens.wnear, &users.bob, d(200, 24), "")
+        .assert_success();
+
+    let borrow_amount = d(50, 24);
+    e.borrow_in_position(
+        &users.alice,
+        position_id,
+        &tokens.wnear,
+        price_data(&tokens, Some(100000), None, None),
+        borrow_amount,
+    )
+    .assert_success();
+
+    // The main account is healthy.
+    let result = e.liquidate(
+        &users.bob,
+        &users.alice,
+        price_data(&tokens, Some(120000), None, None),
+        vec![asset_amount(&tokens.wnear, d(49, 23))],
+        vec![asset_amount(&tokens.ndai, d(60, 18))],
+    );
+    assert!(!result.is_ok());
+
+    // Assuming 5% liquidation bonus of USDC for 4.9 NEAR at 12$.
+    let wnear_amount_in = d(49, 23);
+    let usdc_amount_out = d(60, 18);
+    e.liquidate_position(
+        &users.bob,
+        &users.alice,
+        position_id,
+        price_data(&tokens, Some(120000), None, None),
+        vec![asset_amount(&tokens.wnear, wnear_amount_in)],
+        vec![asset_amount(&tokens.nusdc, usdc_amount_out)],
+    )
+    .assert_success();
+
+    let position = e.get_position(&users.alice, position_id).unwrap();
+    assert_balances(
+        &position.collateral,
+        &[av(tokens.nusdc.account_id(), d(1000, 18) - usdc_amount_out)],
+    );
+    assert_balances(
+        &position.borrowed,
+        &[av(
+            tokens.wnear.account_id(),
+            borrow_amount - wnear_amount_in,
+        )],
+    );
+
+    let account = e.get_account(&users.alice);
+    assert_balances(
+        &account.collateral,
+        &[av(tokens.ndai.account_id(), d(1000, 18))],
+    );
+
+    let account = e.get_account(&users.bob);
+    assert_balances(
+        &account.supplied,
+        &[
+            av(tokens.wnear.account_id(), d(200, 24) - wnear_amount_in),
+            av(tokens.nusdc.account_id(), usdc_amount_out),
+        ],
+    );
+}
+
+/// The position can only be closed when it's empty.
+#[test]
+fn test_close_position() {
+    let (e, tokens, users) = basic_setup();
+
+    let position_id = setup_alice_position(&e, &tokens, &users);
+
+    let result = e.close_position(&users.alice, position_id);
+    assert!(!result.is_ok());
+
+    e.execute_position(
+        &users.alice,
+        position_id,
+        vec![Action::DecreaseCollateral(AssetAmount {
+            token_id: tokens.nusdc.account_id(),
+            amount: None,
+            max_amount: None,
+        })],
+    )
+    .assert_success();
+    e.transfer_to_position(
+        &users.alice,
+        &tokens.nusdc,
+        Some(position_id),
+        None,
+        d(1000, 18),
+    )
+    .assert_success();
+
+    e.close_position(&users.alice, position_id).assert_success();
+    find_event(&e, "close_position");
+
+    assert!(e.get_position(&users.alice, position_id).is_none());
+    let account = e.get_account(&users.alice);
+    assert!(account.position_ids.is_empty());
+    assert_balances(
+        &account.supplied,
+        &[av(tokens.nusdc.account_id(), d(1000, 18))],
+    );
+
+    // The position of another account can't be closed.
+    let result = e.close_position(&users.bob, position_id);
+    assert!(!result.is_ok());
+}
+
+/// The position with debt is indexed separately from the main account, and it's returned as a
+/// liquidation candidate with its `position_id`.
+#[test]
+fn test_position_liquidation_candidate() {
+    let (e, tokens, users) = basic_setup();
+
+    e.supply_to_collateral(&users.alice, &tokens.ndai, d(1000, 18))
+        .assert_success();
+    let position_id = setup_alice_position(&e, &tokens, &users);
+    e.contract_ft_transfer_call(&tokens.wnear, &users.bob, d(200, 24), "")
+        .assert_success();
+    assert_eq!(e.get_num_borrowers(), 0);
+
+    e.borrow_in_position(
+        &users.alice,
+        position_id,
+        &tokens.wnear,
+        price_data(&tokens, Some(100000), None, None),
+        d(50, 24),
+    )
+    .assert_success();
+    assert_eq!(e.get_num_borrowers(), 1);
+
+    let candidates =
+        e.get_liquidation_candidates(price_data(&tokens, Some(100000), None, None), None, None);
+    assert!(candidates.is_empty());
+
+    let candidates =
+        e.get_liquidation_candidates(price_data(&tokens, Some(120000), None, None), None, None);
+    assert_eq!(candidates.len(), 1);
+    assert_eq!(candidates[0].account_id, users.alice.account_id());
+    assert_eq!(candidates[0].position_id, Some(position_id));
+}
+
+/// Alice deposits an NFT to the position. The NFT is kept by the position, so it can only be
+/// withdrawn from the position.
+#[test]
+fn test_position_nft() {
+    let (e, tokens, users) = basic_setup();
+
+    let position_id: PositionId = e.open_position(&users.alice).unwrap_json();
+    e.mint_nft(&users.alice, "1".to_string());
+    e.supply_nft_to_position(
+        &users.alice,
+        position_id,
+        e.nft_contract.account_id(),
+        "1".to_string(),
+    )
+    .assert_success();
+
+    let account = e.get_account(&users.alice);
+    assert!(account.nft_supplied.is_empty());
+    let position = e.get_position(&users.alice, position_id).unwrap();
+    assert_eq!(position.nft_supplied[0].nft_token_id, "1".to_string());
+    let nft_pools = e.get_nft_pools();
+    assert_eq!(nft_pools[0].owner_id, users.alice.account_id());
+    assert_eq!(nft_pools[0].position_id, Some(position_id));
+
+    let res = e.withdraw_nft(
+        &users.alice,
+        price_data(&tokens, None, None, Some(100000)),
+        e.nft_contract.account_id(),
+        "1".to_string(),
+    );
+    let err = match res.status() {
+        ExecutionStatus::Failure(e) => e.to_string(),
+        _ => panic!("Should fail with error"),
+    };
+    assert!(err.contains("The NFT is kept by another position of the account"));
+
+    e.execute_position(
+        &users.alice,
+        position_id,
+        vec![Action::WithdrawNFT(nft_asset(
+            e.nft_contract.account_id(),
+            "1".to_string(),
+        ))],
+    )
+    .assert_success();
+
+    let position = e.get_position(&users.alice, position_id).unwrap();
+    assert!(position.nft_supplied.is_empty());
+    assert!(e.get_nft_pools().is_empty());
+}
+
+/// Alice puts 1000 USDC and an NFT ($30) to the position and borrows 50 NEAR at 10$ in the
+/// position. NEAR goes up to 12$ and Bob liquidates the NFT of the position.
+#[test]
+fn test_liquidate_position_nft() {
+    let (e, tokens, users) = basic_setup();
+
+    let position_id = setup_alice_position(&e, &tokens, &users);
+    e.mint_nft(&users.alice, "1".to_string());
+    e.supply_nft_to_position(
+        &users.alice,
+        position_id,
+        e.nft_contract.account_id(),
+        "1".to_string(),
+    )
+    .assert_success();
+    e.contract_ft_transfer_call(&tokens.wnear, &users.bob, d(100, 24), "")
+        .assert_success();
+
+    let borrow_amount = d(50, 24);
+    e.borrow_in_position(
+        &users.alice,
+        position_id,
+        &tokens.wnear,
+        price_data(&tokens, Some(100000), None, Some(300000)),
+        borrow_amount,
+    )
+    .assert_success();
+
+    // The NFT isn't kept by the main account.
+    let wnear_amount_in = d(245, 22);
+    let result = e.liquidate_nft(
+        &users.bob,
+        &users.alice,
+        price_data(&tokens, Some(120000), None, Some(300000)),
+        vec![asset_amount(&tokens.wnear, wnear_amount_in)],
+        vec![nft_asset(e.nft_contract.account_id(), "1".to_string())],
+    );
+    assert!(!result.is_ok());
+
+    e.liquidate_nft_position(
+        &users.bob,
+        &users.alice,
+        position_id,
+        price_data(&tokens, Some(120000), None, Some(300000)),
+        vec![asset_amount(&tokens.wnear, wnear_amount_in)],
+        vec![nft_asset(e.nft_contract.account_id(), "1".to_string())],
+    )
+    .assert_success();
+
+    let position = e.get_position(&users.alice, position_id).unwrap();
+    assert!(position.nft_supplied.is_empty());
+    assert_balances(
+        &position.borrowed,
+        &[av(
+            tokens.wnear.account_id(),
+            borrow_amount - wnear_amount_in,
+        )],
+    );
+
+    let account = e.get_account(&users.bob);
+    assert_eq!(account.nft_supplied[0].nft_token_id, "1".to_string());
+    let nft_pools = e.get_nft_pools();
+    assert_eq!(nft_pools[0].owner_id, users.bob.account_id());
+    assert_eq!(nft_pools[0].position_id, None);
+}
+
+/// The IDs of the closed positions are not reused.
+#[test]
+fn test_position_ids_not_reused() {
+    let (e, _tokens, users) = basic_setup();
+
+    let position_id: PositionId = e.open_position(&users.alice).unwrap_json();
+    assert_eq!(position_id, 1);
+    let position_id: PositionId = e.open_position(&users.alice).unwrap_json();
+    assert_eq!(position_id, 2);
+    e.close_position(&users.alice, position_id).assert_success();
+
+    let position_id: PositionId = e.open_position(&users.alice).unwrap_json();
+    assert_eq!(position_id, 3);
+    let account = e.get_account(&users.alice);
+    assert_eq!(account.position_ids, vec![1, 3]);
+}
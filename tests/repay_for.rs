@@ -0,0 +1,257 @@
+mod setup;
+
+use crate::setup::*;
+
+fn setup_alice_debt(e: &Env, tokens: &Tokens, users: &Users) {
+    e.supply_to_collateral(&users.alice, &tokens.wnear, d(100, 24))
+        .assert_success();
+    e.borrow(
+        &users.alice,
+        &tokens.ndai,
+        price_data(tokens, Some(100000), None, None),
+        d(200, 18),
+    )
+    .assert_success();
+}
+
+/// Alice puts 100 NEAR and borrows 200 DAI (NEAR at 10$). Bob repays 50 DAI of her debt from his
+/// supplied balance.
+#[test]
+fn test_repay_for() {
+    let (e, tokens, users) = basic_setup();
+    setup_alice_debt(&e, &tokens, &users);
+
+    e.contract_ft_transfer_call(&tokens.ndai, &users.bob, d(100, 18), "")
+        .assert_success();
+
+    e.repay_for(
+        &users.bob,
+        &users.alice,
+        &tokens.ndai,
+        price_data(&tokens, Some(100000), None, None),
+        d(50, 18),
+    )
+    .assert_success();
+
+    let event = find_event(&e, "repay_for");
+    assert_eq!(
+        event["data"][0]["account_id"].as_str(),
+        Some(users.bob.account_id().as_str())
+    );
+    assert_eq!(
+        event["data"][0]["repay_account_id"].as_str(),
+        Some(users.alice.account_id().as_str())
+    );
+
+    let account = e.get_account(&users.alice);
+    almost_eq(
+        find_asset(&account.borrowed, &tokens.ndai.account_id()).balance,
+        d(150, 18),
+        6,
+    );
+    assert_balances(
+        &account.collateral,
+        &[av(tokens.wnear.account_id(), d(100, 24))],
+    );
+
+    let account = e.get_account(&users.bob);
+    almost_eq(
+        find_asset(&account.supplied, &tokens.ndai.account_id()).balance,
+        d(50, 18),
+        6,
+    );
+}
+
+/// Bob transfers 250 DAI to repay the whole debt of Alice. The amount exceeding the debt stays in
+/// his supplied balance.
+#[test]
+fn test_deposit_and_repay_for() {
+    let (e, tokens, users) = basic_setup();
+    setup_alice_debt(&e, &tokens, &users);
+
+    e.deposit_and_repay_for(&users.bob, &users.alice, &tokens.ndai, d(250, 18))
+        .assert_success();
+
+    let event = find_event(&e, "repay_for");
+    assert_eq!(
+        event["data"][0]["repay_account_id"].as_str(),
+        Some(users.alice.account_id().as_str())
+    );
+
+    let account = e.get_account(&users.alice);
+    assert!(account.borrowed.is_empty());
+    assert_balances(
+        &account.collateral,
+        &[av(tokens.wnear.account_id(), d(100, 24))],
+    );
+
+    let account = e.get_account(&users.bob);
+    almost_eq(
+        find_asset(&account.supplied, &tokens.ndai.account_id()).balance,
+        d(50, 18),
+        6,
+    );
+}
+
+/// The debt can only be repaid for another registered account with the debt in this asset.
+#[test]
+fn test_repay_for_invalid_account() {
+    let (e, tokens, users) = basic_setup();
+    setup_alice_debt(&e, &tokens, &users);
+
+    e.contract_ft_transfer_call(&tokens.ndai, &users.bob, d(100, 18), "")
+        .assert_success();
+
+    let result = e.repay_for(
+        &users.alice,
+        &users.alice,
+        &tokens.ndai,
+        price_data(&tokens, Some(100000), None, None),
+        d(50, 18),
+    );
+    assert!(!result.is_ok());
+
+    let result = e.repay_for(
+        &users.bob,
+        &users.dude,
+        &tokens.ndai,
+        price_data(&tokens, Some(100000), None, None),
+        d(50, 18),
+    );
+    assert!(!result.is_ok());
+
+    let result = e.repay_for(
+        &users.alice,
+        &users.bob,
+        &tokens.ndai,
+        price_data(&tokens, Some(100000), None, None),
+        d(50, 18),
+    );
+    assert!(!result.is_ok());
+
+    let account = e.get_account(&users.bob);
+    almost_eq(
+        find_asset(&account.supplied, &tokens.ndai.account_id()).balance,
+        d(100, 18),
+        6,
+    );
+}
+
+/// Alice borrows 200 DAI at a stable rate. Bob repays 50 DAI of her stable debt.
+#[test]
+fn test_repay_for_stable() {
+    let (e, tokens, users) = basic_setup();
+    let mut asset_config = e.get_asset(&tokens.ndai).config;
+    asset_config.can_borrow_stable = true;
+    e.update_asset(tokens.ndai.account_id(), asset_config);
+
+    e.supply_to_collateral(&users.alice, &tokens.wnear, d(100, 24))
+        .assert_success();
+    e.borrow_stable(
+        &users.alice,
+        &tokens.ndai,
+        price_data(&tokens, Some(100000), None, None),
+        d(200, 18),
+    )
+    .assert_success();
+
+    e.contract_ft_transfer_call(&tokens.ndai, &users.bob, d(100, 18), "")
+        .assert_success();
+    e.repay_for(
+        &users.bob,
+        &users.alice,
+        &tokens.ndai,
+        price_data(&tokens, Some(100000), None, None),
+        d(50, 18),
+    )
+    .assert_success();
+
+    let account = e.get_account(&users.alice);
+    almost_eq(account.stable_borrowed[0].balance, d(150, 18), 6);
+    let account = e.get_account(&users.bob);
+    almost_eq(
+        find_asset(&account.supplied, &tokens.ndai.account_id()).balance,
+        d(50, 18),
+        6,
+    );
+}
+
+/// Alice borrows 200 DAI in her position. Bob repays 50 DAI of the position debt, the main
+/// account of Alice is not affected.
+#[test]
+fn test_repay_for_position() {
+    let (e, tokens, users) = basic_setup();
+    setup_alice_debt(&e, &tokens, &users);
+
+    let position_id: PositionId = e.open_position(&users.alice).unwrap_json();
+    e.contract_ft_transfer_call(&tokens.wnear, &users.alice, d(100, 24), "")
+        .assert_success();
+    e.transfer_to_position(
+        &users.alice,
+        &tokens.wnear,
+        None,
+        Some(position_id),
+        d(100, 24),
+    )
+    .assert_success();
+    e.execute_position(
+        &users.alice,
+        position_id,
+        vec![Action::IncreaseCollateral(asset_amount(
+            &tokens.wnear,
+            d(100, 24),
+        ))],
+    )
+    .assert_success();
+    e.borrow_in_position(
+        &users.alice,
+        position_id,
+        &tokens.ndai,
+        price_data(&tokens, Some(100000), None, None),
+        d(200, 18),
+    )
+    .assert_success();
+
+    e.contract_ft_transfer_call(&tokens.ndai, &users.bob, d(100, 18), "")
+        .assert_success();
+    e.repay_for_position(
+        &users.bob,
+        &users.alice,
+        position_id,
+        &tokens.ndai,
+        d(50, 18),
+    )
+    .assert_success();
+
+    let event = find_event(&e, "repay_for");
+    assert_eq!(event["data"][0]["position_id"].as_u64(), Some(1));
+
+    let position = e.get_position(&users.alice, position_id).unwrap();
+    almost_eq(
+        find_asset(&position.borrowed, &tokens.ndai.account_id()).balance,
+        d(150, 18),
+        6,
+    );
+    let account = e.get_account(&users.alice);
+    almost_eq(
+        find_asset(&account.borrowed, &tokens.ndai.account_id()).balance,
+        d(200, 18),
+        6,
+    );
+
+    // Alice can repay the debt of her position from the main account.
+    e.repay_for_position(
+        &users.alice,
+        &users.alice,
+        position_id,
+        &tokens.ndai,
+        d(50, 18),
+    )
+    .assert_success();
+    let position = e.get_position(&users.alice, position_id).unwrap();
+    almost_eq(
+        find_asset(&position.borrowed, &tokens.ndai.account_id()).balance,
+        d(100, 18),
+        6,
+    );
+}
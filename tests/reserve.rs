@@ -0,0 +1,100 @@
+mod setup;
+
+use crate::setup::*;
+use near_sdk_sim::transaction::ExecutionStatus;
+
+/// The owner withdraws a part of the wNEAR reserve to the treasury account.
+#[test]
+fn test_withdraw_reserve() {
+    let (e, tokens, users) = basic_setup();
+
+    let reserved = e.get_asset(&tokens.wnear).reserved;
+    let balance = e.get_balance(&tokens.wnear, &users.alice).0;
+
+    let amount = d(100, 24);
+    e.withdraw_reserve(&e.owner, &tokens.wnear, &users.alice, Some(amount))
+        .assert_success();
+
+    let value = find_event(&e, "reserve_withdrawn");
+    assert_eq!(
+        value["data"][0]["account_id"].as_str().unwrap(),
+        users.alice.account_id().as_str()
+    );
+    assert_eq!(
+        value["data"][0]["amount"].as_str().unwrap(),
+        amount.to_string()
+    );
+
+    assert_eq!(e.get_asset(&tokens.wnear).reserved, reserved - amount);
+    assert_eq!(
+        e.get_balance(&tokens.wnear, &users.alice).0,
+        balance + amount
+    );
+}
+
+/// The receiver isn't registered with the token, so the transfer fails and the reserve is restored.
+#[test]
+fn test_withdraw_reserve_transfer_failed() {
+    let (e, tokens, users) = basic_setup();
+
+    let reserved = e.get_asset(&tokens.wnear).reserved;
+
+    let amount = d(100, 24);
+    e.withdraw_reserve(&e.owner, &tokens.wnear, &users.dude, Some(amount));
+
+    let value = find_event(&e, "reserve_withdraw_failed");
+    assert_eq!(
+        value["data"][0]["account_id"].as_str().unwrap(),
+        users.dude.account_id().as_str()
+    );
+
+    assert_eq!(e.get_asset(&tokens.wnear).reserved, reserved);
+}
+
+/// The borrowed part of the reserve can't be withdrawn.
+#[test]
+fn test_withdraw_reserve_limited_by_available_amount() {
+    let (e, tokens, users) = basic_setup();
+
+    let supply_amount = d(100, 24);
+    e.supply_to_collateral(&users.alice, &tokens.wnear, supply_amount)
+        .assert_success();
+
+    let reserved = e.get_asset(&tokens.ndai).reserved;
+    let borrow_amount = d(200, 18);
+    e.borrow_and_withdraw(
+        &users.alice,
+        &tokens.ndai,
+        price_data(&tokens, Some(100000), None, None),
+        borrow_amount,
+    )
+    .assert_success();
+
+    let res = e.withdraw_reserve(&e.owner, &tokens.ndai, &users.alice, Some(reserved));
+    let err = match res.status() {
+        ExecutionStatus::Failure(e) => e.to_string(),
+        _ => panic!("Should fail with not enough available reserved balance"),
+    };
+    assert!(err.contains("Not enough available reserved balance"));
+
+    e.withdraw_reserve(&e.owner, &tokens.ndai, &users.alice, None)
+        .assert_success();
+
+    let asset = e.get_asset(&tokens.ndai);
+    assert_eq!(
+        asset.supplied.balance + asset.reserved,
+        asset.borrowed.balance
+    );
+}
+
+#[test]
+fn test_withdraw_reserve_not_owner() {
+    let (e, tokens, users) = basic_setup();
+
+    let res = e.withdraw_reserve(&users.alice, &tokens.wnear, &users.alice, Some(d(1, 24)));
+    let err = match res.status() {
+        ExecutionStatus::Failure(e) => e.to_string(),
+        _ => panic!("Should fail with not an owner"),
+    };
+    assert!(err.contains("Not an owner"));
+}
@@ -12,11 +12,14 @@ use near_sdk_sim::{
 };
 
 pub use contract::{
-    AccountDetailedView, Action, AssetAmount, AssetConfig, AssetDetailedView, Config,
-    ContractContract as NearlendContract, PriceReceiverMsg, TokenReceiverMsg,
+    AccountDetailedView, AccountHealthView, AccountPositionsView, Action, AssetAmount, AssetConfig,
+    AssetDetailedView, Config, ContractContract as NearlendContract, EModeCategory,
+    EModeCategoryId, LiquidationCandidateView, NftValuation, OraclePrice, PausableOperation,
+    PauseStatus, PositionId, PricePoint, PriceReceiverMsg, TokenReceiverMsg,
 };
-use contract::{AssetFarmView, AssetView, FarmId, NFTAsset};
+use contract::{AssetFarmView, AssetView, FarmId, NFTAsset, NftAuctionView, NftPool};
 use near_sdk_sim::runtime::RuntimeStandalone;
+use std::collections::HashMap;
 use test_oracle::ContractContract as OracleContract;
 
 use near_contract_standards::non_fungible_token::metadata::TokenMetadata;
@@ -27,6 +30,7 @@ near_sdk_sim::lazy_static_include::lazy_static_include_bytes! {
     NEARLEND_0_4_0_WASM_BYTES => "res/nearlend_protocol_0.4.0.wasm",
     NEARLEND_PREVIOUS_WASM_BYTES => "res/nearlend_protocol_0.5.1.wasm",
     TEST_ORACLE_WASM_BYTES => "res/test_oracle.wasm",
+    TEST_FLASH_LOAN_RECEIVER_WASM_BYTES => "res/test_flash_loan_receiver.wasm",
     FUNGIBLE_TOKEN_WASM_BYTES => "res/fungible_token.wasm",
     NON_FUNGIBLE_TOKEN_WASM_BYTES => "res/nft.wasm",
 }
@@ -53,6 +57,8 @@ pub const NEARLEND_ID: &str = "nearlend.near";
 pub const BOOSTER_TOKEN_ID: &str = "ft.nearlend.near";
 pub const OWNER_ID: &str = "owner.near";
 pub const NFT_ID: &str = "nft-nearlend.near";
+pub const FLASH_LOAN_RECEIVER_ID: &str = "flash-loan-receiver.near";
+pub const EXTRA_ORACLE_ID: &str = "extra-oracle.near";
 
 pub const DEFAULT_GAS: Gas = Gas(Gas::ONE_TERA.0 * 100);
 pub const MAX_GAS: Gas = Gas(Gas::ONE_TERA.0 * 300);
@@ -168,7 +174,12 @@ impl Env {
             init_method: new(
                 Config {
                     oracle_account_id: a(ORACLE_ID),
+                    extra_oracle_account_ids: vec![],
+                    max_price_deviation: 200,
+                    price_history_size: 0,
+                    twap_duration_sec: 0,
                     owner_id: owner.account_id(),
+                    guardian_id: None,
                     booster_token_id: a(BOOSTER_TOKEN_ID),
                     booster_decimals: BOOSTER_TOKEN_DECIMALS,
                     max_num_assets: 10,
@@ -178,6 +189,10 @@ impl Env {
                     maximum_staking_duration_sec: 31536000,
                     x_booster_multiplier_at_maximum_staking_duration: 40000,
                     force_closing_enabled: true,
+                    close_factor: 5000,
+                    nft_auction_duration_sec: 0,
+                    nft_auction_start_price_ratio: 12000,
+                    nft_auction_end_price_ratio: 5000,
                 }
             )
         );
@@ -264,6 +279,187 @@ impl Env {
             .submit()
     }
 
+    pub fn get_config(&self) -> Config {
+        self.near
+            .view_method_call(self.contract.contract.get_config())
+            .unwrap_json()
+    }
+
+    pub fn update_config(&self, config: Config) {
+        self.owner
+            .function_call(
+                self.contract.contract.update_config(config),
+                DEFAULT_GAS.0,
+                ONE_YOCTO,
+            )
+            .assert_success()
+    }
+
+    pub fn get_pause_status(&self) -> PauseStatus {
+        self.near
+            .view_method_call(self.contract.contract.get_pause_status())
+            .unwrap_json()
+    }
+
+    pub fn pause(
+        &self,
+        user: &UserAccount,
+        operation: Option<PausableOperation>,
+    ) -> ExecutionResult {
+        user.function_call(
+            self.contract.contract.pause(operation),
+            DEFAULT_GAS.0,
+            ONE_YOCTO,
+        )
+    }
+
+    pub fn unpause(
+        &self,
+        user: &UserAccount,
+        operation: Option<PausableOperation>,
+    ) -> ExecutionResult {
+        user.function_call(
+            self.contract.contract.unpause(operation),
+            DEFAULT_GAS.0,
+            ONE_YOCTO,
+        )
+    }
+
+    pub fn withdraw_reserve(
+        &self,
+        user: &UserAccount,
+        token: &UserAccount,
+        receiver: &UserAccount,
+        amount: Option<Balance>,
+    ) -> ExecutionResult {
+        user.function_call(
+            self.contract.contract.withdraw_reserve(
+                token.account_id(),
+                receiver.account_id(),
+                amount.map(U128),
+            ),
+            MAX_GAS.0,
+            ONE_YOCTO,
+        )
+    }
+
+    pub fn rebalance_stable_rate(
+        &self,
+        user: &UserAccount,
+        account: &UserAccount,
+        token: &UserAccount,
+    ) -> ExecutionResult {
+        user.function_call(
+            self.contract.contract.rebalance_stable_rate(
+                account.account_id(),
+                None,
+                token.account_id(),
+            ),
+            DEFAULT_GAS.0,
+            ONE_YOCTO,
+        )
+    }
+
+    pub fn rebalance_position_stable_rate(
+        &self,
+        user: &UserAccount,
+        account: &UserAccount,
+        position_id: PositionId,
+        token: &UserAccount,
+    ) -> ExecutionResult {
+        user.function_call(
+            self.contract.contract.rebalance_stable_rate(
+                account.account_id(),
+                Some(position_id),
+                token.account_id(),
+            ),
+            DEFAULT_GAS.0,
+            ONE_YOCTO,
+        )
+    }
+
+    /// Deploys the mock flash loan receiver and registers it with the tokens.
+    pub fn deploy_flash_loan_receiver(&self, tokens: &Tokens) -> UserAccount {
+        let receiver = self.near.deploy(
+            &TEST_FLASH_LOAN_RECEIVER_WASM_BYTES,
+            a(FLASH_LOAN_RECEIVER_ID),
+            STORAGE_AMOUNT,
+        );
+        self.mint_tokens(tokens, &receiver);
+        receiver
+    }
+
+    pub fn deploy_extra_oracle(&self) -> UserAccount {
+        self.near
+            .deploy(&TEST_ORACLE_WASM_BYTES, a(EXTRA_ORACLE_ID), STORAGE_AMOUNT)
+    }
+
+    pub fn extra_oracle_call(
+        &self,
+        oracle: &UserAccount,
+        user: &UserAccount,
+        price_data: PriceData,
+        msg: PriceReceiverMsg,
+    ) -> ExecutionResult {
+        user.call(
+            oracle.account_id(),
+            "oracle_call",
+            &json!({
+                "receiver_id": self.contract.user_account.account_id(),
+                "price_data": price_data,
+                "msg": serde_json::to_string(&msg).unwrap(),
+            })
+            .to_string()
+            .into_bytes(),
+            MAX_GAS.0,
+            ONE_YOCTO,
+        )
+    }
+
+    pub fn get_price(&self, token: &UserAccount) -> Option<Price> {
+        self.near
+            .view_method_call(self.contract.contract.get_price(token.account_id()))
+            .unwrap_json()
+    }
+
+    pub fn get_oracle_prices(&self, token: &UserAccount) -> HashMap<AccountId, OraclePrice> {
+        self.near
+            .view_method_call(self.contract.contract.get_oracle_prices(token.account_id()))
+            .unwrap_json()
+    }
+
+    pub fn get_price_history(&self, token: &UserAccount) -> Vec<PricePoint> {
+        self.near
+            .view_method_call(self.contract.contract.get_price_history(token.account_id()))
+            .unwrap_json()
+    }
+
+    pub fn get_twap_price(&self, token: &UserAccount) -> Option<Price> {
+        self.near
+            .view_method_call(self.contract.contract.get_twap_price(token.account_id()))
+            .unwrap_json()
+    }
+
+    pub fn flash_loan(
+        &self,
+        user: &UserAccount,
+        token: &UserAccount,
+        amount: Balance,
+        receiver: &UserAccount,
+        msg: &str,
+    ) -> ExecutionResult {
+        user.function_call(
+            self.contract.contract.flash_loan(
+                token.account_id(),
+                U128(amount),
+                receiver.account_id(),
+                msg.to_string(),
+            ),
+            MAX_GAS.0,
+            ONE_YOCTO,
+        )
+    }
+
     pub fn update_asset(&self, token_id: AccountId, asset_config: AssetConfig) {
         self.owner
             .function_call(
@@ -274,6 +470,32 @@ impl Env {
             .assert_success()
     }
 
+    pub fn update_nft_valuation(&self, nft_valuation: NftValuation) {
+        self.owner
+            .function_call(
+                self.contract
+                    .contract
+                    .update_nft_valuation(self.nft_contract.account_id(), nft_valuation),
+                DEFAULT_GAS.0,
+                ONE_YOCTO,
+            )
+            .assert_success()
+    }
+
+    pub fn update_e_mode_category(
+        &self,
+        category_id: EModeCategoryId,
+        e_mode_category: EModeCategory,
+    ) -> ExecutionResult {
+        self.owner.function_call(
+            self.contract
+                .contract
+                .update_e_mode_category(category_id, e_mode_category),
+            DEFAULT_GAS.0,
+            ONE_YOCTO,
+        )
+    }
+
     pub fn setup_assets(&self, tokens: &Tokens) {
         self.owner
             .function_call(
@@ -284,12 +506,23 @@ impl Env {
                         target_utilization: 8000,
                         target_utilization_rate: U128(1000000000008319516250272147),
                         max_utilization_rate: U128(1000000000039724853136740579),
+                        rate_breakpoints: vec![],
                         volatility_ratio: 2000,
+                        liquidation_bonus: 500,
                         extra_decimals: 0,
                         can_deposit: true,
                         can_withdraw: true,
                         can_use_as_collateral: false,
                         can_borrow: false,
+                        can_borrow_stable: false,
+                        stable_rate_rebalance_threshold: 0,
+                        can_flash_loan: false,
+                        flash_loan_fee: 0,
+                        isolated: false,
+                        isolated_debt_ceiling: 0,
+                        can_borrow_in_isolation: false,
+                        supply_cap: None,
+                        borrow_cap: None,
                     },
                 ),
                 DEFAULT_GAS.0,
@@ -306,12 +539,23 @@ impl Env {
                         target_utilization: 8000,
                         target_utilization_rate: U128(1000000000001547125956667610),
                         max_utilization_rate: U128(1000000000039724853136740579),
+                        rate_breakpoints: vec![],
                         volatility_ratio: 6000,
+                        liquidation_bonus: 500,
                         extra_decimals: 0,
                         can_deposit: true,
                         can_withdraw: true,
                         can_use_as_collateral: true,
                         can_borrow: true,
+                        can_borrow_stable: false,
+                        stable_rate_rebalance_threshold: 0,
+                        can_flash_loan: false,
+                        flash_loan_fee: 0,
+                        isolated: false,
+                        isolated_debt_ceiling: 0,
+                        can_borrow_in_isolation: false,
+                        supply_cap: None,
+                        borrow_cap: None,
                     },
                 ),
                 DEFAULT_GAS.0,
@@ -328,12 +572,23 @@ impl Env {
                         target_utilization: 8000,
                         target_utilization_rate: U128(1000000000002440418605283556),
                         max_utilization_rate: U128(1000000000039724853136740579),
+                        rate_breakpoints: vec![],
                         volatility_ratio: 9500,
+                        liquidation_bonus: 500,
                         extra_decimals: 0,
                         can_deposit: true,
                         can_withdraw: true,
                         can_use_as_collateral: true,
                         can_borrow: true,
+                        can_borrow_stable: false,
+                        stable_rate_rebalance_threshold: 0,
+                        can_flash_loan: false,
+                        flash_loan_fee: 0,
+                        isolated: false,
+                        isolated_debt_ceiling: 0,
+                        can_borrow_in_isolation: false,
+                        supply_cap: None,
+                        borrow_cap: None,
                     },
                 ),
                 DEFAULT_GAS.0,
@@ -350,12 +605,23 @@ impl Env {
                         target_utilization: 8000,
                         target_utilization_rate: U128(1000000000002440418605283556),
                         max_utilization_rate: U128(1000000000039724853136740579),
+                        rate_breakpoints: vec![],
                         volatility_ratio: 9500,
+                        liquidation_bonus: 500,
                         extra_decimals: 12,
                         can_deposit: true,
                         can_withdraw: true,
                         can_use_as_collateral: true,
                         can_borrow: true,
+                        can_borrow_stable: false,
+                        stable_rate_rebalance_threshold: 0,
+                        can_flash_loan: false,
+                        flash_loan_fee: 0,
+                        isolated: false,
+                        isolated_debt_ceiling: 0,
+                        can_borrow_in_isolation: false,
+                        supply_cap: None,
+                        borrow_cap: None,
                     },
                 ),
                 DEFAULT_GAS.0,
@@ -372,12 +638,23 @@ impl Env {
                         target_utilization: 8000,
                         target_utilization_rate: U128(1000000000002440418605283556),
                         max_utilization_rate: U128(1000000000039724853136740579),
+                        rate_breakpoints: vec![],
                         volatility_ratio: 9500,
+                        liquidation_bonus: 500,
                         extra_decimals: 12,
                         can_deposit: true,
                         can_withdraw: true,
                         can_use_as_collateral: true,
                         can_borrow: true,
+                        can_borrow_stable: false,
+                        stable_rate_rebalance_threshold: 0,
+                        can_flash_loan: false,
+                        flash_loan_fee: 0,
+                        isolated: false,
+                        isolated_debt_ceiling: 0,
+                        can_borrow_in_isolation: false,
+                        supply_cap: None,
+                        borrow_cap: None,
                     },
                 ),
                 DEFAULT_GAS.0,
@@ -394,12 +671,23 @@ impl Env {
                         target_utilization: 8000,
                         target_utilization_rate: U128(1000000000003593629036885046),
                         max_utilization_rate: U128(1000000000039724853136740579),
+                        rate_breakpoints: vec![],
                         volatility_ratio: 6000,
+                        liquidation_bonus: 500,
                         extra_decimals: 0,
                         can_deposit: true,
                         can_withdraw: true,
                         can_use_as_collateral: true,
                         can_borrow: true,
+                        can_borrow_stable: false,
+                        stable_rate_rebalance_threshold: 0,
+                        can_flash_loan: false,
+                        flash_loan_fee: 0,
+                        isolated: false,
+                        isolated_debt_ceiling: 0,
+                        can_borrow_in_isolation: false,
+                        supply_cap: None,
+                        borrow_cap: None,
                     },
                 ),
                 DEFAULT_GAS.0,
@@ -416,12 +704,23 @@ impl Env {
                         target_utilization: 8000,
                         target_utilization_rate: U128(0),
                         max_utilization_rate: U128(0),
+                        rate_breakpoints: vec![],
                         volatility_ratio: 3000,
+                        liquidation_bonus: 1000,
                         extra_decimals: 0,
                         can_deposit: true,
                         can_withdraw: true,
                         can_use_as_collateral: true,
                         can_borrow: false,
+                        can_borrow_stable: false,
+                        stable_rate_rebalance_threshold: 0,
+                        can_flash_loan: false,
+                        flash_loan_fee: 0,
+                        isolated: false,
+                        isolated_debt_ceiling: 0,
+                        can_borrow_in_isolation: false,
+                        supply_cap: None,
+                        borrow_cap: None,
                     },
                 ),
                 DEFAULT_GAS.0,
@@ -588,6 +887,26 @@ impl Env {
         asset.unwrap()
     }
 
+    pub fn get_nft_pools(&self) -> Vec<NftPool> {
+        self.near
+            .view_method_call(self.contract.contract.get_nft_assets_paged(
+                self.nft_contract.account_id(),
+                None,
+                None,
+            ))
+            .unwrap_json()
+    }
+
+    pub fn get_nft_auction(&self, nft_token_id: NFTTokenId) -> Option<NftAuctionView> {
+        self.near
+            .view_method_call(
+                self.contract
+                    .contract
+                    .get_nft_auction(self.nft_contract.account_id(), nft_token_id),
+            )
+            .unwrap_json()
+    }
+
     pub fn get_asset_farm(&self, farm_id: FarmId) -> AssetFarmView {
         let asset_farm: Option<serde_json::value::Value> = self
             .near
@@ -608,6 +927,60 @@ impl Env {
         account.unwrap()
     }
 
+    pub fn get_account_health(
+        &self,
+        user: &UserAccount,
+        price_data: PriceData,
+    ) -> AccountHealthView {
+        let account_health: Option<AccountHealthView> = self
+            .near
+            .view_method_call(
+                self.contract
+                    .contract
+                    .get_account_health(user.account_id(), price_data),
+            )
+            .unwrap_json();
+        account_health.unwrap()
+    }
+
+    pub fn get_borrow_allowance(
+        &self,
+        user: &UserAccount,
+        delegatee: &UserAccount,
+        token: &UserAccount,
+    ) -> Balance {
+        let allowance: U128 = self
+            .near
+            .view_method_call(self.contract.contract.get_borrow_allowance(
+                user.account_id(),
+                delegatee.account_id(),
+                token.account_id(),
+            ))
+            .unwrap_json();
+        allowance.0
+    }
+
+    pub fn get_num_borrowers(&self) -> u64 {
+        self.near
+            .view_method_call(self.contract.contract.get_num_borrowers())
+            .unwrap_json()
+    }
+
+    pub fn get_liquidation_candidates(
+        &self,
+        price_data: PriceData,
+        from_index: Option<u64>,
+        limit: Option<u64>,
+    ) -> Vec<LiquidationCandidateView> {
+        self.near
+            .view_method_call(
+                self.contract
+                    .contract
+                    .get_liquidation_candidates(price_data, from_index, limit),
+            )
+            .unwrap_json()
+    }
+
     pub fn storage_balance_of(&self, user: &UserAccount) -> Option<StorageBalance> {
         self.near
             .view_method_call(self.contract.contract.storage_balance_of(user.account_id()))
@@ -630,7 +1003,30 @@ impl Env {
         token: &UserAccount,
         amount: Balance,
     ) -> ExecutionResult {
-        self.contract_ft_transfer_call(token, user, amount, "")
+        let msg = serde_json::to_string(&TokenReceiverMsg::Execute {
+            actions: vec![Action::IncreaseCollateral(AssetAmount {
+                token_id: token.account_id(),
+                amount: None,
+                max_amount: None,
+            })],
+        })
+        .unwrap();
+        self.contract_ft_transfer_call(token, user, amount, &msg)
+    }
+
+    pub fn supply_nft_to_position(
+        &self,
+        user: &UserAccount,
+        position_id: PositionId,
+        nft_contract_id: NFTContractId,
+        nft_token_id: NFTTokenId,
+    ) -> ExecutionResult {
+        self.contract_nft_transfer_call(
+            user,
+            nft_contract_id,
+            nft_token_id,
+            &json!({ "DepositToPosition": { "position_id": position_id } }).to_string(),
+        )
     }
 
     pub fn supply_nft_to_collateral(
@@ -655,6 +1051,31 @@ impl Env {
         self.contract_ft_transfer_call(token, user, amount, &msg)
     }
 
+    pub fn transfer_and_repay(
+        &self,
+        user: &UserAccount,
+        token: &UserAccount,
+        amount: Balance,
+    ) -> ExecutionResult {
+        let msg = serde_json::to_string(&TokenReceiverMsg::Repay).unwrap();
+        self.contract_ft_transfer_call(token, user, amount, &msg)
+    }
+
+    pub fn deposit_and_repay_for(
+        &self,
+        user: &UserAccount,
+        repay_user: &UserAccount,
+        token: &UserAccount,
+        amount: Balance,
+    ) -> ExecutionResult {
+        let msg = serde_json::to_string(&TokenReceiverMsg::RepayFor {
+            account_id: repay_user.account_id(),
+            position_id: None,
+        })
+        .unwrap();
+        self.contract_ft_transfer_call(token, user, amount, &msg)
+    }
+
     pub fn oracle_call(
         &self,
         user: &UserAccount,
@@ -672,6 +1093,15 @@ impl Env {
         )
     }
 
+    /// Submits the prices through the oracle without executing any actions.
+    pub fn submit_prices(&self, user: &UserAccount, price_data: PriceData) -> ExecutionResult {
+        self.oracle_call(
+            user,
+            price_data,
+            PriceReceiverMsg::Execute { actions: vec![] },
+        )
+    }
+
     pub fn borrow(
         &self,
         user: &UserAccount,
@@ -688,6 +1118,168 @@ impl Env {
         )
     }
 
+    pub fn repay_for(
+        &self,
+        user: &UserAccount,
+        repay_user: &UserAccount,
+        token: &UserAccount,
+        price_data: PriceData,
+        amount: Balance,
+    ) -> ExecutionResult {
+        self.oracle_call(
+            user,
+            price_data,
+            PriceReceiverMsg::Execute {
+                actions: vec![Action::RepayFor {
+                    account_id: repay_user.account_id(),
+                    position_id: None,
+                    asset_amount: asset_amount(token, amount),
+                }],
+            },
+        )
+    }
+
+    pub fn repay_for_position(
+        &self,
+        user: &UserAccount,
+        repay_user: &UserAccount,
+        position_id: PositionId,
+        token: &UserAccount,
+        amount: Balance,
+    ) -> ExecutionResult {
+        user.function_call(
+            self.contract.contract.execute(vec![Action::RepayFor {
+                account_id: repay_user.account_id(),
+                position_id: Some(position_id),
+                asset_amount: asset_amount(token, amount),
+            }]),
+            MAX_GAS.0,
+            ONE_YOCTO,
+        )
+    }
+
+    pub fn approve_borrow_allowance(
+        &self,
+        user: &UserAccount,
+        delegatee: &UserAccount,
+        token: &UserAccount,
+        allowance: Balance,
+    ) -> ExecutionResult {
+        user.function_call(
+            self.contract
+                .contract
+                .execute(vec![Action::ApproveBorrowAllowance {
+                    delegatee_id: delegatee.account_id(),
+                    token_id: token.account_id(),
+                    allowance: allowance.into(),
+                }]),
+            DEFAULT_GAS.0,
+            ONE_YOCTO,
+        )
+    }
+
+    pub fn borrow_delegated(
+        &self,
+        user: &UserAccount,
+        delegator: &UserAccount,
+        token: &UserAccount,
+        price_data: PriceData,
+        amount: Balance,
+    ) -> ExecutionResult {
+        self.oracle_call(
+            user,
+            price_data,
+            PriceReceiverMsg::Execute {
+                actions: vec![Action::BorrowDelegated {
+                    delegator_id: delegator.account_id(),
+                    asset_amount: asset_amount(token, amount),
+                }],
+            },
+        )
+    }
+
+    pub fn borrow_stable(
+        &self,
+        user: &UserAccount,
+        token: &UserAccount,
+        price_data: PriceData,
+        amount: Balance,
+    ) -> ExecutionResult {
+        self.oracle_call(
+            user,
+            price_data,
+            PriceReceiverMsg::Execute {
+                actions: vec![Action::BorrowStable(asset_amount(token, amount))],
+            },
+        )
+    }
+
+    pub fn deposit_and_repay_stable(
+        &self,
+        user: &UserAccount,
+        token: &UserAccount,
+        amount: Balance,
+    ) -> ExecutionResult {
+        let msg = serde_json::to_string(&TokenReceiverMsg::Execute {
+            actions: vec![Action::RepayStable(asset_amount(token, amount))],
+        })
+        .unwrap();
+        self.contract_ft_transfer_call(token, user, amount, &msg)
+    }
+
+    pub fn set_e_mode(
+        &self,
+        user: &UserAccount,
+        price_data: PriceData,
+        category_id: Option<EModeCategoryId>,
+    ) -> ExecutionResult {
+        self.oracle_call(
+            user,
+            price_data,
+            PriceReceiverMsg::Execute {
+                actions: vec![Action::SetEMode { category_id }],
+            },
+        )
+    }
+
+    pub fn leverage(
+        &self,
+        user: &UserAccount,
+        token: &UserAccount,
+        price_data: PriceData,
+        target_leverage: u32,
+    ) -> ExecutionResult {
+        self.oracle_call(
+            user,
+            price_data,
+            PriceReceiverMsg::Execute {
+                actions: vec![Action::Leverage {
+                    token_id: token.account_id(),
+                    target_leverage,
+                }],
+            },
+        )
+    }
+
+    pub fn deleverage(
+        &self,
+        user: &UserAccount,
+        token: &UserAccount,
+        price_data: PriceData,
+        target_leverage: u32,
+    ) -> ExecutionResult {
+        self.oracle_call(
+            user,
+            price_data,
+            PriceReceiverMsg::Execute {
+                actions: vec![Action::Deleverage {
+                    token_id: token.account_id(),
+                    target_leverage,
+                }],
+            },
+        )
+    }
+
     pub fn withdraw(
         &self,
         user: &UserAccount,
@@ -723,6 +1315,25 @@ impl Env {
         )
     }
 
+    pub fn decrease_collateral_and_withdraw(
+        &self,
+        user: &UserAccount,
+        token: &UserAccount,
+        price_data: PriceData,
+        amount: Balance,
+    ) -> ExecutionResult {
+        self.oracle_call(
+            user,
+            price_data,
+            PriceReceiverMsg::Execute {
+                actions: vec![
+                    Action::DecreaseCollateral(asset_amount(token, amount)),
+                    Action::Withdraw(asset_amount(token, amount)),
+                ],
+            },
+        )
+    }
+
     pub fn borrow_and_withdraw(
         &self,
         user: &UserAccount,
@@ -778,6 +1389,30 @@ impl Env {
             PriceReceiverMsg::Execute {
                 actions: vec![Action::Liquidate {
                     account_id: liquidation_user.account_id(),
+                    position_id: None,
+                    in_assets,
+                    out_assets,
+                }],
+            },
+        )
+    }
+
+    pub fn liquidate_position(
+        &self,
+        user: &UserAccount,
+        liquidation_user: &UserAccount,
+        position_id: PositionId,
+        price_data: PriceData,
+        in_assets: Vec<AssetAmount>,
+        out_assets: Vec<AssetAmount>,
+    ) -> ExecutionResult {
+        self.oracle_call(
+            user,
+            price_data,
+            PriceReceiverMsg::Execute {
+                actions: vec![Action::Liquidate {
+                    account_id: liquidation_user.account_id(),
+                    position_id: Some(position_id),
                     in_assets,
                     out_assets,
                 }],
@@ -799,6 +1434,30 @@ impl Env {
             PriceReceiverMsg::Execute {
                 actions: vec![Action::LiquidateNFT {
                     account_id: liquidation_user.account_id(),
+                    position_id: None,
+                    in_assets,
+                    out_nft_assets,
+                }],
+            },
+        )
+    }
+
+    pub fn liquidate_nft_position(
+        &self,
+        user: &UserAccount,
+        liquidation_user: &UserAccount,
+        position_id: PositionId,
+        price_data: PriceData,
+        in_assets: Vec<AssetAmount>,
+        out_nft_assets: Vec<NFTAsset>,
+    ) -> ExecutionResult {
+        self.oracle_call(
+            user,
+            price_data,
+            PriceReceiverMsg::Execute {
+                actions: vec![Action::LiquidateNFT {
+                    account_id: liquidation_user.account_id(),
+                    position_id: Some(position_id),
                     in_assets,
                     out_nft_assets,
                 }],
@@ -806,6 +1465,62 @@ impl Env {
         )
     }
 
+    pub fn start_nft_auction(
+        &self,
+        user: &UserAccount,
+        liquidation_user: &UserAccount,
+        price_data: PriceData,
+        nft_asset: NFTAsset,
+    ) -> ExecutionResult {
+        self.oracle_call(
+            user,
+            price_data,
+            PriceReceiverMsg::Execute {
+                actions: vec![Action::StartNftAuction {
+                    account_id: liquidation_user.account_id(),
+                    position_id: None,
+                    nft_asset,
+                }],
+            },
+        )
+    }
+
+    pub fn buy_nft_auction(
+        &self,
+        user: &UserAccount,
+        price_data: PriceData,
+        nft_asset: NFTAsset,
+        token: &UserAccount,
+        max_amount: Option<Balance>,
+    ) -> ExecutionResult {
+        self.oracle_call(
+            user,
+            price_data,
+            PriceReceiverMsg::Execute {
+                actions: vec![Action::BuyNftAuction {
+                    nft_asset,
+                    token_id: token.account_id(),
+                    max_amount: max_amount.map(U128),
+                }],
+            },
+        )
+    }
+
+    pub fn cancel_nft_auction(
+        &self,
+        user: &UserAccount,
+        price_data: PriceData,
+        nft_asset: NFTAsset,
+    ) -> ExecutionResult {
+        self.oracle_call(
+            user,
+            price_data,
+            PriceReceiverMsg::Execute {
+                actions: vec![Action::CancelNftAuction(nft_asset)],
+            },
+        )
+    }
+
     pub fn force_close(
         &self,
         user: &UserAccount,
@@ -818,11 +1533,108 @@ impl Env {
             PriceReceiverMsg::Execute {
                 actions: vec![Action::ForceClose {
                     account_id: liquidation_user.account_id(),
+                    position_id: None,
                 }],
             },
         )
     }
 
+    pub fn open_position(&self, user: &UserAccount) -> ExecutionResult {
+        user.function_call(
+            self.contract.contract.open_position(),
+            DEFAULT_GAS.0,
+            ONE_YOCTO,
+        )
+    }
+
+    pub fn close_position(&self, user: &UserAccount, position_id: PositionId) -> ExecutionResult {
+        user.function_call(
+            self.contract.contract.close_position(position_id),
+            DEFAULT_GAS.0,
+            ONE_YOCTO,
+        )
+    }
+
+    pub fn execute_position(
+        &self,
+        user: &UserAccount,
+        position_id: PositionId,
+        actions: Vec<Action>,
+    ) -> ExecutionResult {
+        user.function_call(
+            self.contract
+                .contract
+                .execute_position(position_id, actions),
+            MAX_GAS.0,
+            ONE_YOCTO,
+        )
+    }
+
+    pub fn transfer_to_position(
+        &self,
+        user: &UserAccount,
+        token: &UserAccount,
+        from_position_id: Option<PositionId>,
+        to_position_id: Option<PositionId>,
+        amount: Balance,
+    ) -> ExecutionResult {
+        let actions = vec![Action::TransferToPosition {
+            position_id: to_position_id,
+            asset_amount: asset_amount(token, amount),
+        }];
+        match from_position_id {
+            Some(position_id) => self.execute_position(user, position_id, actions),
+            None => user.function_call(
+                self.contract.contract.execute(actions),
+                MAX_GAS.0,
+                ONE_YOCTO,
+            ),
+        }
+    }
+
+    pub fn borrow_in_position(
+        &self,
+        user: &UserAccount,
+        position_id: PositionId,
+        token: &UserAccount,
+        price_data: PriceData,
+        amount: Balance,
+    ) -> ExecutionResult {
+        self.oracle_call(
+            user,
+            price_data,
+            PriceReceiverMsg::ExecutePosition {
+                position_id,
+                actions: vec![Action::Borrow(asset_amount(token, amount))],
+            },
+        )
+    }
+
+    pub fn get_position(
+        &self,
+        user: &UserAccount,
+        position_id: PositionId,
+    ) -> Option<AccountDetailedView> {
+        self.near
+            .view_method_call(
+                self.contract
+                    .contract
+                    .get_position(user.account_id(), position_id),
+            )
+            .unwrap_json()
+    }
+
+    pub fn get_account_positions(&self, user: &UserAccount) -> AccountPositionsView {
+        self.near
+            .view_method_call(
+                self.contract
+                    .contract
+                    .get_account_positions(user.account_id()),
+            )
+            .unwrap_json::<Option<AccountPositionsView>>()
+            .unwrap()
+    }
+
     pub fn skip_time(&self, seconds: u32) {
         self.near.borrow_runtime_mut().cur_block.block_timestamp += to_nano(seconds);
     }
@@ -1084,6 +1896,18 @@ pub fn get_logs(runtime: &RuntimeStandalone) -> Vec<String> {
         .collect()
 }
 
+pub fn find_event(e: &Env, event_name: &str) -> serde_json::Value {
+    get_logs(&e.near.borrow_runtime())
+        .iter()
+        .filter(|log| log.starts_with(EVENT_JSON))
+        .map(|log| {
+            serde_json::from_str::<serde_json::Value>(&log[EVENT_JSON.len()..])
+                .expect("Failed to parse the event")
+        })
+        .find(|value| value["event"].as_str() == Some(event_name))
+        .expect("Event not found")
+}
+
 pub fn find_asset<'a>(assets: &'a [AssetView], token_id: &AccountId) -> &'a AssetView {
     let msg = format!("Missing asset: {:?}", token_id);
     assets
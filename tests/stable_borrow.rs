@@ -0,0 +1,260 @@
+mod setup;
+
+use crate::setup::*;
+
+use contract::BigDecimal;
+use near_sdk_sim::transaction::ExecutionStatus;
+
+fn enable_stable_borrow(e: &Env, tokens: &Tokens, stable_rate_rebalance_threshold: u32) {
+    let mut asset_config = e.get_asset(&tokens.ndai).config;
+    asset_config.can_borrow_stable = true;
+    asset_config.stable_rate_rebalance_threshold = stable_rate_rebalance_threshold;
+    e.update_asset(tokens.ndai.account_id(), asset_config);
+}
+
+/// The stable borrowing is disabled by default.
+#[test]
+fn test_borrow_stable_disabled() {
+    let (e, tokens, users) = basic_setup();
+
+    let supply_amount = d(100, 24);
+    e.supply_to_collateral(&users.alice, &tokens.wnear, supply_amount)
+        .assert_success();
+
+    let result = e.borrow_stable(
+        &users.alice,
+        &tokens.ndai,
+        price_data(&tokens, Some(100000), None, None),
+        d(200, 18),
+    );
+    assert!(!result.is_ok());
+
+    let asset = e.get_asset(&tokens.ndai);
+    assert_eq!(asset.stable_borrowed, 0);
+}
+
+/// Alice puts 100 NEAR and borrows 200 DAI at a stable rate (NEAR at 10$).
+#[test]
+fn test_borrow_stable() {
+    let (e, tokens, users) = basic_setup();
+    enable_stable_borrow(&e, &tokens, 0);
+
+    let supply_amount = d(100, 24);
+    e.supply_to_collateral(&users.alice, &tokens.wnear, supply_amount)
+        .assert_success();
+
+    let borrow_amount = d(200, 18);
+    e.borrow_stable(
+        &users.alice,
+        &tokens.ndai,
+        price_data(&tokens, Some(100000), None, None),
+        borrow_amount,
+    )
+    .assert_success();
+
+    let asset = e.get_asset(&tokens.ndai);
+    assert_eq!(asset.stable_borrowed, borrow_amount);
+    assert_eq!(asset.borrowed.balance, 0);
+    assert!(asset.stable_borrow_apr > BigDecimal::zero());
+    assert!(asset.supply_apr > BigDecimal::zero());
+
+    let account = e.get_account(&users.alice);
+    assert!(account.borrowed.is_empty());
+    assert_eq!(account.stable_borrowed.len(), 1);
+    assert_eq!(
+        account.stable_borrowed[0].token_id,
+        tokens.ndai.account_id()
+    );
+    assert_eq!(account.stable_borrowed[0].balance, borrow_amount);
+    assert!(account.stable_borrowed[0].apr > BigDecimal::zero());
+    assert_balances(
+        &account.supplied,
+        &[av(tokens.ndai.account_id(), borrow_amount)],
+    );
+}
+
+/// Alice borrows 200 DAI at a stable rate and repays it after a year with interest.
+#[test]
+fn test_repay_stable() {
+    let (e, tokens, users) = basic_setup();
+    enable_stable_borrow(&e, &tokens, 0);
+
+    let supply_amount = d(100, 24);
+    e.supply_to_collateral(&users.alice, &tokens.wnear, supply_amount)
+        .assert_success();
+
+    let borrow_amount = d(200, 18);
+    e.borrow_stable(
+        &users.alice,
+        &tokens.ndai,
+        price_data(&tokens, Some(100000), None, None),
+        borrow_amount,
+    )
+    .assert_success();
+
+    e.skip_time(60 * 60 * 24 * 365);
+
+    let account = e.get_account(&users.alice);
+    let debt = account.stable_borrowed[0].balance;
+    assert!(debt > borrow_amount);
+
+    e.deposit_and_repay_stable(&users.alice, &tokens.ndai, d(10, 18))
+        .assert_success();
+
+    let account = e.get_account(&users.alice);
+    assert!(account.stable_borrowed.is_empty());
+    // The total stable debt is compounded at the average rate, so it may keep some dust.
+    almost_eq(e.get_asset(&tokens.ndai).stable_borrowed, 0, 18);
+    assert!(account.supplied[0].balance < borrow_amount + d(10, 18));
+}
+
+/// Alice borrows 200 DAI at a stable rate and repays it with the `Repay` message. The amount
+/// exceeding the debt is deposited.
+#[test]
+fn test_transfer_and_repay_stable() {
+    let (e, tokens, users) = basic_setup();
+    enable_stable_borrow(&e, &tokens, 0);
+
+    e.supply_to_collateral(&users.alice, &tokens.wnear, d(100, 24))
+        .assert_success();
+    let borrow_amount = d(200, 18);
+    e.borrow_stable(
+        &users.alice,
+        &tokens.ndai,
+        price_data(&tokens, Some(100000), None, None),
+        borrow_amount,
+    )
+    .assert_success();
+
+    e.transfer_and_repay(&users.alice, &tokens.ndai, d(250, 18))
+        .assert_success();
+    find_event(&e, "repay_stable");
+
+    let account = e.get_account(&users.alice);
+    assert!(account.stable_borrowed.is_empty());
+    almost_eq(
+        find_asset(&account.supplied, &tokens.ndai.account_id()).balance,
+        borrow_amount + d(50, 18),
+        6,
+    );
+    almost_eq(e.get_asset(&tokens.ndai).stable_borrowed, 0, 18);
+}
+
+/// The owner resets the stable rate of Alice when the borrow rate moves away from it.
+#[test]
+fn test_rebalance_stable_rate() {
+    let (e, tokens, users) = basic_setup();
+    enable_stable_borrow(&e, &tokens, 100);
+
+    let supply_amount = d(100, 24);
+    e.supply_to_collateral(&users.alice, &tokens.wnear, supply_amount)
+        .assert_success();
+    e.supply_to_collateral(&users.bob, &tokens.wnear, d(10000, 24))
+        .assert_success();
+
+    e.borrow_stable(
+        &users.alice,
+        &tokens.ndai,
+        price_data(&tokens, Some(100000), None, None),
+        d(10, 18),
+    )
+    .assert_success();
+
+    // The spread is within the threshold.
+    let res = e.rebalance_stable_rate(&e.owner, &users.alice, &tokens.ndai);
+    let err = match res.status() {
+        ExecutionStatus::Failure(e) => e.to_string(),
+        _ => panic!("Should fail with the spread within the threshold"),
+    };
+    assert!(err.contains("is within the rebalance threshold"));
+
+    // Bob borrows most of the DAI reserve, so the utilization and the borrow rate go up.
+    e.borrow(
+        &users.bob,
+        &tokens.ndai,
+        price_data(&tokens, Some(100000), None, None),
+        d(9000, 18),
+    )
+    .assert_success();
+
+    let old_apr = e.get_account(&users.alice).stable_borrowed[0].apr;
+
+    let res = e.rebalance_stable_rate(&users.alice, &users.alice, &tokens.ndai);
+    assert!(!res.is_ok());
+
+    e.rebalance_stable_rate(&e.owner, &users.alice, &tokens.ndai)
+        .assert_success();
+
+    let new_apr = e.get_account(&users.alice).stable_borrowed[0].apr;
+    assert!(new_apr > old_apr);
+    let asset = e.get_asset(&tokens.ndai);
+    assert!(asset.stable_borrow_apr > old_apr);
+}
+
+/// The owner resets the stable rate of the debt in the position of Alice.
+#[test]
+fn test_rebalance_position_stable_rate() {
+    let (e, tokens, users) = basic_setup();
+    enable_stable_borrow(&e, &tokens, 100);
+
+    let position_id: PositionId = e.open_position(&users.alice).unwrap_json();
+    e.contract_ft_transfer_call(&tokens.wnear, &users.alice, d(100, 24), "")
+        .assert_success();
+    e.transfer_to_position(
+        &users.alice,
+        &tokens.wnear,
+        None,
+        Some(position_id),
+        d(100, 24),
+    )
+    .assert_success();
+    e.supply_to_collateral(&users.bob, &tokens.wnear, d(10000, 24))
+        .assert_success();
+
+    e.oracle_call(
+        &users.alice,
+        price_data(&tokens, Some(100000), None, None),
+        PriceReceiverMsg::ExecutePosition {
+            position_id,
+            actions: vec![
+                Action::IncreaseCollateral(AssetAmount {
+                    token_id: tokens.wnear.account_id(),
+                    amount: None,
+                    max_amount: None,
+                }),
+                Action::BorrowStable(asset_amount(&tokens.ndai, d(10, 18))),
+            ],
+        },
+    )
+    .assert_success();
+
+    e.borrow(
+        &users.bob,
+        &tokens.ndai,
+        price_data(&tokens, Some(100000), None, None),
+        d(9000, 18),
+    )
+    .assert_success();
+
+    let old_apr = e
+        .get_position(&users.alice, position_id)
+        .unwrap()
+        .stable_borrowed[0]
+        .apr;
+
+    // The main account of Alice doesn't have the stable debt.
+    let res = e.rebalance_stable_rate(&e.owner, &users.alice, &tokens.ndai);
+    assert!(!res.is_ok());
+
+    e.rebalance_position_stable_rate(&e.owner, &users.alice, position_id, &tokens.ndai)
+        .assert_success();
+    let event = find_event(&e, "stable_rate_rebalanced");
+    assert_eq!(event["data"][0]["position_id"].as_u64(), Some(position_id));
+
+    let new_apr = e
+        .get_position(&users.alice, position_id)
+        .unwrap()
+        .stable_borrowed[0]
+        .apr;
+    assert!(new_apr > old_apr);
+}
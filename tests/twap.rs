@@ -0,0 +1,86 @@
+mod setup;
+
+use crate::setup::*;
+
+/// Keeps the given number of the latest prices and uses the TWAP over the given duration.
+fn enable_twap(e: &Env, price_history_size: u32, twap_duration_sec: u32) {
+    let mut config = e.get_config();
+    config.price_history_size = price_history_size;
+    config.twap_duration_sec = twap_duration_sec;
+    e.update_config(config);
+}
+
+fn no_actions() -> PriceReceiverMsg {
+    PriceReceiverMsg::Execute { actions: vec![] }
+}
+
+/// A price spike doesn't affect the borrowing power, because the risk checks use the TWAP.
+#[test]
+fn test_twap_borrow() {
+    let (e, tokens, users) = basic_setup();
+    enable_twap(&e, 5, 60);
+
+    e.supply_to_collateral(&users.alice, &tokens.wnear, d(100, 24))
+        .assert_success();
+
+    e.oracle_call(
+        &users.alice,
+        price_data(&tokens, Some(100000), None, None),
+        no_actions(),
+    )
+    .assert_success();
+    e.skip_time(30);
+    e.oracle_call(
+        &users.alice,
+        price_data(&tokens, Some(100000), None, None),
+        no_actions(),
+    )
+    .assert_success();
+    e.skip_time(30);
+
+    // 800 DAI can be borrowed at 20$ per NEAR, but not at 10$ per NEAR.
+    let result = e.borrow(
+        &users.alice,
+        &tokens.ndai,
+        price_data(&tokens, Some(200000), None, None),
+        d(800, 18),
+    );
+    assert!(!result.is_ok());
+
+    let twap_price = e.get_twap_price(&tokens.wnear).unwrap();
+    assert_eq!(twap_price.multiplier, 100000);
+    assert_eq!(twap_price.decimals, 28);
+
+    e.borrow(
+        &users.alice,
+        &tokens.ndai,
+        price_data(&tokens, Some(200000), None, None),
+        d(500, 18),
+    )
+    .assert_success();
+}
+
+/// Only the latest prices are kept in the history.
+#[test]
+fn test_price_history_size() {
+    let (e, tokens, users) = basic_setup();
+    enable_twap(&e, 3, 0);
+
+    for i in 1..=5 {
+        e.oracle_call(
+            &users.alice,
+            price_data(&tokens, Some(100000 * i), None, None),
+            no_actions(),
+        )
+        .assert_success();
+        e.skip_time(10);
+    }
+
+    let price_history = e.get_price_history(&tokens.wnear);
+    let multipliers: Vec<u128> = price_history
+        .iter()
+        .map(|point| point.price.multiplier)
+        .collect();
+    assert_eq!(multipliers, vec![300000, 400000, 500000]);
+    assert!(price_history[0].timestamp < price_history[1].timestamp);
+}